@@ -3,12 +3,12 @@ import cors from 'cors';
 import fs from 'fs/promises';
 import path from 'path';
 import os from 'os';
-import { existsSync, statSync } from 'fs';
+import { existsSync } from 'fs';
 import { GoogleGenerativeAI, SchemaType } from '@google/generative-ai';
 import dotenv from 'dotenv';
 import { execSync } from 'child_process';
-import { lookup } from 'mime-types';
 import rateLimit from 'express-rate-limit';
+import { timingSafeEqual } from 'crypto';
 
 // Load environment variables
 dotenv.config();
@@ -20,22 +20,6 @@ let settings = {
     multimediaSupport: false
 };
 
-const resolvedHomeDir = path.resolve(os.homedir());
-
-function isPathAllowed(p: string | undefined, allowRoot: boolean, currentPath?: string): boolean {
-    if (allowRoot) return true;
-    if (!p) return true; 
-
-    let resolvedPath: string;
-    if (currentPath && !path.isAbsolute(p)) {
-        resolvedPath = path.resolve(currentPath, p);
-    } else {
-        resolvedPath = path.resolve(p);
-    }
-
-    return resolvedPath.startsWith(resolvedHomeDir);
-}
-
 function getAppDataPath(): string {
     switch (os.platform()) {
         case 'win32':
@@ -103,19 +87,30 @@ let GEMINI_API_KEY = '';
 
 const app = express();
 
+// Per-launch secret handed to us by the Tauri side; only the app's webview knows it
+const AUTH_TOKEN = process.env.FSAI_AUTH_TOKEN || '';
+const AUTH_HEADER = 'x-fsai-token';
+
+function isAuthorized(req: express.Request): boolean {
+    const provided = req.header(AUTH_HEADER);
+    if (!AUTH_TOKEN || typeof provided !== 'string') return false;
+
+    const expected = Buffer.from(AUTH_TOKEN);
+    const actual = Buffer.from(provided);
+    return expected.length === actual.length && timingSafeEqual(expected, actual);
+}
+
 // Middleware
 app.use(cors());
+app.use((req, res, next) => {
+    if (!isAuthorized(req)) {
+        return res.status(401).json(createResponse(false, null, 'Unauthorized'));
+    }
+    next();
+});
 app.use(express.json({ limit: '50mb' }));
 
 // Rate limiting because my client code sucks occasionally
-const aiLimiter = rateLimit({
-	windowMs: 15 * 60 * 1000, 
-	max: 100, 
-	standardHeaders: true,
-	legacyHeaders: false,
-  message: 'Too many requests from this IP, please try again after 15 minutes'
-});
-
 const strictLimiter = rateLimit({
   windowMs: 1 * 60 * 1000, 
   max: 15, 
@@ -170,9 +165,13 @@ interface ToolCall {
     sourcePath?: string;
     destinationPath?: string;
     newName?: string;
+    startLine?: number;
+    lineCount?: number;
+    expectedHash?: string;
+    createOnly?: boolean;
+    maxDepth?: number;
   };
   description: string;
-  risk: 'low' | 'high';
 }
 
 interface AIRequest {
@@ -209,6 +208,14 @@ const readFileFunction = {
       path: {
         type: SchemaType.STRING,
         description: 'The full path to the file to read'
+      },
+      startLine: {
+        type: SchemaType.NUMBER,
+        description: 'Optional 1-based line to start reading from. Use it to page through large files.'
+      },
+      lineCount: {
+        type: SchemaType.NUMBER,
+        description: 'Optional number of lines to read, starting at startLine'
       }
     },
     required: ['path']
@@ -243,6 +250,14 @@ const writeFileFunction = {
       content: {
         type: SchemaType.STRING,
         description: 'The content to write to the file.'
+      },
+      expectedHash: {
+        type: SchemaType.STRING,
+        description: 'The hash returned by read_file for this file. When editing an existing file, pass it so the write is refused if the file changed in the meantime.'
+      },
+      createOnly: {
+        type: SchemaType.BOOLEAN,
+        description: 'Set to true when creating a new file, so an existing file with the same name is never overwritten.'
       }
     },
     required: ['path', 'content']
@@ -292,6 +307,10 @@ const getTreeFunction = {
       path: {
         type: SchemaType.STRING,
         description: 'The full path of the directory to get the tree for.'
+      },
+      maxDepth: {
+        type: SchemaType.NUMBER,
+        description: 'Optional number of levels to expand below the directory (default 5). Deeper folders are shown as [...].'
       }
     },
     required: ['path']
@@ -353,7 +372,7 @@ const renameFileFunction = {
 
 const deleteItemFunction = {
   name: 'delete_item',
-  description: 'Deletes a file or directory at the specified path.',
+  description: 'Deletes a file or directory at the specified path. Where supported the item is moved to the trash, so the user can restore it.',
   parameters: {
     type: SchemaType.OBJECT,
     properties: {
@@ -368,13 +387,13 @@ const deleteItemFunction = {
 
 const copyFileFunction = {
   name: 'copy_file',
-  description: 'Copies a file from a source path to a destination path.',
+  description: 'Copies a file or directory from a source path to a destination path. If something already exists at the destination, the copy is saved next to it under a new name.',
   parameters: {
     type: SchemaType.OBJECT,
     properties: {
       path: {
         type: SchemaType.STRING,
-        description: 'The full path to the file to copy.'
+        description: 'The full path to the file or directory to copy.'
       },
       destinationPath: {
         type: SchemaType.STRING,
@@ -389,26 +408,6 @@ function generateToolCallId(): string {
   return 'tc_' + Math.random().toString(36).substring(2, 11);
 }
 
-function determineRisk(toolType: string, parameters: any): 'low' | 'high' {
-  switch (toolType) {
-    case 'read_file':
-    case 'read_directory':
-    case 'get_tree':
-    case 'navigate_user':
-    case 'process_file':
-    case 'create_directory':
-    case 'copy_file':
-      return 'low';
-    case 'write_file':
-    case 'delete_item':
-    case 'move_item':
-    case 'rename_file':
-      return 'high';
-    default:
-      return 'high';
-  }
-}
-
 function truncateChatHistory(messages: Array<{type: string, content: string, timestamp: Date}>, maxTokens: number = 2000): Array<{type: string, content: string, timestamp: Date}> {
   if (!messages || messages.length === 0) return [];
   
@@ -528,8 +527,7 @@ Keep responses brief and focused unless detailed explanation is requested.`;
           id: generateToolCallId(),
           type: functionCall.name as any,
           parameters: functionCall.args as any,
-          description: description,
-          risk: determineRisk(functionCall.name, functionCall.args)
+          description: description
         };
         toolCalls.push(toolCall);
       }
@@ -545,32 +543,6 @@ Keep responses brief and focused unless detailed explanation is requested.`;
   }
 }
 
-async function generateTree(dirPath: string, prefix = ''): Promise<string> {
-    const entries = await fs.readdir(dirPath, { withFileTypes: true });
-    entries.sort((a, b) => {
-        if (a.isDirectory() && !b.isDirectory()) return -1;
-        if (!a.isDirectory() && b.isDirectory()) return 1;
-        return a.name.localeCompare(b.name);
-    });
-
-    let treeString = '';
-    for (let i = 0; i < entries.length; i++) {
-        const entry = entries[i];
-        const isLast = i === entries.length - 1;
-        const connector = isLast ? '└── ' : '├── ';
-        treeString += `${prefix}${connector}${entry.name}\n`;
-        if (entry.isDirectory()) {
-            const newPrefix = prefix + (isLast ? '    ' : '│   ');
-            try {
-                treeString += await generateTree(path.join(dirPath, entry.name), newPrefix);
-            } catch (error) {
-                treeString += `${newPrefix}└── [error reading directory]\n`;
-            }
-        }
-    }
-    return treeString;
-}
-
 async function processFollowUpWithAI(originalPrompt: string, context: AIContext, toolResults: any): Promise<AIResponse> {
   try {
     if (!genAI) {
@@ -732,8 +704,7 @@ Now, analyze the results. If the original request is fully addressed, provide a
           id: generateToolCallId(),
           type: functionCall.name as any,
           parameters: functionCall.args as any,
-          description: description,
-          risk: determineRisk(functionCall.name, functionCall.args)
+          description: description
         };
         toolCalls.push(toolCall);
       }
@@ -788,304 +759,6 @@ app.get('/api/fs/home', (req, res) => {
   }
 });
 
-app.post('/api/fs/list', async (req, res) => {
-  try {
-    const { path: dirPath } = req.body;
-    
-    if (!dirPath || typeof dirPath !== 'string') {
-      return res.json(createResponse(false, null, 'Path is required'));
-    }
-
-    if (!isPathAllowed(dirPath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to path '${dirPath}' is disallowed by settings.`));
-    }
-
-    const normalizedPath = path.resolve(dirPath);
-    
-    if (!existsSync(normalizedPath)) {
-      return res.json(createResponse(false, null, 'Directory does not exist'));
-    }
-
-    const stat = statSync(normalizedPath);
-    if (!stat.isDirectory()) {
-      return res.json(createResponse(false, null, 'Path is not a directory'));
-    }
-
-    const entries = await fs.readdir(normalizedPath);
-    const files: FileItem[] = [];
-
-    for (const entry of entries) {
-      try {
-        const fullPath = path.join(normalizedPath, entry);
-        const entryStat = statSync(fullPath);
-        
-        files.push({
-          name: entry,
-          isDirectory: entryStat.isDirectory(),
-          isFile: entryStat.isFile(),
-          path: fullPath
-        });
-      } catch (e) {
-        console.warn(`Could not read entry ${entry}:`, e);
-      }
-    }
-
-    files.sort((a, b) => {
-      if (a.isDirectory && !b.isDirectory) return -1;
-      if (!a.isDirectory && b.isDirectory) return 1;
-      return a.name.localeCompare(b.name);
-    });
-
-    res.json(createResponse(true, files));
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to list directory: ${error}`));
-  }
-});
-
-// Read file content
-app.post('/api/fs/read', async (req, res) => {
-  try {
-    const { path: filePath } = req.body;
-    
-    if (!filePath || typeof filePath !== 'string') {
-      return res.json(createResponse(false, null, 'Path is required'));
-    }
-
-    if (!isPathAllowed(filePath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to path '${filePath}' is disallowed by settings.`));
-    }
-
-    const normalizedPath = path.resolve(filePath);
-    
-    if (!existsSync(normalizedPath)) {
-      return res.json(createResponse(false, null, 'File does not exist'));
-    }
-
-    const stat = statSync(normalizedPath);
-    if (!stat.isFile()) {
-      return res.json(createResponse(false, null, 'Path is not a file'));
-    }
-
-    const content = await fs.readFile(normalizedPath, 'utf8');
-    res.json(createResponse(true, { content, path: normalizedPath }));
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to read file: ${error}`));
-  }
-});
-
-// Write file content
-app.post('/api/fs/write', async (req, res) => {
-  try {
-    const { path: filePath, content } = req.body;
-    
-    if (!filePath || typeof filePath !== 'string') {
-      return res.json(createResponse(false, null, 'Path is required'));
-    }
-
-    if (!isPathAllowed(filePath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to path '${filePath}' is disallowed by settings.`));
-    }
-
-    if (typeof content !== 'string') {
-      return res.json(createResponse(false, null, 'Content must be a string'));
-    }
-
-    const normalizedPath = path.resolve(filePath);
-    
-    const dir = path.dirname(normalizedPath);
-    await fs.mkdir(dir, { recursive: true });
-
-    await fs.writeFile(normalizedPath, content, 'utf8');
-    res.json(createResponse(true, { message: 'File written successfully', path: normalizedPath }));
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to write file: ${error}`));
-  }
-});
-
-// Delete file or directory
-app.post('/api/fs/delete', async (req, res) => {
-  try {
-    const { path: targetPath } = req.body;
-    
-    if (!targetPath || typeof targetPath !== 'string') {
-      return res.json(createResponse(false, null, 'Path is required'));
-    }
-
-    if (!isPathAllowed(targetPath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to path '${targetPath}' is disallowed by settings.`));
-    }
-
-    const normalizedPath = path.resolve(targetPath);
-    
-    if (!existsSync(normalizedPath)) {
-      return res.json(createResponse(false, null, 'File or directory does not exist'));
-    }
-
-    const stat = statSync(normalizedPath);
-    
-    if (stat.isDirectory()) {
-      await fs.rmdir(normalizedPath, { recursive: true });
-      res.json(createResponse(true, { message: 'Directory deleted successfully', path: normalizedPath }));
-    } else {
-      await fs.unlink(normalizedPath);
-      res.json(createResponse(true, { message: 'File deleted successfully', path: normalizedPath }));
-    }
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to delete: ${error}`));
-  }
-});
-
-// Rename file or directory
-app.post('/api/fs/rename', async (req, res) => {
-  try {
-    const { path: targetPath, newName } = req.body;
-    
-    if (!targetPath || typeof targetPath !== 'string') {
-      return res.json(createResponse(false, null, 'Path is required'));
-    }
-
-    if (!newName || typeof newName !== 'string') {
-      return res.json(createResponse(false, null, 'New name is required'));
-    }
-
-    if (!isPathAllowed(targetPath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to path '${targetPath}' is disallowed by settings.`));
-    }
-
-    const normalizedPath = path.resolve(targetPath);
-    
-    if (!existsSync(normalizedPath)) {
-      return res.json(createResponse(false, null, 'File or directory does not exist'));
-    }
-
-    const parentDir = path.dirname(normalizedPath);
-    const newPath = path.join(parentDir, newName);
-
-    if (existsSync(newPath)) {
-      return res.json(createResponse(false, null, `A file or directory with the name '${newName}' already exists`));
-    }
-
-    await fs.rename(normalizedPath, newPath);
-    res.json(createResponse(true, { message: `Renamed '${path.basename(normalizedPath)}' to '${newName}'`, path: newPath }));
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to rename: ${error}`));
-  }
-});
-
-// Copy file
-app.post('/api/fs/copy', async (req, res) => {
-  try {
-    const { path: sourcePath, destinationPath } = req.body;
-    
-    if (!sourcePath || typeof sourcePath !== 'string') {
-      return res.json(createResponse(false, null, 'Source path is required'));
-    }
-
-    if (!destinationPath || typeof destinationPath !== 'string') {
-      return res.json(createResponse(false, null, 'Destination path is required'));
-    }
-
-    if (!isPathAllowed(sourcePath, settings.allowRootAccess) || !isPathAllowed(destinationPath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to one or more paths is disallowed by settings.`));
-    }
-
-    const normalizedSourcePath = path.resolve(sourcePath);
-    const normalizedDestinationPath = path.resolve(destinationPath);
-    
-    if (!existsSync(normalizedSourcePath)) {
-      return res.json(createResponse(false, null, 'Source file does not exist'));
-    }
-
-    const sourceStat = statSync(normalizedSourcePath);
-    if (!sourceStat.isFile()) {
-      return res.json(createResponse(false, null, 'Source path is not a file. Only files can be copied.'));
-    }
-
-    let finalDestinationPath = normalizedDestinationPath;
-    if (existsSync(normalizedDestinationPath) && statSync(normalizedDestinationPath).isDirectory()) {
-      finalDestinationPath = path.join(normalizedDestinationPath, path.basename(normalizedSourcePath));
-    }
-
-    const destDir = path.dirname(finalDestinationPath);
-    await fs.mkdir(destDir, { recursive: true });
-
-    await fs.copyFile(normalizedSourcePath, finalDestinationPath);
-    res.json(createResponse(true, { message: `File copied from '${normalizedSourcePath}' to '${finalDestinationPath}'`, path: finalDestinationPath }));
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to copy file: ${error}`));
-  }
-});
-
-// Check if a file is text or binary
-app.post('/api/fs/check-type', async (req, res) => {
-  try {
-    const { path: filePath } = req.body;
-
-    if (!filePath || typeof filePath !== 'string') {
-      return res.json(createResponse(false, null, 'Path is required'));
-    }
-
-    if (!isPathAllowed(filePath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to path '${filePath}' is disallowed by settings.`));
-    }
-
-    const normalizedPath = path.resolve(filePath);
-
-    if (!existsSync(normalizedPath)) {
-      return res.json(createResponse(false, { isText: false, reason: 'File does not exist' }, 'File does not exist'));
-    }
-
-    const stat = statSync(normalizedPath);
-    if (!stat.isFile()) {
-      return res.json(createResponse(false, { isText: false, reason: 'Path is not a file' }, 'Path is not a file'));
-    }
-
-    const buffer = Buffer.alloc(512);
-    const fd = await fs.open(normalizedPath, 'r');
-    const { bytesRead } = await fd.read(buffer, 0, 512, 0);
-    await fd.close();
-
-    const chunk = buffer.slice(0, bytesRead);
-    let isText = true;
-    for (let i = 0; i < chunk.length; i++) {
-        if (chunk[i] === 0) {
-            isText = false;
-            break;
-        }
-    }
-
-    res.json(createResponse(true, { isText }));
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to check file type: ${error}`));
-  }
-});
-
-// Create directory
-app.post('/api/fs/mkdir', async (req, res) => {
-  try {
-    const { path: dirPath } = req.body;
-    
-    if (!dirPath || typeof dirPath !== 'string') {
-      return res.json(createResponse(false, null, 'Path is required'));
-    }
-
-    if (!isPathAllowed(dirPath, settings.allowRootAccess)) {
-        return res.json(createResponse(false, null, `Access to path '${dirPath}' is disallowed by settings.`));
-    }
-
-    const normalizedPath = path.resolve(dirPath);
-    
-    if (existsSync(normalizedPath)) {
-      return res.json(createResponse(false, null, 'Directory already exists'));
-    }
-
-    await fs.mkdir(normalizedPath, { recursive: true });
-    res.json(createResponse(true, { message: 'Directory created successfully', path: normalizedPath }));
-  } catch (error) {
-    res.json(createResponse(false, null, `Failed to create directory: ${error}`));
-  }
-});
-
 // AI processing endpoint
 app.post('/api/ai/process', strictLimiter, async (req, res) => {
   try {
@@ -1106,473 +779,6 @@ app.post('/api/ai/process', strictLimiter, async (req, res) => {
   }
 });
 
-// Execute tool call endpoint (for confirmed tool calls)
-app.post('/api/ai/execute-tool', aiLimiter, async (req, res) => {
-  try {
-    const { toolCall, context }: { toolCall: ToolCall; context?: AIContext } = req.body;
-    
-    if (!toolCall || !toolCall.id || !toolCall.type) {
-      return res.json(createResponse(false, null, 'Valid toolCall is required'));
-    }
-
-    const allowRootAccess = context?.settings?.allowRootAccess ?? settings.allowRootAccess;
-
-    switch (toolCall.type) {
-      case 'read_file':
-        if (!toolCall.parameters.path) {
-          return res.json(createResponse(false, null, 'Path parameter is required for read_file'));
-        }
-        if (!isPathAllowed(toolCall.parameters.path, allowRootAccess, context?.currentPath)) {
-          return res.json(createResponse(false, null, `Access to path '${toolCall.parameters.path}' is disallowed.`));
-        }
-        
-        let normalizedPath: string;
-        if (context?.currentPath && !path.isAbsolute(toolCall.parameters.path)) {
-          normalizedPath = path.resolve(context.currentPath, toolCall.parameters.path);
-        } else {
-          normalizedPath = path.resolve(toolCall.parameters.path);
-        }
-        
-        if (!existsSync(normalizedPath)) {
-          return res.json(createResponse(false, null, `File does not exist: ${normalizedPath}`));
-        }
-
-        const stat = statSync(normalizedPath);
-        if (!stat.isFile()) {
-          return res.json(createResponse(false, null, 'Path is not a file'));
-        }
-
-        const content = await fs.readFile(normalizedPath, 'utf8');
-        res.json(createResponse(true, { 
-          toolCallId: toolCall.id,
-          result: { content, path: normalizedPath },
-          message: 'File read successfully'
-        }));
-        break;
-        
-      case 'read_directory':
-        if (!toolCall.parameters.path) {
-            return res.json(createResponse(false, null, 'Path parameter is required for read_directory'));
-        }
-        if (!isPathAllowed(toolCall.parameters.path, allowRootAccess, context?.currentPath)) {
-            return res.json(createResponse(false, null, `Access to path '${toolCall.parameters.path}' is disallowed.`));
-        }
-
-        const dirPath = toolCall.parameters.path;
-        let normalizedDirPath: string;
-        if (context?.currentPath && !path.isAbsolute(dirPath)) {
-          normalizedDirPath = path.resolve(context.currentPath, dirPath);
-        } else {
-          normalizedDirPath = path.resolve(dirPath);
-        }
-
-        if (!existsSync(normalizedDirPath)) {
-            return res.json(createResponse(false, null, `Directory does not exist: ${normalizedDirPath}`));
-        }
-
-        const dirStat = statSync(normalizedDirPath);
-        if (!dirStat.isDirectory()) {
-            return res.json(createResponse(false, null, 'Path is not a directory'));
-        }
-
-        const entries = await fs.readdir(normalizedDirPath);
-        const files: FileItem[] = [];
-
-        for (const entry of entries) {
-            try {
-                const fullPath = path.join(normalizedDirPath, entry);
-                const entryStat = statSync(fullPath);
-                
-                files.push({
-                    name: entry,
-                    isDirectory: entryStat.isDirectory(),
-                    isFile: entryStat.isFile(),
-                    path: fullPath
-                });
-            } catch (e) {
-                console.warn(`Could not read entry ${entry}:`, e);
-            }
-        }
-
-        files.sort((a, b) => {
-            if (a.isDirectory && !b.isDirectory) return -1;
-            if (!a.isDirectory && b.isDirectory) return 1;
-            return a.name.localeCompare(b.name);
-        });
-        
-        res.json(createResponse(true, {
-            toolCallId: toolCall.id,
-            result: { files, path: normalizedDirPath },
-            message: 'Directory listed successfully'
-        }));
-        break;
-
-      case 'get_tree': {
-        if (!toolCall.parameters.path) {
-          return res.json(createResponse(false, null, 'Path parameter is required for get_tree'));
-        }
-        if (!isPathAllowed(toolCall.parameters.path, allowRootAccess, context?.currentPath)) {
-          return res.json(createResponse(false, null, `Access to path '${toolCall.parameters.path}' is disallowed.`));
-        }
-
-        const treeDirPath = toolCall.parameters.path;
-        let normalizedTreePath: string;
-        if (context?.currentPath && !path.isAbsolute(treeDirPath)) {
-          normalizedTreePath = path.resolve(context.currentPath, treeDirPath);
-        } else {
-          normalizedTreePath = path.resolve(treeDirPath);
-        }
-
-        if (!existsSync(normalizedTreePath)) {
-          return res.json(createResponse(false, null, `Directory does not exist: ${normalizedTreePath}`));
-        }
-
-        const treeDirStat = statSync(normalizedTreePath);
-        if (!treeDirStat.isDirectory()) {
-          return res.json(createResponse(false, null, 'Path is not a directory'));
-        }
-
-        const tree = await generateTree(normalizedTreePath);
-        res.json(createResponse(true, {
-          toolCallId: toolCall.id,
-          result: { tree, path: normalizedTreePath },
-          message: 'Directory tree generated successfully'
-        }));
-        break;
-      }
-
-      case 'move_item': {
-        const { sourcePath, destinationPath } = toolCall.parameters;
-
-        if (!sourcePath || !destinationPath) {
-          return res.json(createResponse(false, null, 'sourcePath and destinationPath are required for move_item'));
-        }
-        if (!isPathAllowed(sourcePath, allowRootAccess, context?.currentPath) || !isPathAllowed(destinationPath, allowRootAccess, context?.currentPath)) {
-            return res.json(createResponse(false, null, `Access to one or more paths is disallowed.`));
-        }
-
-        let normalizedSourcePath: string;
-        if (context?.currentPath && !path.isAbsolute(sourcePath)) {
-          normalizedSourcePath = path.resolve(context.currentPath, sourcePath);
-        } else {
-          normalizedSourcePath = path.resolve(sourcePath);
-        }
-
-        let normalizedDestinationPath: string;
-        if (context?.currentPath && !path.isAbsolute(destinationPath)) {
-          normalizedDestinationPath = path.resolve(context.currentPath, destinationPath);
-        } else {
-          normalizedDestinationPath = path.resolve(destinationPath);
-        }
-        
-        if (!existsSync(normalizedSourcePath)) {
-          return res.json(createResponse(false, null, `Source path does not exist: ${normalizedSourcePath}`));
-        }
-
-        let finalDestinationPath = normalizedDestinationPath;
-        if (existsSync(normalizedDestinationPath) && statSync(normalizedDestinationPath).isDirectory()) {
-            finalDestinationPath = path.join(normalizedDestinationPath, path.basename(normalizedSourcePath));
-        }
-        
-        await fs.rename(normalizedSourcePath, finalDestinationPath);
-
-        res.json(createResponse(true, {
-          toolCallId: toolCall.id,
-          result: { message: `Moved '${normalizedSourcePath}' to '${finalDestinationPath}'` },
-          message: 'Item moved successfully'
-        }));
-        break;
-      }
-
-      case 'create_directory': {
-        const { path: dirPath, name: folderName } = toolCall.parameters;
-
-        if (!dirPath || !folderName) {
-          return res.json(createResponse(false, null, 'path and name parameters are required for create_directory'));
-        }
-        if (!isPathAllowed(dirPath, allowRootAccess, context?.currentPath)) {
-            return res.json(createResponse(false, null, `Access to path '${dirPath}' is disallowed.`));
-        }
-
-        let normalizedPath: string;
-        if (context?.currentPath && !path.isAbsolute(dirPath)) {
-          normalizedPath = path.resolve(context.currentPath, dirPath, folderName);
-        } else {
-          normalizedPath = path.resolve(dirPath, folderName);
-        }
-        
-        if (existsSync(normalizedPath)) {
-          return res.json(createResponse(false, null, `Directory already exists: ${normalizedPath}`));
-        }
-
-        await fs.mkdir(normalizedPath, { recursive: true });
-        res.json(createResponse(true, {
-          toolCallId: toolCall.id,
-          result: { message: `Directory '${folderName}' created at '${dirPath}'` },
-          message: 'Directory created successfully'
-        }));
-        break;
-      }
-
-      case 'write_file': {
-        const { path: filePath, content: fileContent } = toolCall.parameters;
-        if (!filePath || fileContent === undefined) {
-          return res.json(createResponse(false, null, 'Path and content parameters are required for write_file'));
-        }
-        if (!isPathAllowed(filePath, allowRootAccess, context?.currentPath)) {
-            return res.json(createResponse(false, null, `Access to path '${filePath}' is disallowed.`));
-        }
-
-        let resolvedPath: string;
-        if (context?.currentPath && !path.isAbsolute(filePath)) {
-          resolvedPath = path.resolve(context.currentPath, filePath);
-        } else {
-          resolvedPath = path.resolve(filePath);
-        }
-
-        const dir = path.dirname(resolvedPath);
-        await fs.mkdir(dir, { recursive: true });
-
-        await fs.writeFile(resolvedPath, fileContent, 'utf8');
-
-        res.json(createResponse(true, {
-            toolCallId: toolCall.id,
-            result: { message: `File written successfully to ${resolvedPath}` },
-            message: 'File written successfully'
-        }));
-        break;
-      }
-
-      case 'process_file': {
-        if (!toolCall.parameters.path) {
-          return res.json(createResponse(false, null, 'Path parameter is required for process_file'));
-        }
-        if (!isPathAllowed(toolCall.parameters.path, allowRootAccess, context?.currentPath)) {
-          return res.json(createResponse(false, null, `Access to path '${toolCall.parameters.path}' is disallowed.`));
-        }
-
-        let normalizedPath: string;
-        if (context?.currentPath && !path.isAbsolute(toolCall.parameters.path)) {
-          normalizedPath = path.resolve(context.currentPath, toolCall.parameters.path);
-        } else {
-          normalizedPath = path.resolve(toolCall.parameters.path);
-        }
-
-        if (!existsSync(normalizedPath)) {
-          return res.json(createResponse(false, null, `File does not exist: ${normalizedPath}`));
-        }
-        
-        const stat = statSync(normalizedPath);
-        if (!stat.isFile()) {
-          return res.json(createResponse(false, null, 'Path is not a file'));
-        }
-
-        const mimeType = lookup(normalizedPath) || 'application/octet-stream';
-
-        const supportedImageMimes = ['image/png', 'image/jpeg', 'image/webp'];
-        const supportedPdfMimes = ['application/pdf'];
-        const supportedVideoMimes = ['video/x-flv', 'video/quicktime', 'video/mpeg', 'video/mp4', 'application/mp4', 'video/webm', 'video/wmv', 'video/3gpp']; // application mp4 is a true mac moment
-        const isSupportedImage = supportedImageMimes.includes(mimeType);
-        const isSupportedPdf = supportedPdfMimes.includes(mimeType);
-        const isSupportedVideo = supportedVideoMimes.includes(mimeType);
-
-        if (!isSupportedImage && !isSupportedPdf && !isSupportedVideo) {
-          return res.json(createResponse(false, null, `Unsupported file type: ${mimeType}. This tool supports images (PNG, JPEG, WEBP), PDFs, and videos.`));
-        }
-
-        const MAX_IMAGE_PDF_SIZE = 7 * 1024 * 1024; // 7 MB
-        const MAX_VIDEO_SIZE = 45 * 1024 * 1024; // 45 MB
-
-        if ((isSupportedImage || isSupportedPdf) && stat.size > MAX_IMAGE_PDF_SIZE) {
-          return res.json(createResponse(false, null, `File size (${(stat.size / 1024 / 1024).toFixed(2)} MB) exceeds the 7 MB limit for images and PDFs.`));
-        }
-        if (isSupportedVideo && stat.size > MAX_VIDEO_SIZE) {
-          return res.json(createResponse(false, null, `File size (${(stat.size / 1024 / 1024).toFixed(2)} MB) exceeds the 45 MB limit for videos.`));
-        }
-
-        const data = await fs.readFile(normalizedPath, 'base64');
-
-        res.json(createResponse(true, { 
-          toolCallId: toolCall.id,
-          result: { path: normalizedPath, mimeType, data, isFile: true },
-          message: 'File processed successfully'
-        }));
-        break;
-      }
-
-      case 'navigate_user': {
-        if (!toolCall.parameters.path) {
-          return res.json(createResponse(false, null, 'Path parameter is required for navigate_user'));
-        }
-        if (!isPathAllowed(toolCall.parameters.path, allowRootAccess, context?.currentPath)) {
-          return res.json(createResponse(false, null, `Access to path '${toolCall.parameters.path}' is disallowed.`));
-        }
-
-        let navigationPath: string;
-        if (context?.currentPath && !path.isAbsolute(toolCall.parameters.path)) {
-          navigationPath = path.resolve(context.currentPath, toolCall.parameters.path);
-        } else {
-          navigationPath = path.resolve(toolCall.parameters.path);
-        }
-
-        if (!existsSync(navigationPath)) {
-          return res.json(createResponse(false, null, `Path does not exist: ${navigationPath}`));
-        }
-
-        const navigationStat = statSync(navigationPath);
-        if (!navigationStat.isDirectory()) {
-          return res.json(createResponse(false, null, 'Path is not a directory'));
-        }
-
-        res.json(createResponse(true, {
-          toolCallId: toolCall.id,
-          result: { 
-            navigationPath: navigationPath, 
-            message: `Navigate to: ${navigationPath}` 
-          },
-          message: 'Navigation path validated successfully'
-        }));
-        break;
-      }
-
-      case 'rename_file': {
-        const { path: filePath, newName } = toolCall.parameters;
-
-        if (!filePath || !newName) {
-          return res.json(createResponse(false, null, 'path and newName parameters are required for rename_file'));
-        }
-        if (!isPathAllowed(filePath, allowRootAccess, context?.currentPath)) {
-          return res.json(createResponse(false, null, `Access to path '${filePath}' is disallowed.`));
-        }
-
-        let normalizedPath: string;
-        if (context?.currentPath && !path.isAbsolute(filePath)) {
-          normalizedPath = path.resolve(context.currentPath, filePath);
-        } else {
-          normalizedPath = path.resolve(filePath);
-        }
-
-        if (!existsSync(normalizedPath)) {
-          return res.json(createResponse(false, null, `File or directory does not exist: ${normalizedPath}`));
-        }
-
-        const parentDir = path.dirname(normalizedPath);
-        const newPath = path.join(parentDir, newName);
-
-        if (existsSync(newPath)) {
-          return res.json(createResponse(false, null, `A file or directory with the name '${newName}' already exists`));
-        }
-
-        await fs.rename(normalizedPath, newPath);
-
-        res.json(createResponse(true, {
-          toolCallId: toolCall.id,
-          result: { message: `Renamed '${path.basename(normalizedPath)}' to '${newName}'` },
-          message: 'Item renamed successfully'
-        }));
-        break;
-      }
-
-      case 'delete_item': {
-        const { path: filePath } = toolCall.parameters;
-
-        if (!filePath) {
-          return res.json(createResponse(false, null, 'path parameter is required for delete_item'));
-        }
-        if (!isPathAllowed(filePath, allowRootAccess, context?.currentPath)) {
-          return res.json(createResponse(false, null, `Access to path '${filePath}' is disallowed.`));
-        }
-
-        let normalizedPath: string;
-        if (context?.currentPath && !path.isAbsolute(filePath)) {
-          normalizedPath = path.resolve(context.currentPath, filePath);
-        } else {
-          normalizedPath = path.resolve(filePath);
-        }
-
-        if (!existsSync(normalizedPath)) {
-          return res.json(createResponse(false, null, `File or directory does not exist: ${normalizedPath}`));
-        }
-
-        const stat = statSync(normalizedPath);
-        
-        if (stat.isDirectory()) {
-          await fs.rmdir(normalizedPath, { recursive: true });
-          res.json(createResponse(true, {
-            toolCallId: toolCall.id,
-            result: { message: `Directory '${path.basename(normalizedPath)}' deleted successfully` },
-            message: 'Directory deleted successfully'
-          }));
-        } else {
-          await fs.unlink(normalizedPath);
-          res.json(createResponse(true, {
-            toolCallId: toolCall.id,
-            result: { message: `File '${path.basename(normalizedPath)}' deleted successfully` },
-            message: 'File deleted successfully'
-          }));
-        }
-        break;
-      }
-
-      case 'copy_file': {
-        const { path: sourcePath, destinationPath } = toolCall.parameters;
-
-        if (!sourcePath || !destinationPath) {
-          return res.json(createResponse(false, null, 'path and destinationPath parameters are required for copy_file'));
-        }
-        if (!isPathAllowed(sourcePath, allowRootAccess, context?.currentPath) || !isPathAllowed(destinationPath, allowRootAccess, context?.currentPath)) {
-          return res.json(createResponse(false, null, `Access to one or more paths is disallowed.`));
-        }
-
-        let normalizedSourcePath: string;
-        if (context?.currentPath && !path.isAbsolute(sourcePath)) {
-          normalizedSourcePath = path.resolve(context.currentPath, sourcePath);
-        } else {
-          normalizedSourcePath = path.resolve(sourcePath);
-        }
-
-        let normalizedDestinationPath: string;
-        if (context?.currentPath && !path.isAbsolute(destinationPath)) {
-          normalizedDestinationPath = path.resolve(context.currentPath, destinationPath);
-        } else {
-          normalizedDestinationPath = path.resolve(destinationPath);
-        }
-
-        if (!existsSync(normalizedSourcePath)) {
-          return res.json(createResponse(false, null, `Source file does not exist: ${normalizedSourcePath}`));
-        }
-
-        const sourceStat = statSync(normalizedSourcePath);
-        if (!sourceStat.isFile()) {
-          return res.json(createResponse(false, null, 'Source path is not a file. Only files can be copied with this tool.'));
-        }
-
-        let finalDestinationPath = normalizedDestinationPath;
-        if (existsSync(normalizedDestinationPath) && statSync(normalizedDestinationPath).isDirectory()) {
-          finalDestinationPath = path.join(normalizedDestinationPath, path.basename(normalizedSourcePath));
-        }
-
-        const destDir = path.dirname(finalDestinationPath);
-        await fs.mkdir(destDir, { recursive: true });
-
-        await fs.copyFile(normalizedSourcePath, finalDestinationPath);
-
-        res.json(createResponse(true, {
-          toolCallId: toolCall.id,
-          result: { message: `File copied from '${normalizedSourcePath}' to '${finalDestinationPath}'` },
-          message: 'File copied successfully'
-        }));
-        break;
-      }
-        
-      default:
-        res.json(createResponse(false, null, `Tool type '${toolCall.type}' not yet implemented`));
-    }
-  } catch (error) {
-    res.json(createResponse(false, null, `Tool execution failed: ${error}`));
-  }
-});
-
 // Follow-up AI processing after tool execution
 app.post('/api/ai/process-followup', strictLimiter, async (req, res) => {
   try {
@@ -1597,18 +803,50 @@ app.post('/api/ai/process-followup', strictLimiter, async (req, res) => {
   }
 });
 
+// Control messages for the Tauri side, one `FSAI:{json}` line each on stdout
+const CONTROL_PROTOCOL_VERSION = 1;
+const BACKEND_VERSION = '1.0.0';
+const BACKEND_CAPABILITIES = ['health', 'settings', 'fs', 'ai'];
+
+type ControlMessage =
+    | { type: 'port'; port: number }
+    | { type: 'version'; version: string }
+    | { type: 'capabilities'; capabilities: string[] }
+    | { type: 'fatal'; message: string };
+
+function sendControl(message: ControlMessage) {
+    process.stdout.write(`FSAI:${JSON.stringify({ v: CONTROL_PROTOCOL_VERSION, ...message })}\n`);
+}
+
+process.on('uncaughtException', (error) => {
+    sendControl({ type: 'fatal', message: `Uncaught exception: ${error.message}` });
+    process.exit(1);
+});
+
 async function startServer() {
+    if (!AUTH_TOKEN) {
+        sendControl({ type: 'fatal', message: 'FSAI_AUTH_TOKEN is not set; refusing to serve unauthenticated requests' });
+        process.exit(1);
+    }
+
+    sendControl({ type: 'version', version: BACKEND_VERSION });
+    sendControl({ type: 'capabilities', capabilities: BACKEND_CAPABILITIES });
+
     await loadSettings();
     initializeGenAI();
 
     const server = app.listen(PORT, '127.0.0.1', () => {
         const address = server.address();
         if (address && typeof address === 'object') {
-            const actualPort = address.port;
-            console.log(`BACKEND_PORT:${actualPort}`);
+            sendControl({ type: 'port', port: address.port });
         }
     });
 
+    server.on('error', (error) => {
+        sendControl({ type: 'fatal', message: `Failed to listen: ${error.message}` });
+        process.exit(1);
+    });
+
     process.on('SIGTERM', () => {
         server.close(() => {
             process.exit(0);
@@ -1624,4 +862,4 @@ async function startServer() {
 
 const PORT = parseInt(process.env.PORT || '0'); 
 
-startServer();
\ No newline at end of file
+startServer();
\ No newline at end of file
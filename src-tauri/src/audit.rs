@@ -0,0 +1,714 @@
+use crate::files::to_millis;
+use crate::files::write::to_hex;
+use crate::permissions::{Decision, Permission};
+use crate::policy;
+use crate::tools::{self, ToolCall, ToolContext};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager, State};
+
+const AUDIT_FILE: &str = "audit.log";
+// Previous hash of the first record
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+// File contents and base64 uploads would swamp the log; longer strings are kept as a hash
+const MAX_AUDITED_STRING: usize = 1024;
+
+/// What happened to a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditAction {
+    /// The model asked for it
+    Proposed,
+    Approved,
+    Denied,
+    /// It ran, successfully or not
+    Executed,
+    /// Not a tool call: the log dropped a line torn by an interrupted write
+    Recovered,
+}
+
+/// Who approved or denied a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Approver {
+    /// The user, from the confirmation bar
+    User,
+    /// The permission profiles, without asking
+    Profile,
+}
+
+/// One audited event, before it is chained into the log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub action: AuditAction,
+    pub tool_call_id: String,
+    pub tool_type: String,
+    pub description: String,
+    pub parameters: Value,
+    /// The paths the call touches, with parent directories resolved
+    pub paths: Vec<PathBuf>,
+    pub approver: Option<Approver>,
+    /// Why the permission profiles decided as they did
+    pub reason: Option<String>,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction, call: &ToolCall, context: &ToolContext) -> Self {
+        Self {
+            action,
+            tool_call_id: call.id.clone(),
+            tool_type: call.tool_type.clone(),
+            description: call.description.clone(),
+            parameters: elide(serde_json::to_value(&call.parameters).unwrap_or_default()),
+            paths: tools::targets(call, context)
+                .into_iter()
+                .map(|(path, _)| policy::canonicalize_entry(Path::new(&path)))
+                .collect(),
+            approver: None,
+            reason: None,
+            result: None,
+            error: None,
+        }
+    }
+
+    pub fn decided(mut self, approver: Approver, reason: Option<String>) -> Self {
+        self.approver = Some(approver);
+        self.reason = reason;
+        self
+    }
+
+    fn recovered(path: &Path, torn: &[u8]) -> Self {
+        Self {
+            action: AuditAction::Recovered,
+            tool_call_id: String::new(),
+            tool_type: String::new(),
+            description: format!(
+                "Discarded {} byte(s) of a record torn by an interrupted write (sha256 {})",
+                torn.len(),
+                to_hex(&Sha256::digest(torn))
+            ),
+            parameters: Value::Null,
+            paths: vec![path.to_path_buf()],
+            approver: None,
+            reason: None,
+            result: None,
+            error: None,
+        }
+    }
+
+    pub fn outcome(mut self, outcome: Result<Value, String>) -> Self {
+        match outcome {
+            Ok(result) => self.result = Some(elide(result)),
+            Err(error) => self.error = Some(error),
+        }
+        self
+    }
+}
+
+/// A line of the audit log. Each record's hash covers its own content and the
+/// previous record's hash, so editing, removing or reordering records breaks
+/// the chain from that point on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub entry: AuditEntry,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    fn digest(seq: u64, timestamp: u64, entry: &AuditEntry, prev_hash: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash.as_bytes());
+        // Reading a record back serializes to the same bytes, so this can be re-checked
+        hasher.update(serde_json::to_vec(&(seq, timestamp, entry)).unwrap_or_default());
+        to_hex(&hasher.finalize())
+    }
+}
+
+/// Replaces long strings with their length and hash.
+fn elide(value: Value) -> Value {
+    match value {
+        Value::String(text) if text.len() > MAX_AUDITED_STRING => Value::String(format!(
+            "<{} bytes, sha256 {}>",
+            text.len(),
+            to_hex(&Sha256::digest(text.as_bytes()))
+        )),
+        Value::Array(items) => Value::Array(items.into_iter().map(elide).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, elide(value)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Filters for `query_audit_log`; all of them are optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditQuery {
+    /// Milliseconds since the Unix epoch, inclusive
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    /// Matches records touching this path or anything below it
+    pub path: Option<String>,
+    pub tool: Option<String>,
+    /// Keeps the most recent records
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditVerification {
+    pub records: u64,
+    pub valid: bool,
+    /// Sequence number of the first record that does not chain
+    pub broken_at: Option<u64>,
+    pub error: Option<String>,
+}
+
+struct AuditFile {
+    path: PathBuf,
+    file: File,
+    seq: u64,
+    last_hash: String,
+    /// Approvals written this session whose call has not run yet, by call id
+    approved: HashMap<String, AuditEntry>,
+}
+
+impl AuditFile {
+    /// Opens the log for appending, continuing the chain from its last record.
+    /// A torn last line is cut off, so the next record does not get glued onto
+    /// it, and the cut is recorded.
+    fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(AUDIT_FILE);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        let complete = content
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |end| end + 1);
+        let (lines, torn) = content.split_at(complete);
+        if !torn.is_empty() {
+            file.set_len(complete as u64)?;
+        }
+
+        let mut seq = 0;
+        let mut last_hash = GENESIS_HASH.to_string();
+        for line in lines.split(|&byte| byte == b'\n') {
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            match serde_json::from_slice::<AuditRecord>(line) {
+                Ok(record) => {
+                    seq = record.seq;
+                    last_hash = record.hash;
+                }
+                Err(e) => eprintln!("Skipping unreadable audit record: {}", e),
+            }
+        }
+        let mut log = Self {
+            path,
+            file,
+            seq,
+            last_hash,
+            approved: HashMap::new(),
+        };
+        if !torn.is_empty() {
+            eprintln!("Discarded a torn record at the end of {}", log.path.display());
+            let entry = AuditEntry::recovered(&log.path, torn);
+            log.append(entry)?;
+        }
+        Ok(log)
+    }
+
+    fn append(&mut self, entry: AuditEntry) -> io::Result<AuditRecord> {
+        let seq = self.seq + 1;
+        let timestamp = to_millis(SystemTime::now()).unwrap_or_default();
+        let hash = AuditRecord::digest(seq, timestamp, &entry, &self.last_hash);
+        let record = AuditRecord {
+            seq,
+            timestamp,
+            entry,
+            prev_hash: self.last_hash.clone(),
+            hash,
+        };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        // One write per record, so a crash leaves at most a torn last line
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        self.seq = seq;
+        self.last_hash = record.hash.clone();
+        match record.entry.action {
+            AuditAction::Approved => {
+                self.approved
+                    .insert(record.entry.tool_call_id.clone(), record.entry.clone());
+            }
+            AuditAction::Denied | AuditAction::Executed => {
+                self.approved.remove(&record.entry.tool_call_id);
+            }
+            _ => {}
+        }
+        Ok(record)
+    }
+
+    /// Uses up the approval of exactly this call, if one is on record. The
+    /// whole call is compared, so approving one call cannot let another one
+    /// with the same id through.
+    fn take_approval(&mut self, entry: &AuditEntry) -> bool {
+        let matches = self
+            .approved
+            .get(&entry.tool_call_id)
+            .is_some_and(|approved| {
+                approved.tool_type == entry.tool_type
+                    && approved.parameters == entry.parameters
+                    && approved.paths == entry.paths
+            });
+        if matches {
+            self.approved.remove(&entry.tool_call_id);
+        }
+        matches
+    }
+}
+
+fn read_records(path: &Path) -> Result<Vec<AuditRecord>, String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to open audit log: {}", e)),
+    };
+    let mut records = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read audit log: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line)
+            .map_err(|e| format!("Unreadable audit record on line {}: {}", index + 1, e))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn verify(path: &Path) -> AuditVerification {
+    let records = match read_records(path) {
+        Ok(records) => records,
+        Err(e) => {
+            return AuditVerification {
+                records: 0,
+                valid: false,
+                broken_at: None,
+                error: Some(e),
+            }
+        }
+    };
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for (expected_seq, record) in (1..).zip(&records) {
+        let hash = AuditRecord::digest(record.seq, record.timestamp, &record.entry, &prev_hash);
+        if record.seq != expected_seq || record.prev_hash != prev_hash || record.hash != hash {
+            return AuditVerification {
+                records: records.len() as u64,
+                valid: false,
+                broken_at: Some(record.seq),
+                error: Some(format!("Record {} does not match the chain", record.seq)),
+            };
+        }
+        prev_hash = hash;
+    }
+    AuditVerification {
+        records: records.len() as u64,
+        valid: true,
+        broken_at: None,
+        error: None,
+    }
+}
+
+fn query(path: &Path, filter: &AuditQuery) -> Result<Vec<AuditRecord>, String> {
+    let under = filter
+        .path
+        .as_deref()
+        .map(|path| policy::canonicalize(Path::new(path)).unwrap_or_else(|_| path.into()));
+    let mut records: Vec<AuditRecord> = read_records(path)?
+        .into_iter()
+        .filter(|record| filter.since.is_none_or(|since| record.timestamp >= since))
+        .filter(|record| filter.until.is_none_or(|until| record.timestamp <= until))
+        .filter(|record| {
+            filter
+                .tool
+                .as_ref()
+                .is_none_or(|tool| &record.entry.tool_type == tool)
+        })
+        .filter(|record| {
+            under.as_ref().is_none_or(|under| {
+                record
+                    .entry
+                    .paths
+                    .iter()
+                    .any(|path| path.starts_with(under))
+            })
+        })
+        .collect();
+    if let Some(limit) = filter.limit {
+        records.drain(..records.len().saturating_sub(limit));
+    }
+    Ok(records)
+}
+
+/// Append-only, hash-chained record of every tool call the agent proposed and
+/// what became of it, kept as JSON lines under the app data dir.
+#[derive(Default)]
+pub struct AuditLog {
+    file: Mutex<Option<AuditFile>>,
+}
+
+impl AuditLog {
+    /// Starts writing to `dir`. Until then, and if it cannot be opened,
+    /// entries are dropped.
+    pub fn persist_to(&self, dir: PathBuf) {
+        match AuditFile::open(&dir) {
+            Ok(file) => *self.file.lock().unwrap() = Some(file),
+            Err(e) => eprintln!("Failed to open audit log: {}", e),
+        }
+    }
+
+    fn path(&self) -> Result<PathBuf, String> {
+        self.file
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|file| file.path.clone())
+            .ok_or_else(|| "The audit log is not available".to_string())
+    }
+
+    pub fn append(&self, entry: AuditEntry) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            if let Err(e) = file.append(entry) {
+                eprintln!("Failed to write audit log: {}", e);
+            }
+        }
+    }
+
+    /// Checks for and uses up an approval of `entry`'s call in one step, so two
+    /// runs racing on the same approval cannot both get it. Without a log there
+    /// is no record of any approval, so nothing is approved.
+    pub fn take_approval(&self, entry: &AuditEntry) -> bool {
+        self.file
+            .lock()
+            .unwrap()
+            .as_mut()
+            .is_some_and(|file| file.take_approval(entry))
+    }
+}
+
+/// Appends `entry` to the app's audit log.
+pub fn record(app: &AppHandle, entry: AuditEntry) {
+    app.state::<AppState>().audit.append(entry);
+}
+
+/// Records a batch the model proposed, along with what the permission
+/// profiles decided for the calls they settle without asking.
+pub fn record_proposed(
+    app: &AppHandle,
+    calls: &[ToolCall],
+    context: &ToolContext,
+    permissions: &[Permission],
+) {
+    for (call, permission) in calls.iter().zip(permissions) {
+        record(
+            app,
+            AuditEntry {
+                reason: Some(permission.reason.clone()),
+                ..AuditEntry::new(AuditAction::Proposed, call, context)
+            },
+        );
+        let action = match permission.decision {
+            Decision::Allow => AuditAction::Approved,
+            Decision::Deny => AuditAction::Denied,
+            Decision::Ask => continue,
+        };
+        record(
+            app,
+            AuditEntry::new(action, call, context)
+                .decided(Approver::Profile, Some(permission.reason.clone())),
+        );
+    }
+}
+
+/// Records the user's answer in the confirmation bar.
+#[tauri::command]
+pub async fn record_tool_decision(
+    app: AppHandle,
+    tool_call: ToolCall,
+    context: Option<ToolContext>,
+    approved: bool,
+) -> Result<(), String> {
+    let action = if approved {
+        AuditAction::Approved
+    } else {
+        AuditAction::Denied
+    };
+    let context = context.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        record(
+            &app,
+            AuditEntry::new(action, &tool_call, &context).decided(Approver::User, None),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to record decision: {}", e))
+}
+
+/// Returns audit records matching `query`, oldest first.
+#[tauri::command]
+pub async fn query_audit_log(
+    state: State<'_, AppState>,
+    query: Option<AuditQuery>,
+) -> Result<Vec<AuditRecord>, String> {
+    let path = state.audit.path()?;
+    let filter = query.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || self::query(&path, &filter))
+        .await
+        .map_err(|e| format!("Failed to query audit log: {}", e))?
+}
+
+/// Re-computes the hash chain to check that no record was altered or removed.
+#[tauri::command]
+pub async fn verify_audit_log(state: State<'_, AppState>) -> Result<AuditVerification, String> {
+    let path = state.audit.path()?;
+    tauri::async_runtime::spawn_blocking(move || verify(&path))
+        .await
+        .map_err(|e| format!("Failed to verify audit log: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{call, Sandbox};
+    use crate::tools::ToolParameters;
+
+    fn sandbox() -> Sandbox {
+        let sandbox = Sandbox::new("audit");
+        sandbox.dir("Downloads");
+        sandbox
+    }
+
+    fn log(sandbox: &Sandbox) -> PathBuf {
+        sandbox.path(AUDIT_FILE)
+    }
+
+    fn entry(sandbox: &Sandbox, action: AuditAction, tool: &str, path: &str) -> AuditEntry {
+        let call = ToolCall {
+            id: format!("tc_{}", path),
+            ..call(
+                tool,
+                ToolParameters {
+                    path: Some(path.to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+        AuditEntry::new(action, &call, &sandbox.context())
+    }
+
+    #[test]
+    fn records_chain_and_verify() {
+        let sandbox = sandbox();
+        let mut file = AuditFile::open(&sandbox.base).unwrap();
+        let first = file
+            .append(entry(&sandbox, AuditAction::Proposed, "delete_item", "Downloads/a.txt"))
+            .unwrap();
+        let second = file
+            .append(entry(&sandbox, AuditAction::Approved, "delete_item", "Downloads/a.txt"))
+            .unwrap();
+        assert_eq!(first.seq, 1);
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(first.entry.paths, vec![sandbox.path("Downloads/a.txt")]);
+        assert_eq!(verify(&log(&sandbox)).records, 2);
+        assert!(verify(&log(&sandbox)).valid);
+    }
+
+    #[test]
+    fn reopening_continues_the_chain() {
+        let sandbox = sandbox();
+        let first = AuditFile::open(&sandbox.base)
+            .unwrap()
+            .append(entry(&sandbox, AuditAction::Proposed, "read_file", "notes.txt"))
+            .unwrap();
+        let second = AuditFile::open(&sandbox.base)
+            .unwrap()
+            .append(entry(&sandbox, AuditAction::Executed, "read_file", "notes.txt"))
+            .unwrap();
+        assert_eq!(second.seq, 2);
+        assert_eq!(second.prev_hash, first.hash);
+        assert!(verify(&log(&sandbox)).valid);
+    }
+
+    #[test]
+    fn cuts_off_a_torn_last_line_before_appending() {
+        let sandbox = sandbox();
+        let first = AuditFile::open(&sandbox.base)
+            .unwrap()
+            .append(entry(&sandbox, AuditAction::Proposed, "read_file", "notes.txt"))
+            .unwrap();
+        // What a crash halfway through the next append leaves behind
+        let mut torn = OpenOptions::new().append(true).open(log(&sandbox)).unwrap();
+        torn.write_all(br#"{"seq":2,"timestamp":17"#).unwrap();
+        drop(torn);
+
+        let mut file = AuditFile::open(&sandbox.base).unwrap();
+        let next = file
+            .append(entry(&sandbox, AuditAction::Executed, "read_file", "notes.txt"))
+            .unwrap();
+        let records = read_records(&log(&sandbox)).unwrap();
+        let actions: Vec<_> = records.iter().map(|record| record.entry.action).collect();
+        assert_eq!(
+            actions,
+            [AuditAction::Proposed, AuditAction::Recovered, AuditAction::Executed]
+        );
+        assert_eq!(records[1].prev_hash, first.hash);
+        assert!(records[1].entry.description.contains("23 byte(s)"));
+        assert_eq!(next.seq, 3);
+        assert!(verify(&log(&sandbox)).valid);
+    }
+
+    #[test]
+    fn detects_edited_and_removed_records() {
+        let sandbox = sandbox();
+        let mut file = AuditFile::open(&sandbox.base).unwrap();
+        for path in ["a.txt", "b.txt", "c.txt"] {
+            file.append(entry(&sandbox, AuditAction::Executed, "delete_item", path))
+                .unwrap();
+        }
+        let original = fs::read_to_string(log(&sandbox)).unwrap();
+
+        fs::write(log(&sandbox), original.replace("b.txt", "z.txt")).unwrap();
+        let edited = verify(&log(&sandbox));
+        assert!(!edited.valid);
+        assert_eq!(edited.broken_at, Some(2));
+
+        let without_second: Vec<&str> = original
+            .lines()
+            .enumerate()
+            .filter(|(i, _)| *i != 1)
+            .map(|(_, line)| line)
+            .collect();
+        fs::write(log(&sandbox), without_second.join("\n")).unwrap();
+        let removed = verify(&log(&sandbox));
+        assert!(!removed.valid);
+        assert_eq!(removed.broken_at, Some(3));
+    }
+
+    #[test]
+    fn queries_filter_by_tool_path_and_time() {
+        let sandbox = sandbox();
+        let mut file = AuditFile::open(&sandbox.base).unwrap();
+        let first = file
+            .append(entry(&sandbox, AuditAction::Executed, "delete_item", "Downloads/a.txt"))
+            .unwrap();
+        file.append(entry(&sandbox, AuditAction::Executed, "read_file", "Downloads/b.txt"))
+            .unwrap();
+        file.append(entry(&sandbox, AuditAction::Executed, "delete_item", "notes.txt"))
+            .unwrap();
+
+        let tool = |tool: &str| AuditQuery {
+            tool: Some(tool.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query(&log(&sandbox), &tool("delete_item")).unwrap().len(),
+            2
+        );
+
+        let downloads = AuditQuery {
+            path: Some(sandbox.path("Downloads").to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let found = query(&log(&sandbox), &downloads).unwrap();
+        assert_eq!(
+            found.iter().map(|record| record.seq).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        let latest = AuditQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(query(&log(&sandbox), &latest).unwrap()[0].seq, 3);
+
+        let future = AuditQuery {
+            since: Some(first.timestamp + 60_000),
+            ..Default::default()
+        };
+        assert!(query(&log(&sandbox), &future).unwrap().is_empty());
+    }
+
+    #[test]
+    fn long_strings_are_kept_as_hashes() {
+        let sandbox = sandbox();
+        let entry = entry(&sandbox, AuditAction::Executed, "read_file", "big.txt").outcome(Ok(
+                serde_json::json!({ "content": "x".repeat(5000), "size": 5000 }),
+            ));
+        let result = entry.result.unwrap();
+        assert!(result["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("<5000 bytes, sha256 "));
+        assert_eq!(result["size"], 5000);
+        assert!(entry.parameters.get("content").is_none());
+    }
+
+    #[test]
+    fn an_approval_covers_one_run_of_exactly_that_call() {
+        let sandbox = sandbox();
+        let mut file = AuditFile::open(&sandbox.base).unwrap();
+        let approved = entry(&sandbox, AuditAction::Approved, "delete_item", "Downloads/a.txt");
+        assert!(!file.take_approval(&approved));
+        file.append(approved.clone()).unwrap();
+
+        let swapped = AuditEntry {
+            tool_call_id: approved.tool_call_id.clone(),
+            ..entry(&sandbox, AuditAction::Approved, "delete_item", "Downloads/b.txt")
+        };
+        assert!(!file.take_approval(&swapped));
+
+        assert!(file.take_approval(&approved));
+        assert!(!file.take_approval(&approved));
+    }
+
+    #[test]
+    fn approvals_do_not_outlive_the_session() {
+        let sandbox = sandbox();
+        let approved = entry(&sandbox, AuditAction::Approved, "delete_item", "Downloads/a.txt");
+        AuditFile::open(&sandbox.base)
+            .unwrap()
+            .append(approved.clone())
+            .unwrap();
+        assert!(!AuditFile::open(&sandbox.base).unwrap().take_approval(&approved));
+    }
+}
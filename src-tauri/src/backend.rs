@@ -0,0 +1,853 @@
+use crate::logs::{self, LogLevel, LogSource};
+use crate::protocol::{self, ControlMessage, LineDecoder, SidecarLine};
+use crate::{health, lifecycle, AppState};
+use serde::{Serialize, Serializer};
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+
+// Restart policy for the supervised sidecar
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// A run that lasts at least this long is considered healthy and resets the backoff
+const STABLE_RUN: Duration = Duration::from_secs(60);
+// Give up after this many consecutive short-lived runs
+const MAX_CRASHES: u32 = 5;
+// Every request to the sidecar must carry the per-launch token in this header
+const AUTH_HEADER: &str = "X-FSai-Token";
+const AUTH_TOKEN_ENV: &str = "FSAI_AUTH_TOKEN";
+
+// Health probes against the sidecar's `/health` route
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const PROBE_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Tunables for the sidecar, read from the environment at startup.
+pub struct BackendConfig {
+    /// Time the sidecar has to report its port and answer `/health` after being spawned.
+    /// Set with `FSAI_BACKEND_STARTUP_TIMEOUT` (seconds).
+    pub startup_timeout: Duration,
+    /// Time between `/health` polls once the sidecar is ready.
+    /// Set with `FSAI_BACKEND_HEALTH_INTERVAL` (seconds).
+    pub health_interval: Duration,
+    /// Consecutive failed polls after which a live but unresponsive sidecar is restarted.
+    /// Set with `FSAI_BACKEND_HEALTH_FAILURES`.
+    pub max_health_failures: u32,
+    /// Time the sidecar gets to exit after SIGTERM before it is force-killed.
+    /// Set with `FSAI_BACKEND_SHUTDOWN_GRACE` (seconds).
+    pub shutdown_grace: Duration,
+}
+
+impl BackendConfig {
+    pub fn from_env() -> Self {
+        Self {
+            startup_timeout: env_secs("FSAI_BACKEND_STARTUP_TIMEOUT")
+                .unwrap_or(Duration::from_secs(15)),
+            health_interval: env_secs("FSAI_BACKEND_HEALTH_INTERVAL")
+                .unwrap_or(Duration::from_secs(10)),
+            max_health_failures: std::env::var("FSAI_BACKEND_HEALTH_FAILURES")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(3),
+            shutdown_grace: env_secs("FSAI_BACKEND_SHUTDOWN_GRACE")
+                .unwrap_or(Duration::from_secs(5)),
+        }
+    }
+}
+
+fn env_secs(name: &str) -> Option<Duration> {
+    let value = std::env::var(name).ok()?;
+    match value.trim().parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => {
+            eprintln!("Ignoring invalid {}: {:?}", name, value);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackendStatus {
+    Starting,
+    Ready,
+    Unhealthy,
+    Stopped,
+}
+
+/// Reasons the sidecar could not be brought up. Returned from `start_backend`
+/// and emitted to the frontend as `backend-failed`.
+#[derive(Debug, Clone)]
+pub enum BackendError {
+    MissingBinary(String),
+    SpawnFailed(String),
+    HandshakeTimeout(Duration),
+    HealthCheckFailed(String),
+    Fatal(String),
+    PrematureExit { code: Option<i32>, signal: Option<i32> },
+}
+
+impl BackendError {
+    fn kind(&self) -> &'static str {
+        match self {
+            BackendError::MissingBinary(_) => "missingBinary",
+            BackendError::SpawnFailed(_) => "spawnFailed",
+            BackendError::HandshakeTimeout(_) => "handshakeTimeout",
+            BackendError::HealthCheckFailed(_) => "healthCheckFailed",
+            BackendError::Fatal(_) => "fatal",
+            BackendError::PrematureExit { .. } => "prematureExit",
+        }
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::MissingBinary(e) => write!(
+                f,
+                "The backend server binary could not be found ({}). Try reinstalling FSai.",
+                e
+            ),
+            BackendError::SpawnFailed(e) => write!(
+                f,
+                "The backend server could not be started ({}). Check that it is executable and not blocked by antivirus software.",
+                e
+            ),
+            BackendError::HandshakeTimeout(timeout) => write!(
+                f,
+                "The backend server did not report a port within {} seconds.",
+                timeout.as_secs()
+            ),
+            BackendError::HealthCheckFailed(e) => write!(
+                f,
+                "The backend server started but is not answering health checks ({}).",
+                e
+            ),
+            BackendError::Fatal(e) => {
+                write!(f, "The backend server failed to start: {}", e)
+            }
+            BackendError::PrematureExit { code, signal } => {
+                write!(f, "The backend server exited before it was ready")?;
+                match (code, signal) {
+                    (Some(code), _) => write!(f, " (exit code {}).", code),
+                    (None, Some(signal)) => write!(f, " (signal {}).", signal),
+                    (None, None) => write!(f, "."),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+// Serialized as `{ kind, message }` so the UI can both branch on it and show it
+impl Serialize for BackendError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("BackendError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackendExited {
+    code: Option<i32>,
+    signal: Option<i32>,
+    restarting: bool,
+}
+
+#[tauri::command]
+pub async fn get_backend_port(state: State<'_, AppState>) -> Result<Option<u16>, String> {
+    let port = state.backend_port.lock().unwrap();
+    Ok(*port)
+}
+
+/// Hands the per-launch token to the webview so it can authenticate its requests
+/// to the sidecar. Nothing else on the machine learns it.
+#[tauri::command]
+pub async fn get_backend_token(state: State<'_, AppState>) -> Result<String, String> {
+    Ok(state.backend_token.clone())
+}
+
+/// Generates the random secret that authenticates requests to the sidecar.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).expect("failed to read from the OS random number generator");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[tauri::command]
+pub async fn get_backend_status(state: State<'_, AppState>) -> Result<BackendStatus, String> {
+    Ok(*state.backend_status.lock().unwrap())
+}
+
+pub(crate) fn set_status(app_handle: &AppHandle, status: BackendStatus) {
+    *app_handle.state::<AppState>().backend_status.lock().unwrap() = status;
+}
+
+/// Launches the supervised sidecar and waits until it has reported its port,
+/// or the first launch has failed.
+#[tauri::command]
+pub async fn start_backend(app_handle: AppHandle) -> Result<(), BackendError> {
+    let generation = {
+        let app_state = app_handle.state::<AppState>();
+        if app_state.child_process.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        let mut generation = app_state.backend_generation.lock().unwrap();
+        *generation += 1;
+        *generation
+    };
+
+    let (ready_tx, ready_rx) = oneshot::channel();
+    tauri::async_runtime::spawn(supervise(app_handle, generation, ready_tx));
+
+    // The sender is only dropped without a result if we were stopped during startup
+    ready_rx.await.unwrap_or(Ok(()))
+}
+
+#[tauri::command]
+pub async fn stop_backend(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(child) = detach_child(&state) {
+        let grace = state.backend_config.shutdown_grace;
+        tauri::async_runtime::spawn_blocking(move || shutdown_child(child, grace))
+            .await
+            .map_err(|e| format!("Failed to stop backend process: {}", e))??;
+        println!("Backend process terminated");
+    }
+    Ok(())
+}
+
+/// Marks the backend as stopped and hands back the running child, if any, for the
+/// caller to shut down. The supervisor will not relaunch it.
+pub(crate) fn detach_child(state: &AppState) -> Option<CommandChild> {
+    // Bumping the generation tells the supervisor not to relaunch
+    *state.backend_generation.lock().unwrap() += 1;
+    *state.backend_port.lock().unwrap() = None;
+    *state.backend_status.lock().unwrap() = BackendStatus::Stopped;
+
+    let child = state.child_process.lock().unwrap().take();
+    state.sidecar_guard.release();
+    child
+}
+
+/// Asks the sidecar to exit with SIGTERM so `server.ts` can close its HTTP server
+/// and finish in-flight writes, then force-kills it if it is still running after
+/// `grace`. Blocks the calling thread for up to `grace`. The shell plugin reaps
+/// the child as soon as it exits, which [`lifecycle::terminate`] relies on.
+pub(crate) fn shutdown_child(child: CommandChild, grace: Duration) -> Result<(), String> {
+    lifecycle::terminate(child.pid(), grace, || child.kill())
+        .map_err(|e| format!("Failed to kill backend process: {}", e))
+}
+
+/// Restart bookkeeping for the supervisor: the delay before the next launch and
+/// the run of short-lived launches behind it.
+struct Restarts {
+    backoff: Duration,
+    crashes: u32,
+}
+
+impl Default for Restarts {
+    fn default() -> Self {
+        Self {
+            backoff: INITIAL_BACKOFF,
+            crashes: 0,
+        }
+    }
+}
+
+impl Restarts {
+    /// Records a run that lasted `ran` and returns whether another launch is
+    /// allowed. A stable run starts the schedule over.
+    fn record(&mut self, ran: Duration) -> bool {
+        if ran >= STABLE_RUN {
+            *self = Self::default();
+        } else {
+            self.crashes += 1;
+        }
+        self.crashes < MAX_CRASHES
+    }
+
+    /// The delay before the next launch; each one doubles, up to `MAX_BACKOFF`.
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.backoff;
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+        delay
+    }
+}
+
+pub(crate) fn is_current(app_handle: &AppHandle, generation: u64) -> bool {
+    *app_handle.state::<AppState>().backend_generation.lock().unwrap() == generation
+}
+
+/// Runs the sidecar and relaunches it with exponential backoff whenever it exits,
+/// until `stop_backend` is called or it crash-loops `MAX_CRASHES` times in a row.
+async fn supervise(
+    app_handle: AppHandle,
+    generation: u64,
+    ready_tx: oneshot::Sender<Result<(), BackendError>>,
+) {
+    let mut ready_tx = Some(ready_tx);
+    let mut restarts = Restarts::default();
+
+    loop {
+        let started_at = Instant::now();
+        let exit = match run_sidecar(&app_handle, generation, &mut ready_tx).await {
+            Ok(exit) => exit,
+            Err(e) => {
+                logs::record(
+                    &app_handle,
+                    LogLevel::Error,
+                    LogSource::Supervisor,
+                    format!("Failed to start backend: {}", e),
+                );
+                let _ = app_handle.emit("backend-failed", &e);
+                if let Some(tx) = ready_tx.take() {
+                    let _ = tx.send(Err(e.clone()));
+                }
+                match e {
+                    BackendError::PrematureExit { code, signal } => (code, signal),
+                    _ => (None, None),
+                }
+            }
+        };
+
+        // A newer supervisor may already own the state after a stop/start cycle
+        if is_current(&app_handle, generation) {
+            let app_state = app_handle.state::<AppState>();
+            *app_state.backend_port.lock().unwrap() = None;
+            *app_state.child_process.lock().unwrap() = None;
+            app_state.sidecar_guard.release();
+            let mut status = app_state.backend_status.lock().unwrap();
+            if *status != BackendStatus::Unhealthy {
+                *status = BackendStatus::Starting;
+            }
+        }
+
+        let may_restart = restarts.record(started_at.elapsed());
+        let restarting = is_current(&app_handle, generation) && may_restart;
+        let (code, signal) = exit;
+        let _ = app_handle.emit("backend-exited", BackendExited { code, signal, restarting });
+
+        if !restarting {
+            if !may_restart {
+                logs::record(
+                    &app_handle,
+                    LogLevel::Error,
+                    LogSource::Supervisor,
+                    format!("Backend crashed {} times in a row, giving up", restarts.crashes),
+                );
+                set_status(&app_handle, BackendStatus::Stopped);
+            }
+            break;
+        }
+
+        let backoff = restarts.next_delay();
+        logs::record(
+            &app_handle,
+            LogLevel::Warn,
+            LogSource::Supervisor,
+            format!(
+                "Backend exited (code: {:?}, signal: {:?}), restarting in {:?}",
+                code, signal, backoff
+            ),
+        );
+        tokio::time::sleep(backoff).await;
+
+        // stop_backend may have been called while we were waiting
+        if !is_current(&app_handle, generation) {
+            break;
+        }
+    }
+}
+
+/// Spawns one instance of the sidecar and forwards its output until it terminates.
+/// Returns the exit code and signal reported by the process.
+async fn run_sidecar(
+    app_handle: &AppHandle,
+    generation: u64,
+    ready_tx: &mut Option<oneshot::Sender<Result<(), BackendError>>>,
+) -> Result<(Option<i32>, Option<i32>), BackendError> {
+    let sidecar_command = app_handle
+        .shell()
+        .sidecar("server")
+        .map_err(|e| BackendError::MissingBinary(e.to_string()))?;
+
+    // Raw chunks, framed by `LineDecoder`, so control lines survive arbitrary pipe splits
+    let token = app_handle.state::<AppState>().backend_token.clone();
+    let (rx, child) = sidecar_command
+        .set_raw_out(true)
+        .env(AUTH_TOKEN_ENV, token)
+        .spawn().map_err(|e| match e {
+        tauri_plugin_shell::Error::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            BackendError::MissingBinary(e.to_string())
+        }
+        e => BackendError::SpawnFailed(e.to_string()),
+    })?;
+
+    let pid = child.pid();
+
+    // Store the child process in the state, unless we were stopped in the meantime
+    {
+        let app_state = app_handle.state::<AppState>();
+        let mut child_process = app_state.child_process.lock().unwrap();
+        if is_current(app_handle, generation) {
+            *app_state.backend_status.lock().unwrap() = BackendStatus::Starting;
+            app_state.sidecar_guard.track(pid);
+            *child_process = Some(child);
+        } else {
+            let _ = child.kill();
+            return Ok((None, None));
+        }
+    }
+
+    let mut output = SidecarOutput::new(rx);
+    let startup_timeout = app_handle.state::<AppState>().backend_config.startup_timeout;
+    let deadline = tokio::time::Instant::now() + startup_timeout;
+    let result = match tokio::time::timeout_at(deadline, wait_for_port(app_handle, &mut output)).await {
+        Ok(Ok(port)) => wait_until_healthy(app_handle, port, deadline, &mut output)
+            .await
+            .map(|_| port),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err(BackendError::HandshakeTimeout(startup_timeout)),
+    };
+    let port = match result {
+        Ok(port) => port,
+        Err(e) => {
+            // The process may still be alive; make sure it is gone before relaunching
+            let alive = matches!(
+                e,
+                BackendError::HandshakeTimeout(_)
+                    | BackendError::HealthCheckFailed(_)
+                    | BackendError::Fatal(_)
+            );
+            if alive && is_current(app_handle, generation) {
+                set_status(app_handle, BackendStatus::Unhealthy);
+                if let Some(child) = app_handle.state::<AppState>().child_process.lock().unwrap().take() {
+                    let _ = child.kill();
+                }
+            }
+            return Err(e);
+        }
+    };
+
+    if !is_current(app_handle, generation) {
+        return Ok((None, None));
+    }
+
+    {
+        let app_state = app_handle.state::<AppState>();
+        *app_state.backend_port.lock().unwrap() = Some(port);
+        *app_state.backend_status.lock().unwrap() = BackendStatus::Ready;
+    }
+
+    // Emit event to frontend
+    let _ = app_handle.emit("backend-ready", port);
+    logs::record(
+        app_handle,
+        LogLevel::Info,
+        LogSource::Supervisor,
+        format!("Backend started on port: {}", port),
+    );
+    if let Some(tx) = ready_tx.take() {
+        let _ = tx.send(Ok(()));
+    }
+
+    tauri::async_runtime::spawn(health::monitor(app_handle.clone(), generation, pid, port));
+
+    loop {
+        match output.next(app_handle).await {
+            Some(SidecarEvent::Terminated { code, signal }) => return Ok((code, signal)),
+            Some(SidecarEvent::Control(ControlMessage::Fatal { message })) => {
+                logs::record(
+                    app_handle,
+                    LogLevel::Error,
+                    LogSource::Supervisor,
+                    format!("Backend reported a fatal error: {}", message),
+                );
+            }
+            Some(SidecarEvent::Control(message)) => log_control_message(app_handle, message),
+            None => return Ok((None, None)),
+        }
+    }
+}
+
+enum SidecarEvent {
+    Control(ControlMessage),
+    Terminated { code: Option<i32>, signal: Option<i32> },
+}
+
+/// Frames the sidecar's raw stdout/stderr into lines, logs ordinary output and
+/// surfaces control messages and termination in the order they happened.
+struct SidecarOutput {
+    rx: Receiver<CommandEvent>,
+    stdout: LineDecoder,
+    stderr: LineDecoder,
+    pending: VecDeque<SidecarEvent>,
+}
+
+impl SidecarOutput {
+    fn new(rx: Receiver<CommandEvent>) -> Self {
+        Self {
+            rx,
+            stdout: LineDecoder::default(),
+            stderr: LineDecoder::default(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Waits for the next control message or for termination. Cancel-safe: the
+    /// only await point is the channel receive.
+    async fn next(&mut self, app_handle: &AppHandle) -> Option<SidecarEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+
+            match self.rx.recv().await? {
+                CommandEvent::Stdout(chunk) => {
+                    for line in self.stdout.push(&chunk) {
+                        self.on_stdout_line(app_handle, line);
+                    }
+                }
+                CommandEvent::Stderr(chunk) => {
+                    for line in self.stderr.push(&chunk) {
+                        logs::record(app_handle, LogLevel::Error, LogSource::Stderr, line);
+                    }
+                }
+                CommandEvent::Error(e) => {
+                    logs::record(
+                        app_handle,
+                        LogLevel::Error,
+                        LogSource::Supervisor,
+                        format!("[sidecar error]: {}", e),
+                    );
+                }
+                CommandEvent::Terminated(payload) => {
+                    if let Some(line) = self.stdout.finish() {
+                        self.on_stdout_line(app_handle, line);
+                    }
+                    if let Some(line) = self.stderr.finish() {
+                        logs::record(app_handle, LogLevel::Error, LogSource::Stderr, line);
+                    }
+                    self.pending.push_back(SidecarEvent::Terminated {
+                        code: payload.code,
+                        signal: payload.signal,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn on_stdout_line(&mut self, app_handle: &AppHandle, line: String) {
+        match protocol::parse_line(line) {
+            SidecarLine::Control(message) => self.pending.push_back(SidecarEvent::Control(message)),
+            SidecarLine::Invalid { line, reason } => logs::record(
+                app_handle,
+                LogLevel::Warn,
+                LogSource::Supervisor,
+                format!("Ignoring malformed control message ({}): {}", reason, line),
+            ),
+            SidecarLine::Output(line) => {
+                logs::record(app_handle, LogLevel::Info, LogSource::Stdout, line)
+            }
+        }
+    }
+}
+
+fn log_control_message(app_handle: &AppHandle, message: ControlMessage) {
+    let (level, message) = match message {
+        ControlMessage::Version { version } => {
+            (LogLevel::Info, format!("Backend version {}", version))
+        }
+        ControlMessage::Capabilities { capabilities } => (
+            LogLevel::Info,
+            format!("Backend capabilities: {}", capabilities.join(", ")),
+        ),
+        ControlMessage::Port { port } => (
+            LogLevel::Warn,
+            format!("Ignoring unexpected port announcement: {}", port),
+        ),
+        ControlMessage::Fatal { message } => (LogLevel::Error, message),
+    };
+    logs::record(app_handle, level, LogSource::Supervisor, message);
+}
+
+/// Waits for the sidecar to announce its port.
+async fn wait_for_port(
+    app_handle: &AppHandle,
+    output: &mut SidecarOutput,
+) -> Result<u16, BackendError> {
+    loop {
+        match output.next(app_handle).await {
+            Some(SidecarEvent::Control(ControlMessage::Port { port })) => return Ok(port),
+            Some(SidecarEvent::Control(ControlMessage::Fatal { message })) => {
+                return Err(BackendError::Fatal(message));
+            }
+            Some(SidecarEvent::Control(message)) => log_control_message(app_handle, message),
+            Some(SidecarEvent::Terminated { code, signal }) => {
+                return Err(BackendError::PrematureExit { code, signal });
+            }
+            None => return Err(BackendError::PrematureExit { code: None, signal: None }),
+        }
+    }
+}
+
+/// Probes `/health` until it answers or `deadline` passes, while still draining
+/// sidecar output so it cannot block on a full pipe.
+async fn wait_until_healthy(
+    app_handle: &AppHandle,
+    port: u16,
+    deadline: tokio::time::Instant,
+    output: &mut SidecarOutput,
+) -> Result<(), BackendError> {
+    let token = &app_handle.state::<AppState>().backend_token;
+    let probe = async {
+        loop {
+            let error = match probe_health(port, token).await {
+                Ok(_) => return Ok(()),
+                Err(e) => e,
+            };
+            if tokio::time::Instant::now() + PROBE_RETRY_INTERVAL >= deadline {
+                return Err(BackendError::HealthCheckFailed(error));
+            }
+            tokio::time::sleep(PROBE_RETRY_INTERVAL).await;
+        }
+    };
+    tokio::pin!(probe);
+
+    loop {
+        tokio::select! {
+            result = &mut probe => return result,
+            event = output.next(app_handle) => match event {
+                Some(SidecarEvent::Control(ControlMessage::Fatal { message })) => {
+                    return Err(BackendError::Fatal(message));
+                }
+                Some(SidecarEvent::Control(message)) => log_control_message(app_handle, message),
+                Some(SidecarEvent::Terminated { code, signal }) => {
+                    return Err(BackendError::PrematureExit { code, signal });
+                }
+                None => return Err(BackendError::PrematureExit { code: None, signal: None }),
+            },
+        }
+    }
+}
+
+/// Issues a single `GET /health` against the sidecar and returns how long it took.
+pub(crate) async fn probe_health(port: u16, token: &str) -> Result<Duration, String> {
+    let started_at = Instant::now();
+    let request = async {
+        let mut stream = TcpStream::connect(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Failed to connect: {}", e))?;
+        let request = format!(
+            "GET /health HTTP/1.1\r\nHost: 127.0.0.1:{}\r\n{}: {}\r\nConnection: close\r\n\r\n",
+            port, AUTH_HEADER, token
+        );
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+
+        let mut response = Vec::new();
+        stream
+            .read_to_end(&mut response)
+            .await
+            .map_err(|e| format!("Failed to read response: {}", e))?;
+        check_health_response(&response)
+    };
+
+    match tokio::time::timeout(PROBE_TIMEOUT, request).await {
+        Ok(Ok(())) => Ok(started_at.elapsed()),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err(format!("No response within {:?}", PROBE_TIMEOUT)),
+    }
+}
+
+/// Accepts a `/health` response only if its status line says 200.
+fn check_health_response(response: &[u8]) -> Result<(), String> {
+    let response = String::from_utf8_lossy(response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some("200") => Ok(()),
+        Some(status) => Err(format!("Unexpected status {}", status)),
+        None => Err("Malformed response".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let mut restarts = Restarts::default();
+        let delays: Vec<Duration> = (0..8).map(|_| restarts.next_delay()).collect();
+        let millis: Vec<u128> = delays.iter().map(Duration::as_millis).collect();
+        assert_eq!(millis, [500, 1000, 2000, 4000, 8000, 16000, 30000, 30000]);
+    }
+
+    #[test]
+    fn gives_up_after_too_many_short_runs() {
+        let mut restarts = Restarts::default();
+        let short = Duration::from_secs(1);
+        for _ in 1..MAX_CRASHES {
+            assert!(restarts.record(short));
+        }
+        assert!(!restarts.record(short));
+        assert_eq!(restarts.crashes, MAX_CRASHES);
+    }
+
+    #[test]
+    fn a_stable_run_starts_the_schedule_over() {
+        let mut restarts = Restarts::default();
+        for _ in 0..3 {
+            restarts.record(Duration::from_secs(1));
+            restarts.next_delay();
+        }
+        assert_eq!(restarts.next_delay(), Duration::from_secs(4));
+        assert!(restarts.record(STABLE_RUN));
+        assert_eq!(restarts.crashes, 0);
+        assert_eq!(restarts.next_delay(), INITIAL_BACKOFF);
+    }
+
+    #[test]
+    fn errors_serialize_as_kind_and_message() {
+        let cases = [
+            (
+                BackendError::MissingBinary("not found".to_string()),
+                "missingBinary",
+                "The backend server binary could not be found (not found). Try reinstalling FSai.",
+            ),
+            (
+                BackendError::SpawnFailed("permission denied".to_string()),
+                "spawnFailed",
+                "The backend server could not be started (permission denied). Check that it is executable and not blocked by antivirus software.",
+            ),
+            (
+                BackendError::HandshakeTimeout(Duration::from_secs(15)),
+                "handshakeTimeout",
+                "The backend server did not report a port within 15 seconds.",
+            ),
+            (
+                BackendError::HealthCheckFailed("Unexpected status 503".to_string()),
+                "healthCheckFailed",
+                "The backend server started but is not answering health checks (Unexpected status 503).",
+            ),
+            (
+                BackendError::Fatal("port in use".to_string()),
+                "fatal",
+                "The backend server failed to start: port in use",
+            ),
+            (
+                BackendError::PrematureExit { code: Some(1), signal: None },
+                "prematureExit",
+                "The backend server exited before it was ready (exit code 1).",
+            ),
+            (
+                BackendError::PrematureExit { code: None, signal: Some(9) },
+                "prematureExit",
+                "The backend server exited before it was ready (signal 9).",
+            ),
+            (
+                BackendError::PrematureExit { code: None, signal: None },
+                "prematureExit",
+                "The backend server exited before it was ready.",
+            ),
+        ];
+        for (error, kind, message) in cases {
+            assert_eq!(
+                serde_json::to_value(&error).unwrap(),
+                json!({ "kind": kind, "message": message })
+            );
+        }
+    }
+
+    #[test]
+    fn only_a_200_counts_as_healthy() {
+        let cases = [
+            ("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok", Ok(())),
+            ("HTTP/1.0 200\r\n\r\n", Ok(())),
+            (
+                "HTTP/1.1 401 Unauthorized\r\n\r\n",
+                Err("Unexpected status 401"),
+            ),
+            (
+                "HTTP/1.1 503 Service Unavailable\r\n\r\n",
+                Err("Unexpected status 503"),
+            ),
+            ("garbage", Err("Malformed response")),
+            ("", Err("Malformed response")),
+        ];
+        for (response, expected) in cases {
+            assert_eq!(
+                check_health_response(response.as_bytes()),
+                expected.map_err(str::to_string),
+                "{:?}",
+                response
+            );
+        }
+    }
+
+    /// Serves one request with `response` and hands back what was sent.
+    fn serve_once(response: &'static str) -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                assert!(read > 0, "connection closed mid-request");
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (port, server)
+    }
+
+    #[test]
+    fn probes_health_with_the_token() {
+        let (port, server) = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+        let result = tauri::async_runtime::block_on(probe_health(port, "secret"));
+        assert!(result.is_ok(), "{:?}", result);
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /health HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nX-FSai-Token: secret\r\n"));
+    }
+
+    #[test]
+    fn probe_reports_an_unhealthy_answer() {
+        let (port, server) = serve_once("HTTP/1.1 503 Service Unavailable\r\n\r\n");
+        let result = tauri::async_runtime::block_on(probe_health(port, "secret"));
+        assert_eq!(result.unwrap_err(), "Unexpected status 503");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn probe_reports_a_closed_port() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let result = tauri::async_runtime::block_on(probe_health(port, "secret"));
+        assert!(result.unwrap_err().starts_with("Failed to connect"));
+    }
+}
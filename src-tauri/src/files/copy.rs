@@ -0,0 +1,825 @@
+use super::check_access;
+use crate::policy::{Access, PathPolicy};
+use super::transfer::check_destination;
+use super::write::hash_file;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+// Keeps the event stream readable for trees with many small files
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+// How often a copy waiting on a conflict answer checks whether it was cancelled
+const ANSWER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// How often a paused operation checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default)]
+struct Flags {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+}
+
+/// Shared flags a long-running operation polls to find out it should pause or stop.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<Flags>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn pause(&self) {
+        self.0.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.0.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Blocks while paused, then fails if the operation was cancelled.
+    pub fn check(&self) -> io::Result<()> {
+        while self.0.paused.load(Ordering::SeqCst) && !self.is_cancelled() {
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        if self.is_cancelled() {
+            Err(cancelled())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+fn cancelled() -> io::Error {
+    io::Error::other(Cancelled)
+}
+
+pub fn is_cancelled(error: &io::Error) -> bool {
+    error.get_ref().is_some_and(|inner| inner.is::<Cancelled>())
+}
+
+/// What to do when something already exists at a destination path. Directories
+/// never conflict with directories; their contents are merged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    Skip,
+    Overwrite,
+    /// Copy next to it as `name (2).ext`
+    #[default]
+    Rename,
+    /// Emit `copy-conflict` and wait for `resolve_copy_conflict`
+    Ask,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictResolution {
+    Skip,
+    Overwrite,
+    Rename,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictAnswer {
+    pub resolution: ConflictResolution,
+    /// Use the same resolution for every later conflict of this copy
+    #[serde(default)]
+    pub apply_to_all: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CopyOptions {
+    /// Identifies the copy in events and for `cancel_copy`; generated when omitted
+    pub id: Option<String>,
+    pub conflict: ConflictPolicy,
+    /// Fsync every file and compare digests with the source after copying
+    pub verify: bool,
+}
+
+/// Payload of the `copy-progress` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyProgress {
+    pub id: String,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    /// File being copied right now
+    pub current: PathBuf,
+    pub file_bytes_done: u64,
+    pub file_bytes_total: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: u64,
+    pub files_total: u64,
+}
+
+/// Payload of the `copy-conflict` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyConflict {
+    pub id: String,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub source_is_dir: bool,
+    pub destination_is_dir: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyResult {
+    pub id: String,
+    pub source: PathBuf,
+    /// Where the copy ended up, which differs from the request after a rename
+    pub destination: PathBuf,
+    pub files_copied: u64,
+    pub files_skipped: u64,
+    pub bytes_copied: u64,
+}
+
+/// Receives progress and conflict questions from a running copy.
+pub trait CopyObserver {
+    /// `force` marks the first and last update, which must not be throttled away.
+    fn progress(&mut self, progress: &CopyProgress, force: bool);
+    /// Called for each conflict under [`ConflictPolicy::Ask`]; `None` cancels the copy.
+    fn conflict(&mut self, conflict: &CopyConflict) -> Option<ConflictAnswer>;
+}
+
+/// A running copy as seen from the commands that control it.
+pub(crate) struct CopyHandle {
+    cancel: CancelToken,
+    answers: mpsc::Sender<ConflictAnswer>,
+}
+
+fn file_times(metadata: &fs::Metadata) -> io::Result<fs::FileTimes> {
+    Ok(fs::FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?))
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, destination)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    let target = fs::read_link(source)?;
+    if fs::metadata(source).is_ok_and(|m| m.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, destination)
+    } else {
+        std::os::windows::fs::symlink_file(target, destination)
+    }
+}
+
+/// Counts the regular files under `path` and their total size, without following symlinks.
+fn measure(path: &Path) -> io::Result<(u64, u64)> {
+    let mut files = 0;
+    let mut bytes = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let metadata = fs::symlink_metadata(&path)?;
+        if metadata.is_dir() {
+            for entry in fs::read_dir(&path)? {
+                pending.push(entry?.path());
+            }
+        } else if metadata.is_file() {
+            files += 1;
+            bytes += metadata.len();
+        }
+    }
+    Ok((files, bytes))
+}
+
+/// First free `name (n).ext` next to `path`.
+fn free_name(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (stem, extension) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) if !stem.is_empty() => {
+            (stem.to_string_lossy(), format!(".{}", ext.to_string_lossy()))
+        }
+        _ => (name, String::new()),
+    };
+    (2..)
+        .map(|n| parent.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .expect("ran out of candidate names")
+}
+
+fn temp_path(destination: &Path) -> io::Result<PathBuf> {
+    let mut suffix = [0u8; 6];
+    getrandom::fill(&mut suffix).map_err(|e| io::Error::other(e.to_string()))?;
+    let suffix: String = suffix.iter().map(|b| format!("{:02x}", b)).collect();
+    let name = destination.file_name().unwrap_or_default().to_string_lossy();
+    Ok(destination.with_file_name(format!(".{}.fsai-copy-{}.tmp", name, suffix)))
+}
+
+/// Linux fast paths: a reflink shares the extents outright on btrfs, XFS and
+/// bcachefs, and `copy_file_range` keeps the data in the kernel (and lets NFS
+/// and SMB copy server-side).
+#[cfg(target_os = "linux")]
+mod fast {
+    use super::CancelToken;
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::AsRawFd;
+
+    // _IOW(0x94, 9, int), not exported by every libc version we build against
+    const FICLONE: u32 = 0x4004_9409;
+    // Small enough to check for cancellation regularly
+    const RANGE_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+    /// Returns `Ok(false)` when the caller has to finish the copy itself, from the
+    /// current file offsets.
+    pub fn copy(
+        input: &File,
+        output: &File,
+        cancel: &CancelToken,
+        on_bytes: &mut dyn FnMut(u64),
+    ) -> io::Result<bool> {
+        let len = input.metadata()?.len();
+        // SAFETY: both descriptors are open for as long as the borrowed `File`s
+        // live, and FICLONE takes the source descriptor by value, not a pointer
+        if unsafe { libc::ioctl(output.as_raw_fd(), FICLONE as _, input.as_raw_fd()) } == 0 {
+            on_bytes(len);
+            return Ok(true);
+        }
+
+        let mut copied = 0;
+        loop {
+            cancel.check()?;
+            // SAFETY: the descriptors are valid for the duration of the call, and
+            // null offset pointers make the kernel use and advance the file offsets
+            let n = unsafe {
+                libc::copy_file_range(
+                    input.as_raw_fd(),
+                    std::ptr::null_mut(),
+                    output.as_raw_fd(),
+                    std::ptr::null_mut(),
+                    RANGE_CHUNK_SIZE,
+                    0,
+                )
+            };
+            match n {
+                // Pseudo filesystems report 0 before the real end of the data
+                0 => return Ok(copied >= len),
+                n if n > 0 => {
+                    copied += n as u64;
+                    on_bytes(n as u64);
+                }
+                _ => {
+                    let error = io::Error::last_os_error();
+                    match error.raw_os_error() {
+                        Some(libc::EINTR) => continue,
+                        Some(libc::EXDEV | libc::ENOSYS | libc::EOPNOTSUPP | libc::EINVAL) => {
+                            return Ok(false)
+                        }
+                        _ => return Err(error),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn copy_contents(
+    input: &File,
+    output: &File,
+    cancel: &CancelToken,
+    on_bytes: &mut dyn FnMut(u64),
+) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    if fast::copy(input, output, cancel, on_bytes)? {
+        return Ok(());
+    }
+
+    let (mut input, mut output) = (input, output);
+    let mut buf = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        cancel.check()?;
+        let n = input.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        output.write_all(&buf[..n])?;
+        on_bytes(n as u64);
+    }
+}
+
+struct Engine<'a> {
+    policy: ConflictPolicy,
+    verify: bool,
+    access: &'a PathPolicy,
+    cancel: &'a CancelToken,
+    observer: &'a mut dyn CopyObserver,
+    progress: CopyProgress,
+    files_skipped: u64,
+}
+
+impl Engine<'_> {
+    fn resolve_conflict(
+        &mut self,
+        source: &Path,
+        destination: &Path,
+        source_is_dir: bool,
+        destination_is_dir: bool,
+    ) -> io::Result<ConflictResolution> {
+        Ok(match self.policy {
+            ConflictPolicy::Skip => ConflictResolution::Skip,
+            ConflictPolicy::Overwrite => ConflictResolution::Overwrite,
+            ConflictPolicy::Rename => ConflictResolution::Rename,
+            ConflictPolicy::Ask => {
+                let answer = self
+                    .observer
+                    .conflict(&CopyConflict {
+                        id: self.progress.id.clone(),
+                        source: source.to_path_buf(),
+                        destination: destination.to_path_buf(),
+                        source_is_dir,
+                        destination_is_dir,
+                    })
+                    .ok_or_else(cancelled)?;
+                if answer.apply_to_all {
+                    self.policy = match answer.resolution {
+                        ConflictResolution::Skip => ConflictPolicy::Skip,
+                        ConflictResolution::Overwrite => ConflictPolicy::Overwrite,
+                        ConflictResolution::Rename => ConflictPolicy::Rename,
+                        ConflictResolution::Cancel => ConflictPolicy::Ask,
+                    };
+                }
+                answer.resolution
+            }
+        })
+    }
+
+    /// Copies `source` to `destination`, returning where it ended up, or `None`
+    /// when it was skipped.
+    fn copy_entry(&mut self, source: &Path, destination: &Path) -> io::Result<Option<PathBuf>> {
+        self.cancel.check()?;
+        let metadata = fs::symlink_metadata(source)?;
+        let mut destination = destination.to_path_buf();
+        let mut replace = false;
+        let mut merge = false;
+
+        if let Ok(existing) = fs::symlink_metadata(&destination) {
+            if metadata.is_dir() && existing.is_dir() {
+                merge = true;
+            } else {
+                match self.resolve_conflict(source, &destination, metadata.is_dir(), existing.is_dir())? {
+                    ConflictResolution::Skip => {
+                        let (files, bytes) = measure(source)?;
+                        self.progress.files_total = self.progress.files_total.saturating_sub(files);
+                        self.progress.bytes_total = self.progress.bytes_total.saturating_sub(bytes);
+                        self.files_skipped += files;
+                        return Ok(None);
+                    }
+                    ConflictResolution::Overwrite if existing.is_dir() => {
+                        return Err(io::Error::other(format!(
+                            "cannot overwrite directory '{}' with a file",
+                            destination.display()
+                        )));
+                    }
+                    ConflictResolution::Overwrite => replace = true,
+                    ConflictResolution::Rename => {
+                        destination = free_name(&destination);
+                        // The new name has to pass the policy like the one it replaces
+                        self.access
+                            .check(&destination, Access::Write)
+                            .and_then(|_| {
+                                self.access.check_tree(source, Access::Read, Some(&destination))
+                            })
+                            .map_err(io::Error::other)?;
+                    }
+                    ConflictResolution::Cancel => return Err(cancelled()),
+                }
+            }
+        }
+
+        let file_type = metadata.file_type();
+        if file_type.is_dir() {
+            if replace {
+                fs::remove_file(&destination)?;
+            }
+            if !merge {
+                fs::create_dir(&destination)?;
+            }
+            let mut children = fs::read_dir(source)?
+                .map(|entry| entry.map(|entry| entry.file_name()))
+                .collect::<io::Result<Vec<_>>>()?;
+            children.sort();
+            for name in children {
+                self.copy_entry(&source.join(&name), &destination.join(&name))?;
+            }
+            // Attributes last: adding children bumps the mtime, and a read-only
+            // directory would refuse them. Merged directories keep their own.
+            if !merge {
+                // Opening directories to set times is not possible everywhere; best effort
+                if let Ok(dir) = File::open(&destination) {
+                    let _ = file_times(&metadata).and_then(|times| dir.set_times(times));
+                }
+                fs::set_permissions(&destination, metadata.permissions())?;
+            }
+        } else if file_type.is_symlink() {
+            if replace {
+                fs::remove_file(&destination)?;
+            }
+            copy_symlink(source, &destination)?;
+        } else if file_type.is_file() {
+            self.copy_file(source, &destination, &metadata, replace)?;
+        } else {
+            return Err(io::Error::other(format!(
+                "cannot copy special file '{}'",
+                source.display()
+            )));
+        }
+        Ok(Some(destination))
+    }
+
+    fn copy_file(
+        &mut self,
+        source: &Path,
+        destination: &Path,
+        metadata: &fs::Metadata,
+        replace: bool,
+    ) -> io::Result<()> {
+        self.progress.current = source.to_path_buf();
+        self.progress.file_bytes_done = 0;
+        self.progress.file_bytes_total = metadata.len();
+
+        // Replacements go through a temp file, so a failed or cancelled copy
+        // leaves the old file intact
+        let target = if replace {
+            temp_path(destination)?
+        } else {
+            destination.to_path_buf()
+        };
+        self.write_file(source, &target, metadata)?;
+        if replace {
+            if let Err(e) = fs::rename(&target, destination) {
+                let _ = fs::remove_file(&target);
+                return Err(e);
+            }
+        }
+
+        self.progress.files_done += 1;
+        self.observer.progress(&self.progress, false);
+        Ok(())
+    }
+
+    /// Writes a new file at `target`, removing it again if anything goes wrong.
+    fn write_file(&mut self, source: &Path, target: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+        let input = File::open(source)?;
+        let output = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(target)?;
+        let result = self.fill(&input, output, source, target, metadata);
+        if result.is_err() {
+            let _ = fs::remove_file(target);
+        }
+        result
+    }
+
+    fn fill(
+        &mut self,
+        input: &File,
+        output: File,
+        source: &Path,
+        target: &Path,
+        metadata: &fs::Metadata,
+    ) -> io::Result<()> {
+        let Engine {
+            cancel,
+            observer,
+            progress,
+            ..
+        } = self;
+        copy_contents(input, &output, cancel, &mut |n| {
+            progress.file_bytes_done += n;
+            progress.bytes_done += n;
+            observer.progress(progress, false);
+        })?;
+        output.set_permissions(metadata.permissions())?;
+        output.set_times(file_times(metadata)?)?;
+
+        if self.verify {
+            output.sync_all()?;
+            drop(output);
+            if hash_file(source)? != hash_file(target)? {
+                return Err(io::Error::other(format!(
+                    "verification failed for '{}'",
+                    target.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copies `source` to `destination` (already resolved with
+/// [`resolve_destination`](super::transfer::resolve_destination)),
+/// recursing into directories. Refuses up front if `access` denies anything
+/// inside the tree, where it is or where it would land.
+pub fn copy_path(
+    source: &Path,
+    destination: &Path,
+    options: &CopyOptions,
+    access: &PathPolicy,
+    cancel: &CancelToken,
+    observer: &mut dyn CopyObserver,
+) -> Result<CopyResult, String> {
+    let metadata = fs::symlink_metadata(source)
+        .map_err(|_| format!("Source path does not exist: {}", source.display()))?;
+    if metadata.is_dir() && destination.starts_with(source) {
+        return Err("Cannot copy a directory into itself".to_string());
+    }
+    access.check_tree(source, Access::Read, Some(destination))?;
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let (files_total, bytes_total) =
+        measure(source).map_err(|e| format!("Failed to copy: {}", e))?;
+
+    let id = options.id.clone().unwrap_or_default();
+    let mut engine = Engine {
+        policy: options.conflict,
+        verify: options.verify,
+        access,
+        cancel,
+        observer,
+        progress: CopyProgress {
+            id: id.clone(),
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+            current: source.to_path_buf(),
+            file_bytes_done: 0,
+            file_bytes_total: 0,
+            bytes_done: 0,
+            bytes_total,
+            files_done: 0,
+            files_total,
+        },
+        files_skipped: 0,
+    };
+    engine.observer.progress(&engine.progress, true);
+    let copied = engine.copy_entry(source, destination).map_err(|e| {
+        if is_cancelled(&e) {
+            "Copy cancelled".to_string()
+        } else {
+            format!("Failed to copy: {}", e)
+        }
+    })?;
+    engine.observer.progress(&engine.progress, true);
+
+    Ok(CopyResult {
+        id,
+        source: source.to_path_buf(),
+        destination: copied.unwrap_or_else(|| destination.to_path_buf()),
+        files_copied: engine.progress.files_done,
+        files_skipped: engine.files_skipped,
+        bytes_copied: engine.progress.bytes_done,
+    })
+}
+
+/// Forwards a copy's progress and conflicts to the frontend as events.
+struct EventObserver {
+    app: AppHandle,
+    answers: mpsc::Receiver<ConflictAnswer>,
+    cancel: CancelToken,
+    last_emit: Option<Instant>,
+}
+
+impl CopyObserver for EventObserver {
+    fn progress(&mut self, progress: &CopyProgress, force: bool) {
+        if force || self.last_emit.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+            self.last_emit = Some(Instant::now());
+            let _ = self.app.emit("copy-progress", progress);
+        }
+    }
+
+    fn conflict(&mut self, conflict: &CopyConflict) -> Option<ConflictAnswer> {
+        let _ = self.app.emit("copy-conflict", conflict);
+        loop {
+            match self.answers.recv_timeout(ANSWER_POLL_INTERVAL) {
+                Ok(answer) => return Some(answer),
+                Err(mpsc::RecvTimeoutError::Timeout) if !self.cancel.is_cancelled() => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+fn new_id() -> String {
+    let mut bytes = [0u8; 8];
+    getrandom::fill(&mut bytes).expect("failed to read from the OS random number generator");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Runs a copy that the frontend can follow through events and stop with
+/// `cancel_copy`. Blocks until the copy is done.
+fn run(
+    app: &AppHandle,
+    source: &Path,
+    destination: &Path,
+    options: CopyOptions,
+) -> Result<CopyResult, String> {
+    let id = options.id.clone().unwrap_or_else(new_id);
+    let cancel = CancelToken::default();
+    let (answers_tx, answers) = mpsc::channel();
+
+    let state = app.state::<AppState>();
+    {
+        let mut operations = state.copy_operations.lock().unwrap();
+        if operations.contains_key(&id) {
+            return Err(format!("A copy with id '{}' is already running", id));
+        }
+        operations.insert(
+            id.clone(),
+            CopyHandle {
+                cancel: cancel.clone(),
+                answers: answers_tx,
+            },
+        );
+    }
+
+    let mut observer = EventObserver {
+        app: app.clone(),
+        answers,
+        cancel: cancel.clone(),
+        last_emit: None,
+    };
+    let options = CopyOptions {
+        id: Some(id.clone()),
+        ..options
+    };
+    let result = copy_path(
+        source,
+        destination,
+        &options,
+        &PathPolicy::current(),
+        &cancel,
+        &mut observer,
+    );
+    state.copy_operations.lock().unwrap().remove(&id);
+    result
+}
+
+/// Copies a file or directory tree, emitting `copy-progress` along the way.
+/// A `destination` that is an existing directory receives the copy inside it.
+#[tauri::command]
+pub async fn copy_item(
+    app: AppHandle,
+    source: String,
+    destination: String,
+    options: Option<CopyOptions>,
+) -> Result<CopyResult, String> {
+    let source = check_access(&source, Access::Read)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let destination = check_destination(&source, &destination)?;
+        run(&app, &source, &destination, options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Failed to copy: {}", e))?
+}
+
+#[tauri::command]
+pub async fn cancel_copy(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let operations = state.copy_operations.lock().unwrap();
+    let handle = operations
+        .get(&id)
+        .ok_or_else(|| format!("No copy with id '{}' is running", id))?;
+    handle.cancel.cancel();
+    Ok(())
+}
+
+/// Answers the `copy-conflict` a copy under [`ConflictPolicy::Ask`] is waiting on.
+#[tauri::command]
+pub async fn resolve_copy_conflict(
+    state: State<'_, AppState>,
+    id: String,
+    answer: ConflictAnswer,
+) -> Result<(), String> {
+    let operations = state.copy_operations.lock().unwrap();
+    let handle = operations
+        .get(&id)
+        .ok_or_else(|| format!("No copy with id '{}' is running", id))?;
+    handle
+        .answers
+        .send(answer)
+        .map_err(|_| format!("Copy '{}' is no longer waiting for an answer", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    struct Unattended;
+
+    impl CopyObserver for Unattended {
+        fn progress(&mut self, _progress: &CopyProgress, _force: bool) {}
+
+        fn conflict(&mut self, _conflict: &CopyConflict) -> Option<ConflictAnswer> {
+            None
+        }
+    }
+
+    fn copy(
+        sandbox: &Sandbox,
+        from: &str,
+        to: &str,
+        access: &PathPolicy,
+    ) -> Result<CopyResult, String> {
+        copy_path(
+            &sandbox.path(from),
+            &sandbox.path(to),
+            &CopyOptions::default(),
+            access,
+            &CancelToken::default(),
+            &mut Unattended,
+        )
+    }
+
+    #[test]
+    fn copies_trees_the_policy_allows() {
+        let sandbox = Sandbox::new("copy");
+        sandbox.file("config/app.toml", "theme = 'dark'");
+        sandbox.file("config/chrome/Cookies", "session");
+        let result = copy(&sandbox, "config", "backup", &sandbox.policy(&[])).unwrap();
+        assert_eq!(result.files_copied, 2);
+        assert!(sandbox.path("backup/chrome/Cookies").exists());
+    }
+
+    #[test]
+    fn refuses_trees_holding_denied_entries() {
+        let sandbox = Sandbox::new("copy");
+        sandbox.file("config/app.toml", "theme = 'dark'");
+        sandbox.file("config/chrome/Cookies", "session");
+        let access = sandbox.policy(&["config/chrome"]);
+        let result = copy(&sandbox, "config", "backup", &access);
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert!(!sandbox.path("backup").exists());
+    }
+
+    #[test]
+    fn refuses_entries_that_would_land_in_denied_paths() {
+        let sandbox = Sandbox::new("copy");
+        sandbox.file("keys/ssh/id_ed25519", "key");
+        let access = sandbox.policy(&["backup/ssh"]);
+        let result = copy(&sandbox, "keys", "backup", &access);
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert!(!sandbox.path("backup").exists());
+    }
+
+    #[test]
+    fn checks_the_free_name_a_conflict_is_renamed_to() {
+        let sandbox = Sandbox::new("copy");
+        sandbox.file("report.txt", "new");
+        sandbox.file("out/report.txt", "old");
+        let options = CopyOptions {
+            conflict: ConflictPolicy::Rename,
+            ..Default::default()
+        };
+        let copy = |access: &PathPolicy| {
+            copy_path(
+                &sandbox.path("report.txt"),
+                &sandbox.path("out/report.txt"),
+                &options,
+                access,
+                &CancelToken::default(),
+                &mut Unattended,
+            )
+        };
+        let result = copy(&sandbox.policy(&["out/report (2).txt"]));
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert!(!sandbox.path("out/report (2).txt").exists());
+
+        let result = copy(&sandbox.policy(&[])).unwrap();
+        assert_eq!(result.destination, sandbox.path("out/report (2).txt"));
+    }
+}
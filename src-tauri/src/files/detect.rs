@@ -0,0 +1,759 @@
+use super::check_access;
+use crate::policy::Access;
+use super::read::{detect_encoding, read_full, Encoding};
+use serde::Serialize;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+// Enough to reach the tar header and the first entries of a zip
+const SAMPLE_SIZE: usize = 8 * 1024;
+// Same caps the sidecar applied to `process_file`
+const MAX_IMAGE_PDF_SIZE: u64 = 7 * 1024 * 1024;
+const MAX_VIDEO_SIZE: u64 = 45 * 1024 * 1024;
+
+/// Formats the model accepts as inline data.
+const UPLOADABLE: [&str; 11] = [
+    "image/png",
+    "image/jpeg",
+    "image/webp",
+    "application/pdf",
+    "video/x-flv",
+    "video/quicktime",
+    "video/mpeg",
+    "video/mp4",
+    "video/webm",
+    "video/wmv",
+    "video/3gpp",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileCategory {
+    Image,
+    Video,
+    Audio,
+    Pdf,
+    Archive,
+    Code,
+    Document,
+    /// Plain text that is not source code
+    Text,
+    /// Binary content nothing above matched
+    Other,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileType {
+    pub path: PathBuf,
+    pub mime_type: String,
+    pub category: FileCategory,
+    pub is_text: bool,
+    pub encoding: Encoding,
+    pub size: u64,
+    /// Whether the agent may send this file to the model with `process_file`
+    pub uploadable: bool,
+}
+
+impl FileType {
+    /// Checks that the file can go to the model, with the same messages the sidecar used.
+    pub fn check_uploadable(&self) -> Result<(), String> {
+        if !UPLOADABLE.contains(&self.mime_type.as_str()) {
+            return Err(format!(
+                "Unsupported file type: {}. This tool supports images (PNG, JPEG, WEBP), PDFs, and videos.",
+                self.mime_type
+            ));
+        }
+        let megabytes = self.size as f64 / 1024.0 / 1024.0;
+        if self.category == FileCategory::Video {
+            if self.size > MAX_VIDEO_SIZE {
+                return Err(format!(
+                    "File size ({:.2} MB) exceeds the 45 MB limit for videos.",
+                    megabytes
+                ));
+            }
+        } else if self.size > MAX_IMAGE_PDF_SIZE {
+            return Err(format!(
+                "File size ({:.2} MB) exceeds the 7 MB limit for images and PDFs.",
+                megabytes
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+/// Looks for a known signature at the start of `sample`.
+pub fn sniff(sample: &[u8]) -> Option<(&'static str, FileCategory)> {
+    use FileCategory::*;
+
+    let at = |offset: usize, magic: &[u8]| sample.get(offset..offset + magic.len()) == Some(magic);
+    let found = match sample {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => ("image/png", Image),
+        [0xFF, 0xD8, 0xFF, ..] => ("image/jpeg", Image),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => ("image/gif", Image),
+        [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => ("image/tiff", Image),
+        [b'B', b'M', _, _, _, _, 0, 0, 0, 0, ..] => ("image/bmp", Image),
+        [b'R', b'I', b'F', b'F', _, _, _, _, rest @ ..] => match rest.get(..4)? {
+            b"WEBP" => ("image/webp", Image),
+            b"WAVE" => ("audio/wav", Audio),
+            b"AVI " => ("video/x-msvideo", Video),
+            _ => return None,
+        },
+        // ISO base media: the brand says what is inside the box structure
+        [_, _, _, _, b'f', b't', b'y', b'p', rest @ ..] => match rest.get(..4)? {
+            b"avif" | b"avis" => ("image/avif", Image),
+            b"heic" | b"heix" | b"heim" | b"heis" => ("image/heic", Image),
+            b"mif1" | b"msf1" => ("image/heif", Image),
+            b"qt  " => ("video/quicktime", Video),
+            b"M4A " | b"M4B " => ("audio/mp4", Audio),
+            [b'3', b'g', ..] => ("video/3gpp", Video),
+            _ => ("video/mp4", Video),
+        },
+        [0x1A, 0x45, 0xDF, 0xA3, ..] if contains(sample, b"webm") => ("video/webm", Video),
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => ("video/x-matroska", Video),
+        [b'F', b'L', b'V', 0x01, ..] => ("video/x-flv", Video),
+        [0x00, 0x00, 0x01, 0xBA | 0xB3, ..] => ("video/mpeg", Video),
+        [0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, ..] => ("video/wmv", Video),
+        [0x47, ..] if at(188, &[0x47]) && at(376, &[0x47]) => ("video/mp2t", Video),
+        [b'O', b'g', b'g', b'S', ..] if contains(sample, b"theora") => ("video/ogg", Video),
+        [b'O', b'g', b'g', b'S', ..] => ("audio/ogg", Audio),
+        [b'I', b'D', b'3', ..] => ("audio/mpeg", Audio),
+        // MPEG audio frame sync, layers I-III
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 && second & 0x06 != 0 => ("audio/mpeg", Audio),
+        [b'f', b'L', b'a', b'C', ..] => ("audio/flac", Audio),
+        [b'M', b'T', b'h', b'd', ..] => ("audio/midi", Audio),
+        [b'%', b'P', b'D', b'F', b'-', ..] => ("application/pdf", Pdf),
+        [b'P', b'K', 0x03, 0x04, ..] => sniff_zip(sample),
+        [b'P', b'K', 0x05 | 0x07, 0x06 | 0x08, ..] => ("application/zip", Archive),
+        [0x1F, 0x8B, ..] => ("application/gzip", Archive),
+        [b'B', b'Z', b'h', ..] => ("application/x-bzip2", Archive),
+        [0xFD, b'7', b'z', b'X', b'Z', 0x00, ..] => ("application/x-xz", Archive),
+        [0x28, 0xB5, 0x2F, 0xFD, ..] => ("application/zstd", Archive),
+        [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C, ..] => ("application/x-7z-compressed", Archive),
+        [b'R', b'a', b'r', b'!', 0x1A, 0x07, ..] => ("application/vnd.rar", Archive),
+        _ if at(257, b"ustar") => ("application/x-tar", Archive),
+        // Compound documents: legacy Word, Excel and PowerPoint files
+        [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, ..] => ("application/x-ole-storage", Document),
+        [b'{', b'\\', b'r', b't', b'f', ..] => ("application/rtf", Document),
+        [0x7F, b'E', b'L', b'F', ..] => ("application/x-executable", Other),
+        [b'M', b'Z', ..] => ("application/vnd.microsoft.portable-executable", Other),
+        [0xCF | 0xCE, 0xFA, 0xED, 0xFE, ..] | [0xCA, 0xFE, 0xBA, 0xBE, ..] => ("application/x-mach-binary", Other),
+        [0x00, b'a', b's', b'm', ..] => ("application/wasm", Other),
+        _ if sample.starts_with(b"SQLite format 3\0") => ("application/vnd.sqlite3", Other),
+        _ => return None,
+    };
+    Some(found)
+}
+
+/// Zip containers hold office documents and e-books as well as plain archives.
+fn sniff_zip(sample: &[u8]) -> (&'static str, FileCategory) {
+    use FileCategory::*;
+
+    // OpenDocument and EPUB store their type uncompressed as the first entry
+    if sample.get(30..38) == Some(b"mimetype") {
+        let declared = &sample[38..sample.len().min(38 + 80)];
+        for (mime, category) in [
+            ("application/epub+zip", Document),
+            ("application/vnd.oasis.opendocument.text", Document),
+            ("application/vnd.oasis.opendocument.spreadsheet", Document),
+            ("application/vnd.oasis.opendocument.presentation", Document),
+        ] {
+            if declared.starts_with(mime.as_bytes()) {
+                return (mime, category);
+            }
+        }
+    }
+    if contains(sample, b"[Content_Types].xml") || contains(sample, b"_rels/.rels") {
+        if contains(sample, b"word/") {
+            return ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", Document);
+        }
+        if contains(sample, b"xl/") {
+            return ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", Document);
+        }
+        if contains(sample, b"ppt/") {
+            return ("application/vnd.openxmlformats-officedocument.presentationml.presentation", Document);
+        }
+    }
+    ("application/zip", Archive)
+}
+
+/// Text has no signature, so the extension is trusted for it, after a look at
+/// the first line for markup and interpreter lines.
+fn classify_text(path: &Path, sample: &[u8]) -> (&'static str, FileCategory) {
+    use FileCategory::*;
+
+    let start = String::from_utf8_lossy(&sample[..sample.len().min(512)]);
+    let start = start.trim_start_matches('\u{FEFF}').trim_start();
+    let lower = start.to_ascii_lowercase();
+    if lower.starts_with("<svg") || (lower.starts_with("<?xml") && lower.contains("<svg")) {
+        return ("image/svg+xml", Image);
+    }
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        return ("text/html", Code);
+    }
+    if let Some(interpreter) = start.strip_prefix("#!") {
+        let interpreter = interpreter.lines().next().unwrap_or_default();
+        return if interpreter.contains("python") {
+            ("text/x-python", Code)
+        } else if interpreter.contains("node") {
+            ("text/javascript", Code)
+        } else {
+            ("text/x-shellscript", Code)
+        };
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "rs" => ("text/x-rust", Code),
+        "ts" | "tsx" | "mts" | "cts" => ("text/typescript", Code),
+        "js" | "jsx" | "mjs" | "cjs" => ("text/javascript", Code),
+        "py" | "pyw" => ("text/x-python", Code),
+        "go" => ("text/x-go", Code),
+        "java" => ("text/x-java", Code),
+        "kt" | "kts" => ("text/x-kotlin", Code),
+        "swift" => ("text/x-swift", Code),
+        "c" | "h" => ("text/x-c", Code),
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" => ("text/x-c++", Code),
+        "cs" => ("text/x-csharp", Code),
+        "rb" => ("text/x-ruby", Code),
+        "php" => ("text/x-php", Code),
+        "sh" | "bash" | "zsh" | "fish" => ("text/x-shellscript", Code),
+        "svelte" | "vue" => ("text/plain", Code),
+        "html" | "htm" => ("text/html", Code),
+        "css" | "scss" | "sass" | "less" => ("text/css", Code),
+        "json" | "jsonc" => ("application/json", Code),
+        "xml" => ("application/xml", Code),
+        "yaml" | "yml" => ("application/yaml", Code),
+        "toml" => ("application/toml", Code),
+        "sql" => ("application/sql", Code),
+        "md" | "markdown" => ("text/markdown", Text),
+        "csv" => ("text/csv", Text),
+        "tsv" => ("text/tab-separated-values", Text),
+        _ if lower.starts_with("<?xml") => ("application/xml", Code),
+        _ => ("text/plain", Text),
+    }
+}
+
+/// Works out what `path` holds from its content. The extension only refines
+/// text files, so a renamed file is still recognised for what it is.
+pub fn detect(path: &Path) -> Result<FileType, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let metadata = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    if !metadata.is_file() {
+        return Err("Path is not a file".to_string());
+    }
+    let size = metadata.len();
+
+    let mut sample = vec![0u8; SAMPLE_SIZE.min(size as usize)];
+    let filled = read_full(&mut file, &mut sample).map_err(|e| format!("Failed to read file: {}", e))?;
+    sample.truncate(filled);
+
+    let encoding = detect_encoding(&sample);
+    let is_binary = encoding == Encoding::Binary;
+    let (mime_type, category, is_text) = match sniff(&sample) {
+        // Short ASCII signatures like `MZ` or `BZh` also start ordinary text;
+        // only PDF and RTF are expected to look like text
+        Some((mime, category))
+            if is_binary || matches!(category, FileCategory::Pdf | FileCategory::Document) =>
+        {
+            (mime, category, !is_binary && category == FileCategory::Document)
+        }
+        _ if is_binary => ("application/octet-stream", FileCategory::Other, false),
+        _ => {
+            let (mime, category) = classify_text(path, &sample);
+            (mime, category, true)
+        }
+    };
+
+    let mut file_type = FileType {
+        path: path.to_path_buf(),
+        mime_type: mime_type.to_string(),
+        category,
+        is_text,
+        encoding,
+        size,
+        uploadable: false,
+    };
+    file_type.uploadable = file_type.check_uploadable().is_ok();
+    Ok(file_type)
+}
+
+/// Sniffs a file's type from its first bytes instead of trusting its extension.
+#[tauri::command]
+pub async fn detect_file_type(path: String) -> Result<FileType, String> {
+    let path = check_access(&path, Access::Read)?;
+    tauri::async_runtime::spawn_blocking(move || detect(&path))
+        .await
+        .map_err(|e| format!("Failed to detect file type: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+    use FileCategory::*;
+
+    /// `magic` followed by zeros, so that nothing past the signature matters.
+    fn padded(magic: &[u8]) -> Vec<u8> {
+        let mut sample = magic.to_vec();
+        sample.resize(512, 0);
+        sample
+    }
+
+    /// A zip local file header for `name`, followed by `data`.
+    fn zip_entry(name: &str, data: &str) -> Vec<u8> {
+        let mut entry = b"PK\x03\x04".to_vec();
+        entry.resize(30, 0);
+        entry.extend_from_slice(name.as_bytes());
+        entry.extend_from_slice(data.as_bytes());
+        entry
+    }
+
+    fn transport_stream() -> Vec<u8> {
+        let mut sample = vec![0; 512];
+        for offset in [0, 188, 376] {
+            sample[offset] = 0x47;
+        }
+        sample
+    }
+
+    fn tar() -> Vec<u8> {
+        let mut sample = padded(b"notes.txt");
+        sample[257..262].copy_from_slice(b"ustar");
+        sample
+    }
+
+    #[test]
+    fn sniffs_every_signature() {
+        let cases: Vec<(Vec<u8>, &str, FileCategory)> = vec![
+            (padded(b"\x89PNG\r\n\x1a\n"), "image/png", Image),
+            (padded(b"\xff\xd8\xff\xe0"), "image/jpeg", Image),
+            (padded(b"GIF87a"), "image/gif", Image),
+            (padded(b"GIF89a"), "image/gif", Image),
+            (padded(b"II\x2a\x00"), "image/tiff", Image),
+            (padded(b"MM\x00\x2a"), "image/tiff", Image),
+            (
+                padded(b"BM\x36\x00\x0c\x00\x00\x00\x00\x00"),
+                "image/bmp",
+                Image,
+            ),
+            (padded(b"RIFF\x24\x00\x00\x00WEBP"), "image/webp", Image),
+            (padded(b"RIFF\x24\x00\x00\x00WAVE"), "audio/wav", Audio),
+            (
+                padded(b"RIFF\x24\x00\x00\x00AVI "),
+                "video/x-msvideo",
+                Video,
+            ),
+            (padded(b"\x00\x00\x00\x1cftypavif"), "image/avif", Image),
+            (padded(b"\x00\x00\x00\x1cftypavis"), "image/avif", Image),
+            (padded(b"\x00\x00\x00\x18ftypheic"), "image/heic", Image),
+            (padded(b"\x00\x00\x00\x18ftypmif1"), "image/heif", Image),
+            (
+                padded(b"\x00\x00\x00\x14ftypqt  "),
+                "video/quicktime",
+                Video,
+            ),
+            (padded(b"\x00\x00\x00\x20ftypM4A "), "audio/mp4", Audio),
+            (padded(b"\x00\x00\x00\x14ftyp3gp5"), "video/3gpp", Video),
+            (padded(b"\x00\x00\x00\x20ftypisom"), "video/mp4", Video),
+            (
+                padded(b"\x1a\x45\xdf\xa3\x9f\x42\x82\x84webm"),
+                "video/webm",
+                Video,
+            ),
+            (
+                padded(b"\x1a\x45\xdf\xa3\xa3\x42\x82\x88matroska"),
+                "video/x-matroska",
+                Video,
+            ),
+            (padded(b"FLV\x01\x05"), "video/x-flv", Video),
+            (padded(b"\x00\x00\x01\xba"), "video/mpeg", Video),
+            (padded(b"\x00\x00\x01\xb3"), "video/mpeg", Video),
+            (
+                padded(b"\x30\x26\xb2\x75\x8e\x66\xcf\x11"),
+                "video/wmv",
+                Video,
+            ),
+            (transport_stream(), "video/mp2t", Video),
+            (
+                padded(b"OggS\x00\x02\x00\x00\x80theora"),
+                "video/ogg",
+                Video,
+            ),
+            (
+                padded(b"OggS\x00\x02\x00\x00\x01vorbis"),
+                "audio/ogg",
+                Audio,
+            ),
+            (padded(b"ID3\x04\x00"), "audio/mpeg", Audio),
+            (padded(b"\xff\xfb\x90\x64"), "audio/mpeg", Audio),
+            (padded(b"fLaC"), "audio/flac", Audio),
+            (padded(b"MThd\x00\x00\x00\x06"), "audio/midi", Audio),
+            (padded(b"%PDF-1.7"), "application/pdf", Pdf),
+            (padded(b"PK\x05\x06"), "application/zip", Archive),
+            (padded(b"PK\x07\x08"), "application/zip", Archive),
+            (padded(b"\x1f\x8b\x08"), "application/gzip", Archive),
+            (padded(b"BZh91AY&SY"), "application/x-bzip2", Archive),
+            (padded(b"\xfd7zXZ\x00"), "application/x-xz", Archive),
+            (padded(b"\x28\xb5\x2f\xfd"), "application/zstd", Archive),
+            (
+                padded(b"7z\xbc\xaf\x27\x1c"),
+                "application/x-7z-compressed",
+                Archive,
+            ),
+            (
+                padded(b"Rar!\x1a\x07\x01\x00"),
+                "application/vnd.rar",
+                Archive,
+            ),
+            (tar(), "application/x-tar", Archive),
+            (
+                padded(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1"),
+                "application/x-ole-storage",
+                Document,
+            ),
+            (padded(b"{\\rtf1\\ansi"), "application/rtf", Document),
+            (
+                padded(b"\x7fELF\x02\x01"),
+                "application/x-executable",
+                Other,
+            ),
+            (
+                padded(b"MZ\x90\x00"),
+                "application/vnd.microsoft.portable-executable",
+                Other,
+            ),
+            (
+                padded(b"\xcf\xfa\xed\xfe"),
+                "application/x-mach-binary",
+                Other,
+            ),
+            (
+                padded(b"\xca\xfe\xba\xbe"),
+                "application/x-mach-binary",
+                Other,
+            ),
+            (
+                padded(b"\x00asm\x01\x00\x00\x00"),
+                "application/wasm",
+                Other,
+            ),
+            (
+                padded(b"SQLite format 3\x00"),
+                "application/vnd.sqlite3",
+                Other,
+            ),
+        ];
+        for (sample, mime, category) in cases {
+            assert_eq!(sniff(&sample), Some((mime, category)), "{}", mime);
+        }
+    }
+
+    #[test]
+    fn unknown_content_has_no_signature() {
+        assert_eq!(sniff(b""), None);
+        assert_eq!(sniff(b"hello world"), None);
+        assert_eq!(sniff(&padded(b"RIFF\x24\x00\x00\x00CDXA")), None);
+        // Too short to hold the brand
+        assert_eq!(sniff(b"\x00\x00\x00\x18ftyp"), None);
+    }
+
+    #[test]
+    fn tells_office_documents_from_plain_zips() {
+        let content_types = zip_entry("[Content_Types].xml", "<Types/>");
+        let cases = [
+            (
+                zip_entry("mimetype", "application/vnd.oasis.opendocument.text"),
+                "application/vnd.oasis.opendocument.text",
+                Document,
+            ),
+            (
+                zip_entry("mimetype", "application/vnd.oasis.opendocument.spreadsheet"),
+                "application/vnd.oasis.opendocument.spreadsheet",
+                Document,
+            ),
+            (
+                zip_entry(
+                    "mimetype",
+                    "application/vnd.oasis.opendocument.presentation",
+                ),
+                "application/vnd.oasis.opendocument.presentation",
+                Document,
+            ),
+            (
+                zip_entry("mimetype", "application/epub+zip"),
+                "application/epub+zip",
+                Document,
+            ),
+            (
+                [content_types.clone(), zip_entry("word/document.xml", "")].concat(),
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                Document,
+            ),
+            (
+                [content_types.clone(), zip_entry("xl/workbook.xml", "")].concat(),
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                Document,
+            ),
+            (
+                [
+                    zip_entry("_rels/.rels", ""),
+                    zip_entry("ppt/presentation.xml", ""),
+                ]
+                .concat(),
+                "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+                Document,
+            ),
+            (
+                zip_entry("notes/readme.txt", "hello"),
+                "application/zip",
+                Archive,
+            ),
+            // Folder names alone do not make a document
+            (
+                zip_entry("word/notes.txt", "hello"),
+                "application/zip",
+                Archive,
+            ),
+            (
+                zip_entry("mimetype", "application/x-unknown"),
+                "application/zip",
+                Archive,
+            ),
+            (content_types, "application/zip", Archive),
+        ];
+        for (sample, mime, category) in cases {
+            assert_eq!(sniff(&sample), Some((mime, category)), "{}", mime);
+        }
+    }
+
+    #[test]
+    fn classifies_text_by_content_then_extension() {
+        let cases = [
+            (
+                "logo.txt",
+                "<svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+                "image/svg+xml",
+                Image,
+            ),
+            (
+                "logo",
+                "<?xml version=\"1.0\"?>\n<svg/>",
+                "image/svg+xml",
+                Image,
+            ),
+            (
+                "page.txt",
+                "\u{FEFF}  <!DOCTYPE html>\n<html>",
+                "text/html",
+                Code,
+            ),
+            ("page", "<HTML><body/></HTML>", "text/html", Code),
+            (
+                "run",
+                "#!/usr/bin/env python3\nprint(1)",
+                "text/x-python",
+                Code,
+            ),
+            ("run.txt", "#!/usr/bin/env node\n", "text/javascript", Code),
+            ("run", "#!/bin/sh\necho python", "text/x-shellscript", Code),
+            ("main.rs", "fn main() {}", "text/x-rust", Code),
+            ("App.TSX", "export {}", "text/typescript", Code),
+            ("setup.py", "import os", "text/x-python", Code),
+            ("Page.svelte", "<script></script>", "text/plain", Code),
+            ("config.yml", "a: 1", "application/yaml", Code),
+            ("pom.xml", "<project/>", "application/xml", Code),
+            (
+                "layout.conf",
+                "<?xml version=\"1.0\"?><layout/>",
+                "application/xml",
+                Code,
+            ),
+            ("README.md", "# Title", "text/markdown", Text),
+            ("data.csv", "a,b\n1,2", "text/csv", Text),
+            ("notes", "just some notes", "text/plain", Text),
+        ];
+        for (name, content, mime, category) in cases {
+            assert_eq!(
+                classify_text(Path::new(name), content.as_bytes()),
+                (mime, category),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn content_wins_over_a_misleading_extension() {
+        let sandbox = Sandbox::new("detect");
+        let cases = [
+            // Text saved under a media name
+            (
+                "movie.mp4",
+                b"just some notes\n".to_vec(),
+                "text/plain",
+                Text,
+                true,
+                false,
+            ),
+            (
+                "setup.exe",
+                b"fn main() {}\n".to_vec(),
+                "text/plain",
+                Text,
+                true,
+                false,
+            ),
+            // Binaries saved under a text or image name
+            (
+                "notes.txt",
+                padded(b"\x00\x00\x00\x20ftypisom"),
+                "video/mp4",
+                Video,
+                false,
+                true,
+            ),
+            (
+                "photo.png",
+                padded(b"MZ\x90\x00"),
+                "application/vnd.microsoft.portable-executable",
+                Other,
+                false,
+                false,
+            ),
+            (
+                "image.txt",
+                padded(b"\x89PNG\r\n\x1a\n"),
+                "image/png",
+                Image,
+                false,
+                true,
+            ),
+            (
+                "blob.md",
+                padded(b"\x00\x01\x02"),
+                "application/octet-stream",
+                Other,
+                false,
+                false,
+            ),
+        ];
+        for (name, content, mime, category, is_text, uploadable) in cases {
+            let detected = detect(&sandbox.file(name, content)).unwrap();
+            assert_eq!(detected.mime_type, mime, "{}", name);
+            assert_eq!(detected.category, category, "{}", name);
+            assert_eq!(detected.is_text, is_text, "{}", name);
+            assert_eq!(detected.uploadable, uploadable, "{}", name);
+        }
+    }
+
+    #[test]
+    fn text_that_starts_like_a_signature_stays_text() {
+        let sandbox = Sandbox::new("detect");
+        let cases = [
+            (
+                "dos.md",
+                "MZ is the DOS executable signature\n",
+                "text/markdown",
+                Text,
+            ),
+            ("notes.txt", "BZh, said nobody\n", "text/plain", Text),
+            (
+                "GIF.md",
+                "GIF89a was the animated one\n",
+                "text/markdown",
+                Text,
+            ),
+            ("fmt.rs", "ID3 tags come first\n", "text/x-rust", Code),
+        ];
+        for (name, content, mime, category) in cases {
+            let detected = detect(&sandbox.file(name, content)).unwrap();
+            assert_eq!(detected.mime_type, mime, "{}", name);
+            assert_eq!(detected.category, category, "{}", name);
+            assert!(detected.is_text, "{}", name);
+        }
+    }
+
+    #[test]
+    fn pdf_and_rtf_are_sniffed_even_though_they_look_like_text() {
+        let sandbox = Sandbox::new("detect");
+        let pdf = detect(&sandbox.file("paper", "%PDF-1.4\n1 0 obj\n")).unwrap();
+        assert_eq!(pdf.mime_type, "application/pdf");
+        assert!(!pdf.is_text);
+        assert!(pdf.uploadable);
+
+        let rtf = detect(&sandbox.file("letter.txt", "{\\rtf1\\ansi Hello}")).unwrap();
+        assert_eq!(rtf.mime_type, "application/rtf");
+        assert_eq!(rtf.category, Document);
+        assert!(rtf.is_text);
+        assert!(!rtf.uploadable);
+    }
+
+    #[test]
+    fn detect_refuses_directories() {
+        let sandbox = Sandbox::new("detect");
+        assert_eq!(
+            detect(&sandbox.dir("folder")).unwrap_err(),
+            "Path is not a file"
+        );
+    }
+
+    fn file_type(mime_type: &str, category: FileCategory, size: u64) -> FileType {
+        FileType {
+            path: PathBuf::from("file"),
+            mime_type: mime_type.to_string(),
+            category,
+            is_text: false,
+            encoding: Encoding::Binary,
+            size,
+            uploadable: false,
+        }
+    }
+
+    #[test]
+    fn decides_what_may_be_uploaded() {
+        const MB: u64 = 1024 * 1024;
+        let cases = [
+            (file_type("image/png", Image, MB), Ok(())),
+            (file_type("application/pdf", Pdf, 7 * MB), Ok(())),
+            (
+                file_type("image/jpeg", Image, 7 * MB + 1),
+                Err("File size (7.00 MB) exceeds the 7 MB limit for images and PDFs."),
+            ),
+            (
+                file_type("application/pdf", Pdf, 8 * MB),
+                Err("File size (8.00 MB) exceeds the 7 MB limit for images and PDFs."),
+            ),
+            (file_type("video/mp4", Video, 45 * MB), Ok(())),
+            (
+                file_type("video/webm", Video, 46 * MB),
+                Err("File size (46.00 MB) exceeds the 45 MB limit for videos."),
+            ),
+            (
+                file_type("image/gif", Image, 10),
+                Err("Unsupported file type: image/gif. This tool supports images (PNG, JPEG, WEBP), PDFs, and videos."),
+            ),
+            (
+                file_type("video/x-matroska", Video, 10),
+                Err("Unsupported file type: video/x-matroska. This tool supports images (PNG, JPEG, WEBP), PDFs, and videos."),
+            ),
+        ];
+        for (file_type, expected) in cases {
+            assert_eq!(
+                file_type.check_uploadable(),
+                expected.map_err(str::to_string),
+                "{}",
+                file_type.mime_type
+            );
+        }
+    }
+}
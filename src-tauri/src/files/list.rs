@@ -0,0 +1,499 @@
+use super::{is_hidden, to_millis};
+use crate::policy::{Access, PathPolicy};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use tauri::ipc::Channel;
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    File,
+    Directory,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPermissions {
+    pub readonly: bool,
+    /// Unix permission bits, e.g. `0o644`
+    pub mode: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirEntry {
+    pub name: String,
+    pub path: PathBuf,
+    /// What the entry is, following symlinks; a dangling link is `Other`
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Milliseconds since the Unix epoch
+    pub modified: Option<u64>,
+    pub permissions: EntryPermissions,
+    pub is_symlink: bool,
+    pub symlink_target: Option<PathBuf>,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Modified,
+    Kind,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ListOptions {
+    pub sort_by: SortKey,
+    pub descending: bool,
+    pub directories_first: bool,
+    pub show_hidden: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            sort_by: SortKey::Name,
+            descending: false,
+            directories_first: true,
+            show_hidden: true,
+            offset: 0,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirListing {
+    pub path: PathBuf,
+    pub entries: Vec<DirEntry>,
+    /// Number of entries before pagination
+    pub total: usize,
+    pub has_more: bool,
+}
+
+fn permissions(metadata: &Metadata) -> EntryPermissions {
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        Some(metadata.permissions().mode() & 0o7777)
+    };
+    #[cfg(not(unix))]
+    let mode = None;
+
+    EntryPermissions {
+        readonly: metadata.permissions().readonly(),
+        mode,
+    }
+}
+
+/// Builds the entry for `path` without failing on broken symlinks.
+pub fn read_entry(path: &Path) -> std::io::Result<DirEntry> {
+    let link_metadata = fs::symlink_metadata(path)?;
+    let is_symlink = link_metadata.file_type().is_symlink();
+    let symlink_target = if is_symlink {
+        fs::read_link(path).ok()
+    } else {
+        None
+    };
+    // Describe what a link points at, but fall back to the link itself if it dangles
+    let metadata = if is_symlink {
+        fs::metadata(path).unwrap_or_else(|_| link_metadata.clone())
+    } else {
+        link_metadata
+    };
+
+    let kind = if metadata.is_dir() {
+        EntryKind::Directory
+    } else if metadata.is_file() {
+        EntryKind::File
+    } else {
+        EntryKind::Other
+    };
+
+    Ok(DirEntry {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: path.to_path_buf(),
+        kind,
+        size: if kind == EntryKind::File { metadata.len() } else { 0 },
+        modified: metadata.modified().ok().and_then(to_millis),
+        permissions: permissions(&metadata),
+        is_symlink,
+        symlink_target,
+        hidden: is_hidden(path, &metadata),
+    })
+}
+
+fn open_dir(path: &str, access: &PathPolicy) -> Result<(PathBuf, fs::ReadDir), String> {
+    let dir = access.check(path, Access::Read)?;
+    let metadata = fs::metadata(&dir).map_err(|_| "Directory does not exist".to_string())?;
+    if !metadata.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+    let read_dir = fs::read_dir(&dir).map_err(|e| format!("Failed to list directory: {}", e))?;
+    Ok((dir, read_dir))
+}
+
+/// Reads the entries of a directory opened with `open_dir`, leaving out the
+/// ones `access` denies.
+fn read_entries<'a>(
+    read_dir: fs::ReadDir,
+    access: &'a PathPolicy,
+) -> impl Iterator<Item = DirEntry> + 'a {
+    read_dir.filter_map(move |entry| {
+        let entry = entry.ok()?;
+        if !access.permits_entry(&entry.path(), Access::Read) {
+            return None;
+        }
+        match read_entry(&entry.path()) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                eprintln!("Could not read entry {}: {}", entry.path().display(), e);
+                None
+            }
+        }
+    })
+}
+
+fn compare(a: &DirEntry, b: &DirEntry, options: &ListOptions) -> Ordering {
+    if options.directories_first {
+        let a_dir = a.kind == EntryKind::Directory;
+        let b_dir = b.kind == EntryKind::Directory;
+        if a_dir != b_dir {
+            return b_dir.cmp(&a_dir);
+        }
+    }
+
+    let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+    let ordering = match options.sort_by {
+        SortKey::Name => by_name(),
+        SortKey::Size => a.size.cmp(&b.size).then_with(by_name),
+        SortKey::Modified => a.modified.cmp(&b.modified).then_with(by_name),
+        SortKey::Kind => extension(&a.name).cmp(&extension(&b.name)).then_with(by_name),
+    };
+    if options.descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn extension(name: &str) -> String {
+    Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Lists `path`, leaving out entries `access` denies, names included.
+pub fn list(path: &str, options: &ListOptions, access: &PathPolicy) -> Result<DirListing, String> {
+    let (dir, read_dir) = open_dir(path, access)?;
+    let mut entries: Vec<DirEntry> = read_entries(read_dir, access)
+        .filter(|entry| options.show_hidden || !entry.hidden)
+        .collect();
+    entries.sort_by(|a, b| compare(a, b, options));
+
+    let total = entries.len();
+    let end = options
+        .limit
+        .map_or(total, |limit| options.offset.saturating_add(limit).min(total));
+    let entries = if options.offset < total {
+        entries.drain(options.offset..end).collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(DirListing {
+        path: dir,
+        entries,
+        total,
+        has_more: end < total,
+    })
+}
+
+/// Lists a directory natively, without going through the sidecar.
+#[tauri::command]
+pub async fn list_directory(
+    path: String,
+    options: Option<ListOptions>,
+) -> Result<DirListing, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        list(&path, &options.unwrap_or_default(), &PathPolicy::current())
+    })
+    .await
+    .map_err(|e| format!("Failed to list directory: {}", e))?
+}
+
+/// Streams a directory's entries in unsorted batches as they are read, for folders
+/// too large to list in one response. Returns the number of entries sent.
+#[tauri::command]
+pub async fn stream_directory(
+    path: String,
+    batch_size: Option<usize>,
+    show_hidden: Option<bool>,
+    on_batch: Channel<Vec<DirEntry>>,
+) -> Result<usize, String> {
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let show_hidden = show_hidden.unwrap_or(true);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let access = PathPolicy::current();
+        let (_, read_dir) = open_dir(&path, &access)?;
+        let mut sent = 0;
+        let mut batch = Vec::with_capacity(batch_size);
+        for entry in read_entries(read_dir, &access).filter(|entry| show_hidden || !entry.hidden) {
+            batch.push(entry);
+            if batch.len() == batch_size {
+                sent += batch.len();
+                on_batch
+                    .send(std::mem::take(&mut batch))
+                    .map_err(|e| format!("Failed to send directory batch: {}", e))?;
+            }
+        }
+        if !batch.is_empty() {
+            sent += batch.len();
+            on_batch
+                .send(batch)
+                .map_err(|e| format!("Failed to send directory batch: {}", e))?;
+        }
+        Ok(sent)
+    })
+    .await
+    .map_err(|e| format!("Failed to list directory: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    fn names(sandbox: &Sandbox, options: &ListOptions) -> Vec<String> {
+        let listing = list(
+            sandbox.base.to_str().unwrap(),
+            options,
+            &sandbox.policy(&[]),
+        )
+        .unwrap();
+        listing
+            .entries
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect()
+    }
+
+    fn sorted(sort_by: SortKey, descending: bool) -> ListOptions {
+        ListOptions {
+            sort_by,
+            descending,
+            ..Default::default()
+        }
+    }
+
+    /// Files whose name, size, extension and age all sort differently.
+    fn populated() -> Sandbox {
+        let sandbox = Sandbox::new("list");
+        let epoch = SystemTime::UNIX_EPOCH;
+        // Same age, so only the name tells them apart
+        for name in ["zeta", "Alpha"] {
+            let dir = File::open(sandbox.dir(name)).unwrap();
+            dir.set_modified(epoch).unwrap();
+        }
+        for (name, size, age) in [("b.txt", 30, 1), ("C.md", 10, 3), ("a.rs", 20, 2)] {
+            let path = sandbox.file(name, "x".repeat(size));
+            let file = File::options().write(true).open(path).unwrap();
+            file.set_modified(epoch + Duration::from_secs(1_000_000 - age * 1000))
+                .unwrap();
+        }
+        sandbox
+    }
+
+    #[test]
+    fn sorts_by_each_key_with_directories_first() {
+        let sandbox = populated();
+        let cases = [
+            (
+                SortKey::Name,
+                false,
+                ["Alpha", "zeta", "a.rs", "b.txt", "C.md"],
+            ),
+            (
+                SortKey::Name,
+                true,
+                ["zeta", "Alpha", "C.md", "b.txt", "a.rs"],
+            ),
+            (
+                SortKey::Size,
+                false,
+                ["Alpha", "zeta", "C.md", "a.rs", "b.txt"],
+            ),
+            (
+                SortKey::Modified,
+                false,
+                ["Alpha", "zeta", "C.md", "a.rs", "b.txt"],
+            ),
+            (
+                SortKey::Modified,
+                true,
+                ["zeta", "Alpha", "b.txt", "a.rs", "C.md"],
+            ),
+            (
+                SortKey::Kind,
+                false,
+                ["Alpha", "zeta", "C.md", "a.rs", "b.txt"],
+            ),
+        ];
+        for (sort_by, descending, expected) in cases {
+            assert_eq!(
+                names(&sandbox, &sorted(sort_by, descending)),
+                expected,
+                "{:?} descending: {}",
+                sort_by,
+                descending
+            );
+        }
+    }
+
+    #[test]
+    fn directories_can_be_mixed_in() {
+        let sandbox = populated();
+        let options = ListOptions {
+            directories_first: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&sandbox, &options),
+            ["a.rs", "Alpha", "b.txt", "C.md", "zeta"]
+        );
+    }
+
+    #[test]
+    fn paginates_after_sorting() {
+        let sandbox = Sandbox::new("list");
+        for name in ["e", "d", "c", "b", "a"] {
+            sandbox.file(name, "");
+        }
+        let page = |offset, limit| {
+            let options = ListOptions {
+                offset,
+                limit,
+                ..Default::default()
+            };
+            let listing = list(
+                sandbox.base.to_str().unwrap(),
+                &options,
+                &sandbox.policy(&[]),
+            )
+            .unwrap();
+            let names: Vec<String> = listing
+                .entries
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect();
+            (names, listing.total, listing.has_more)
+        };
+        assert_eq!(page(0, Some(2)), (vec!["a".into(), "b".into()], 5, true));
+        assert_eq!(page(2, Some(2)), (vec!["c".into(), "d".into()], 5, true));
+        assert_eq!(page(4, Some(2)), (vec!["e".into()], 5, false));
+        assert_eq!(page(3, None), (vec!["d".into(), "e".into()], 5, false));
+        assert_eq!(page(5, Some(2)), (vec![], 5, false));
+        assert_eq!(page(9, Some(usize::MAX)), (vec![], 5, false));
+    }
+
+    #[test]
+    fn hidden_entries_are_shown_unless_asked_not_to() {
+        let sandbox = Sandbox::new("list");
+        sandbox.file(".env", "");
+        sandbox.dir(".cache");
+        sandbox.file("visible.txt", "");
+        assert_eq!(
+            names(&sandbox, &ListOptions::default()),
+            [".cache", ".env", "visible.txt"]
+        );
+        let options = ListOptions {
+            show_hidden: false,
+            ..Default::default()
+        };
+        let listing = list(
+            sandbox.base.to_str().unwrap(),
+            &options,
+            &sandbox.policy(&[]),
+        )
+        .unwrap();
+        assert_eq!(listing.total, 1);
+        assert_eq!(listing.entries[0].name, "visible.txt");
+        assert!(!listing.entries[0].hidden);
+    }
+
+    #[test]
+    fn leaves_out_denied_entries() {
+        let sandbox = Sandbox::new("list");
+        sandbox.file(".ssh/id_ed25519", "key");
+        sandbox.file("server.pem", "certificate");
+        sandbox.file("notes.txt", "");
+        let access = sandbox.policy(&[".ssh", "**/*.pem"]);
+        let listing = list(
+            sandbox.base.to_str().unwrap(),
+            &ListOptions::default(),
+            &access,
+        )
+        .unwrap();
+        assert_eq!(listing.total, 1);
+        assert_eq!(listing.entries[0].name, "notes.txt");
+        assert!(list(
+            sandbox.path(".ssh").to_str().unwrap(),
+            &ListOptions::default(),
+            &access
+        )
+        .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lists_dangling_symlinks_as_links() {
+        let sandbox = Sandbox::new("list");
+        let missing = sandbox.path("missing.txt");
+        sandbox.link(&missing, sandbox.path("dangling"));
+        sandbox.dir("folder");
+        sandbox.link(sandbox.path("folder"), sandbox.path("shortcut"));
+
+        let listing = list(
+            sandbox.base.to_str().unwrap(),
+            &ListOptions::default(),
+            &sandbox.policy(&[]),
+        )
+        .unwrap();
+        let entry = |name: &str| {
+            listing
+                .entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .unwrap()
+        };
+        let dangling = entry("dangling");
+        assert_eq!(dangling.kind, EntryKind::Other);
+        assert!(dangling.is_symlink);
+        assert_eq!(dangling.symlink_target.as_deref(), Some(missing.as_path()));
+        assert_eq!(dangling.size, 0);
+        // A working link is described by what it points at
+        let shortcut = entry("shortcut");
+        assert_eq!(shortcut.kind, EntryKind::Directory);
+        assert!(shortcut.is_symlink);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::policy::{Access, PathPolicy};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod copy;
+pub mod detect;
+pub mod list;
+pub mod read;
+pub mod transfer;
+pub mod trash;
+pub mod tree;
+pub mod watch;
+pub mod write;
+
+/// The gate every native file command and agent tool passes through: checks
+/// `path` against the current [`PathPolicy`] and returns the path to operate on.
+pub(crate) fn check_access(path: impl AsRef<Path>, access: Access) -> Result<PathBuf, String> {
+    PathPolicy::current().check(path, access)
+}
+
+/// Milliseconds since the Unix epoch, as the frontend's `Date` expects.
+pub(crate) fn to_millis(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+/// What a directory operation would touch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Footprint {
+    /// Regular files, not counting directories and symlinks
+    pub files: u64,
+    pub bytes: u64,
+    /// False when the walk stopped early at the entry limit
+    pub complete: bool,
+}
+
+/// Counts the files under `path` and their total size, without following
+/// symlinks, giving up after `max_entries` entries. Unreadable directories
+/// are skipped.
+pub(crate) fn footprint(path: &Path, max_entries: usize) -> std::io::Result<Footprint> {
+    let mut footprint = Footprint {
+        complete: true,
+        ..Default::default()
+    };
+    let mut entries = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let metadata = std::fs::symlink_metadata(&path)?;
+        if metadata.is_dir() {
+            let Ok(children) = std::fs::read_dir(&path) else {
+                continue;
+            };
+            for entry in children.flatten() {
+                entries += 1;
+                if entries > max_entries {
+                    footprint.complete = false;
+                    return Ok(footprint);
+                }
+                pending.push(entry.path());
+            }
+        } else if metadata.is_file() {
+            footprint.files += 1;
+            footprint.bytes += metadata.len();
+        }
+    }
+    Ok(footprint)
+}
+
+#[cfg(unix)]
+pub(crate) fn is_hidden(path: &Path, _metadata: &std::fs::Metadata) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+#[cfg(windows)]
+pub(crate) fn is_hidden(path: &Path, metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+
+    metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+        || path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
@@ -0,0 +1,457 @@
+use super::check_access;
+use crate::policy::Access;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+// How much of the file is inspected to pick an encoding
+const SAMPLE_SIZE: usize = 8 * 1024;
+// Upper bound on a single read unless the caller asks for less
+pub const DEFAULT_MAX_BYTES: u64 = 1024 * 1024;
+// What `max_bytes` is clamped to; the whole range is held in memory twice
+const MAX_BYTES_LIMIT: u64 = 64 * 1024 * 1024;
+const SCAN_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Encoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    Binary,
+}
+
+impl Encoding {
+    /// Length of the byte order mark `sample` starts with, if any.
+    fn bom_len(self, sample: &[u8]) -> u64 {
+        match self {
+            Encoding::Utf8Bom => 3,
+            Encoding::Utf16Le if sample.starts_with(&[0xFF, 0xFE]) => 2,
+            Encoding::Utf16Be if sample.starts_with(&[0xFE, 0xFF]) => 2,
+            _ => 0,
+        }
+    }
+
+    /// Size of one code unit, which ranges are aligned to.
+    fn unit(self) -> u64 {
+        match self {
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    fn is_newline(self, unit: &[u8]) -> bool {
+        match self {
+            Encoding::Utf16Le => unit == [b'\n', 0],
+            Encoding::Utf16Be => unit == [0, b'\n'],
+            _ => unit == [b'\n'],
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReadOptions {
+    /// Byte offset to start reading at
+    pub offset: Option<u64>,
+    /// Number of bytes to read from `offset`
+    pub length: Option<u64>,
+    /// First line to return, 1-based. Takes precedence over `offset`/`length`.
+    pub start_line: Option<u64>,
+    /// Number of lines to return from `start_line`, at least 1
+    pub line_count: Option<u64>,
+    /// Cap on the bytes read, defaults to `DEFAULT_MAX_BYTES` and never exceeds
+    /// `MAX_BYTES_LIMIT`
+    pub max_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileContent {
+    pub path: PathBuf,
+    /// Decoded text, or `None` for binary files
+    pub content: Option<String>,
+    pub encoding: Encoding,
+    pub is_binary: bool,
+    /// Total size of the file in bytes
+    pub size: u64,
+    /// Byte range that was read, end exclusive
+    pub start: u64,
+    pub end: u64,
+    /// Line range that was returned, for line-based reads
+    pub start_line: Option<u64>,
+    pub end_line: Option<u64>,
+    /// Whether there is more of the requested range (or file) than was returned
+    pub truncated: bool,
+}
+
+/// Picks an encoding from a sample taken at the start of the file.
+pub fn detect_encoding(sample: &[u8]) -> Encoding {
+    if sample.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Encoding::Utf8Bom;
+    }
+    if sample.starts_with(&[0xFF, 0xFE]) {
+        return Encoding::Utf16Le;
+    }
+    if sample.starts_with(&[0xFE, 0xFF]) {
+        return Encoding::Utf16Be;
+    }
+    if sample.is_empty() {
+        return Encoding::Utf8;
+    }
+
+    // BOM-less UTF-16 text is mostly ASCII, so every other byte is zero
+    let pairs = sample.len() / 2;
+    if pairs >= 2 {
+        let even_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd_zeros = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        if odd_zeros * 10 >= pairs * 4 && even_zeros * 10 <= pairs {
+            return Encoding::Utf16Le;
+        }
+        if even_zeros * 10 >= pairs * 4 && odd_zeros * 10 <= pairs {
+            return Encoding::Utf16Be;
+        }
+    }
+
+    if sample.contains(&0) {
+        return Encoding::Binary;
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => return Encoding::Utf8,
+        // The sample may end in the middle of a multi-byte character
+        Err(e) if e.error_len().is_none() => return Encoding::Utf8,
+        Err(_) => {}
+    }
+
+    // Not UTF-8: treat it as Latin-1 text unless it is full of control characters
+    let controls = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B))
+        .count();
+    if controls * 20 <= sample.len() {
+        Encoding::Latin1
+    } else {
+        Encoding::Binary
+    }
+}
+
+/// Decodes `bytes`, dropping partial characters at either end of the range.
+pub fn decode(bytes: &[u8], encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Utf8 | Encoding::Utf8Bom => {
+            // Skip continuation bytes of a character that started before the range
+            let start = bytes
+                .iter()
+                .take(3)
+                .take_while(|&&b| b & 0xC0 == 0x80)
+                .count();
+            let bytes = &bytes[start..];
+            match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_string(),
+                Err(e) if e.error_len().is_none() => {
+                    String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned()
+                }
+                Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+            }
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units = bytes.chunks_exact(2).map(|pair| {
+                if encoding == Encoding::Utf16Le {
+                    u16::from_le_bytes([pair[0], pair[1]])
+                } else {
+                    u16::from_be_bytes([pair[0], pair[1]])
+                }
+            });
+            let mut text: String = char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect();
+            // A surrogate pair cut by the range boundary decodes as a lone replacement
+            if text.ends_with(char::REPLACEMENT_CHARACTER) {
+                text.pop();
+            }
+            if text.starts_with(char::REPLACEMENT_CHARACTER) {
+                text.remove(0);
+            }
+            text
+        }
+        Encoding::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+        Encoding::Binary => String::new(),
+    }
+}
+
+/// Finds the byte range covering `line_count` lines starting at 1-based `start_line`,
+/// scanning from `from` in constant memory. Returns the range and the number of
+/// lines it covers.
+fn line_range(
+    file: &mut File,
+    encoding: Encoding,
+    from: u64,
+    size: u64,
+    start_line: u64,
+    line_count: Option<u64>,
+) -> std::io::Result<(u64, u64, u64)> {
+    let unit = encoding.unit() as usize;
+    let mut line = 1;
+    let mut start = if start_line <= 1 { Some(from) } else { None };
+    let mut lines_in_range = 0;
+    // Where the line following the most recent newline begins
+    let mut line_start = from;
+
+    file.seek(SeekFrom::Start(from))?;
+    let mut position = from;
+    let mut buf = vec![0u8; SCAN_CHUNK_SIZE];
+    loop {
+        let read = read_full(file, &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        // Chunks are a multiple of the unit size, so units never straddle chunks
+        for (i, code_unit) in buf[..read].chunks(unit).enumerate() {
+            if !encoding.is_newline(code_unit) {
+                continue;
+            }
+            let next = position + ((i + 1) * unit) as u64;
+            line += 1;
+            line_start = next;
+            match start {
+                None if line == start_line => start = Some(next),
+                Some(start) => {
+                    lines_in_range += 1;
+                    if line_count.is_some_and(|count| lines_in_range >= count) {
+                        return Ok((start, next, lines_in_range));
+                    }
+                }
+                None => {}
+            }
+        }
+        position += read as u64;
+    }
+
+    match start {
+        // The last line has no trailing newline
+        Some(start) if line_start.max(start) < size => Ok((start, size, lines_in_range + 1)),
+        Some(start) => Ok((start, size, lines_in_range)),
+        None => Ok((size, size, 0)),
+    }
+}
+
+pub(super) fn read_full(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+pub fn read(path: &Path, options: &ReadOptions) -> Result<FileContent, String> {
+    if options.line_count == Some(0) {
+        return Err("lineCount must be at least 1".to_string());
+    }
+    let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let metadata = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    if !metadata.is_file() {
+        return Err("Path is not a file".to_string());
+    }
+    let size = metadata.len();
+    let max_bytes = options
+        .max_bytes
+        .unwrap_or(DEFAULT_MAX_BYTES)
+        .min(MAX_BYTES_LIMIT);
+
+    let mut sample = vec![0u8; SAMPLE_SIZE.min(size as usize)];
+    read_full(&mut file, &mut sample).map_err(|e| format!("Failed to read file: {}", e))?;
+    let encoding = detect_encoding(&sample);
+    let content_start = encoding.bom_len(&sample);
+
+    let (start, mut end, mut lines) = if let Some(start_line) = options.start_line {
+        let (start, end, lines) = line_range(
+            &mut file,
+            encoding,
+            content_start,
+            size,
+            start_line.max(1),
+            options.line_count,
+        )
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+        (start, end, Some(lines))
+    } else {
+        let unit = encoding.unit();
+        // Keep UTF-16 reads aligned to code units
+        let offset = options.offset.unwrap_or(0).max(content_start);
+        let offset = offset - (offset - content_start) % unit;
+        let end = match options.length {
+            Some(length) => offset.saturating_add(length).min(size),
+            None => size,
+        };
+        (offset.min(size), end, None)
+    };
+
+    let truncated = end - start > max_bytes;
+    if truncated {
+        end = start + max_bytes - max_bytes % encoding.unit();
+        // Lines past the cap were not returned, so the count no longer holds
+        lines = None;
+    }
+
+    let mut bytes = vec![0u8; (end - start) as usize];
+    file.seek(SeekFrom::Start(start))
+        .and_then(|_| read_full(&mut file, &mut bytes))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let is_binary = encoding == Encoding::Binary;
+    let start_line = options.start_line.map(|line| line.max(1));
+    Ok(FileContent {
+        path: path.to_path_buf(),
+        content: if is_binary { None } else { Some(decode(&bytes, encoding)) },
+        encoding,
+        is_binary,
+        size,
+        start,
+        end,
+        start_line,
+        end_line: start_line
+            .zip(lines.filter(|&count| count > 0))
+            .map(|(first, count)| first + count - 1),
+        truncated,
+    })
+}
+
+/// Reads a file natively, detecting its encoding and optionally limiting the read
+/// to a byte or line range.
+#[tauri::command]
+pub async fn read_file(path: String, options: Option<ReadOptions>) -> Result<FileContent, String> {
+    let path = check_access(&path, Access::Read)?;
+    tauri::async_runtime::spawn_blocking(move || read(&path, &options.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn utf16be(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    fn lines(
+        sandbox: &Sandbox,
+        contents: impl AsRef<[u8]>,
+        start_line: u64,
+        line_count: Option<u64>,
+    ) -> FileContent {
+        let path = sandbox.file("lines.txt", contents);
+        read(
+            &path,
+            &ReadOptions {
+                start_line: Some(start_line),
+                line_count,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn detects_byte_order_marks() {
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFhello"), Encoding::Utf8Bom);
+        assert_eq!(detect_encoding(b"\xFF\xFEh\0i\0"), Encoding::Utf16Le);
+        assert_eq!(detect_encoding(b"\xFE\xFF\0h\0i"), Encoding::Utf16Be);
+    }
+
+    #[test]
+    fn detects_utf16_without_a_byte_order_mark() {
+        assert_eq!(detect_encoding(&utf16le("plain text")), Encoding::Utf16Le);
+        assert_eq!(detect_encoding(&utf16be("plain text")), Encoding::Utf16Be);
+    }
+
+    #[test]
+    fn detects_utf8_even_when_the_sample_cuts_a_character() {
+        assert_eq!(detect_encoding(b""), Encoding::Utf8);
+        assert_eq!(detect_encoding("naïve".as_bytes()), Encoding::Utf8);
+        assert_eq!(detect_encoding(&"café".as_bytes()[..4]), Encoding::Utf8);
+    }
+
+    #[test]
+    fn tells_latin1_text_from_binary() {
+        assert_eq!(detect_encoding(b"caf\xe9 cr\xe8me"), Encoding::Latin1);
+        assert_eq!(detect_encoding(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Encoding::Binary);
+        assert_eq!(detect_encoding(b"\x01\x02\x03\x04\x05\xff\x06\x07"), Encoding::Binary);
+    }
+
+    #[test]
+    fn returns_the_requested_window_of_lines() {
+        let sandbox = Sandbox::new("read");
+        let content = lines(&sandbox, "one\ntwo\nthree\nfour\n", 2, Some(2));
+        assert_eq!(content.content.as_deref(), Some("two\nthree\n"));
+        assert_eq!((content.start_line, content.end_line), (Some(2), Some(3)));
+        assert!(!content.truncated);
+    }
+
+    #[test]
+    fn counts_a_last_line_without_a_newline() {
+        let sandbox = Sandbox::new("read");
+        let content = lines(&sandbox, "one\ntwo\nthree", 2, None);
+        assert_eq!(content.content.as_deref(), Some("two\nthree"));
+        assert_eq!(content.end_line, Some(3));
+    }
+
+    #[test]
+    fn windows_past_the_end_are_empty() {
+        let sandbox = Sandbox::new("read");
+        let content = lines(&sandbox, "one\ntwo\n", 5, Some(2));
+        assert_eq!(content.content.as_deref(), Some(""));
+        assert_eq!(content.end_line, None);
+    }
+
+    #[test]
+    fn windows_utf16_files_by_code_unit() {
+        let sandbox = Sandbox::new("read");
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(utf16le("one\ntwo\nthree\n"));
+        let content = lines(&sandbox, bytes, 2, Some(1));
+        assert_eq!(content.encoding, Encoding::Utf16Le);
+        assert_eq!(content.content.as_deref(), Some("two\n"));
+    }
+
+    #[test]
+    fn rejects_a_zero_line_count() {
+        let sandbox = Sandbox::new("read");
+        let path = sandbox.file("lines.txt", "one\ntwo\n");
+        let options = ReadOptions {
+            start_line: Some(1),
+            line_count: Some(0),
+            ..Default::default()
+        };
+        assert!(read(&path, &options).is_err());
+    }
+
+    #[test]
+    fn truncates_at_max_bytes_and_drops_the_line_count() {
+        let sandbox = Sandbox::new("read");
+        let path = sandbox.file("lines.txt", "one\ntwo\nthree\n");
+        let options = ReadOptions {
+            start_line: Some(1),
+            max_bytes: Some(5),
+            ..Default::default()
+        };
+        let content = read(&path, &options).unwrap();
+        assert_eq!(content.content.as_deref(), Some("one\nt"));
+        assert!(content.truncated);
+        assert_eq!(content.end_line, None);
+    }
+}
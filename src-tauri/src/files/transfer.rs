@@ -0,0 +1,314 @@
+use super::check_access;
+use crate::policy::{Access, PathPolicy};
+use super::copy::{
+    copy_path, CancelToken, ConflictAnswer, ConflictPolicy, CopyConflict, CopyObserver,
+    CopyOptions, CopyProgress,
+};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// Keeps the event stream readable for trees with many small files
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MoveMethod {
+    /// Same filesystem: a single atomic rename
+    Rename,
+    /// Different filesystems: copied, verified, then removed from the source
+    Copy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveResult {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub method: MoveMethod,
+}
+
+/// Payload of the `move-progress` event, emitted while a cross-device move copies.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveProgress {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: u64,
+    pub files_total: u64,
+}
+
+/// Maps the copy engine's progress onto `move-progress`. Conflicts cannot be
+/// answered: the destination did not exist when the move started.
+struct MoveObserver<'a> {
+    report: &'a mut dyn FnMut(&MoveProgress, bool),
+}
+
+impl CopyObserver for MoveObserver<'_> {
+    fn progress(&mut self, progress: &CopyProgress, force: bool) {
+        (self.report)(
+            &MoveProgress {
+                source: progress.source.clone(),
+                destination: progress.destination.clone(),
+                bytes_done: progress.bytes_done,
+                bytes_total: progress.bytes_total,
+                files_done: progress.files_done,
+                files_total: progress.files_total,
+            },
+            force,
+        );
+    }
+
+    fn conflict(&mut self, _conflict: &CopyConflict) -> Option<ConflictAnswer> {
+        None
+    }
+}
+
+fn remove(path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Where `source` ends up: inside `destination` when that is an existing
+/// directory, at `destination` otherwise.
+pub fn resolve_destination(source: &Path, destination: &Path) -> Result<PathBuf, String> {
+    if destination.is_dir() {
+        let name = source.file_name().ok_or("Source path has no file name")?;
+        Ok(destination.join(name))
+    } else {
+        Ok(destination.to_path_buf())
+    }
+}
+
+/// Checks `destination` for writing, then resolves where `source` would end
+/// up there and checks that too: a deny pattern may match the final name.
+pub(crate) fn check_destination(
+    source: &Path,
+    destination: impl AsRef<Path>,
+) -> Result<PathBuf, String> {
+    let destination = check_access(destination, Access::Write)?;
+    check_access(resolve_destination(source, &destination)?, Access::Write)
+}
+
+/// Moves `source` to `destination` (already resolved with [`resolve_destination`]).
+/// Falls back to copy-then-delete when the two live on different filesystems.
+/// Refuses up front if `access` denies anything inside the tree, where it is
+/// or where it would land.
+pub fn move_path(
+    source: &Path,
+    destination: &Path,
+    access: &PathPolicy,
+    cancel: &CancelToken,
+    report: &mut dyn FnMut(&MoveProgress, bool),
+) -> Result<MoveResult, String> {
+    let metadata = fs::symlink_metadata(source)
+        .map_err(|_| format!("Source path does not exist: {}", source.display()))?;
+    if fs::symlink_metadata(destination).is_ok() {
+        return Err(format!("Destination already exists: {}", destination.display()));
+    }
+    if metadata.is_dir() && destination.starts_with(source) {
+        return Err("Cannot move a directory into itself".to_string());
+    }
+    // A rename carries the whole tree along without looking inside
+    access.check_tree(source, Access::Write, Some(destination))?;
+
+    let result = |method| MoveResult {
+        source: source.to_path_buf(),
+        destination: destination.to_path_buf(),
+        method,
+    };
+    match fs::rename(source, destination) {
+        Ok(()) => return Ok(result(MoveMethod::Rename)),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+        Err(e) => return Err(format!("Failed to move: {}", e)),
+    }
+
+    // Verified, because the source is deleted on the strength of this copy
+    let options = CopyOptions {
+        conflict: ConflictPolicy::Ask,
+        verify: true,
+        ..Default::default()
+    };
+    let mut observer = MoveObserver { report };
+    if let Err(e) = copy_path(source, destination, &options, access, cancel, &mut observer) {
+        // Everything under `destination` is ours, it did not exist before
+        if let Ok(copied) = fs::symlink_metadata(destination) {
+            let _ = remove(destination, &copied);
+        }
+        return Err(e);
+    }
+
+    remove(source, &metadata).map_err(|e| {
+        format!(
+            "Copied to '{}' but failed to remove the original: {}",
+            destination.display(),
+            e
+        )
+    })?;
+    Ok(result(MoveMethod::Copy))
+}
+
+/// Emits `move-progress` at most every [`PROGRESS_INTERVAL`], plus the first and last update.
+fn progress_emitter(app: AppHandle) -> impl FnMut(&MoveProgress, bool) {
+    let mut last_emit: Option<Instant> = None;
+    move |progress, force| {
+        if force || last_emit.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+            last_emit = Some(Instant::now());
+            let _ = app.emit("move-progress", progress);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn move_item(
+    app: AppHandle,
+    source: String,
+    destination: String,
+) -> Result<MoveResult, String> {
+    let source = check_access(&source, Access::Write)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let destination = check_destination(&source, &destination)?;
+        move_path(
+            &source,
+            &destination,
+            &PathPolicy::current(),
+            &CancelToken::default(),
+            &mut progress_emitter(app),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to move: {}", e))?
+}
+
+/// Gives `path` a new name in the same directory and returns the new path.
+/// `new_name` must be a plain name, and nothing may already be called that.
+pub fn rename(path: &Path, new_name: &str, access: &PathPolicy) -> Result<PathBuf, String> {
+    if Path::new(new_name).file_name() != Some(new_name.as_ref()) {
+        return Err(format!("'{}' is not a valid name", new_name));
+    }
+    if fs::symlink_metadata(path).is_err() {
+        return Err(format!("File or directory does not exist: {}", path.display()));
+    }
+    let renamed = access.check(path.with_file_name(new_name), Access::Write)?;
+    if fs::symlink_metadata(&renamed).is_ok() {
+        return Err(format!(
+            "A file or directory with the name '{}' already exists",
+            new_name
+        ));
+    }
+    // Renaming a directory renames the path of everything inside it
+    access.check_tree(path, Access::Write, Some(&renamed))?;
+    fs::rename(path, &renamed).map_err(|e| format!("Failed to rename: {}", e))?;
+    Ok(renamed)
+}
+
+#[tauri::command]
+pub async fn rename_item(path: String, new_name: String) -> Result<PathBuf, String> {
+    let path = check_access(&path, Access::Write)?;
+    tauri::async_runtime::spawn_blocking(move || rename(&path, &new_name, &PathPolicy::current()))
+        .await
+        .map_err(|e| format!("Failed to rename: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    fn move_to(
+        sandbox: &Sandbox,
+        from: &str,
+        to: &str,
+        access: &PathPolicy,
+    ) -> Result<MoveResult, String> {
+        move_path(
+            &sandbox.path(from),
+            &sandbox.path(to),
+            access,
+            &CancelToken::default(),
+            &mut |_, _| {},
+        )
+    }
+
+    #[test]
+    fn moves_trees_the_policy_allows() {
+        let sandbox = Sandbox::new("transfer");
+        sandbox.file("config/chrome/Cookies", "session");
+        move_to(&sandbox, "config", "moved", &sandbox.policy(&[])).unwrap();
+        assert!(sandbox.path("moved/chrome/Cookies").exists());
+        assert!(!sandbox.path("config").exists());
+    }
+
+    #[test]
+    fn refuses_trees_holding_denied_entries() {
+        let sandbox = Sandbox::new("transfer");
+        sandbox.file("config/chrome/Cookies", "session");
+        let access = sandbox.policy(&["config/chrome"]);
+        let result = move_to(&sandbox, "config", "moved", &access);
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert!(sandbox.path("config/chrome/Cookies").exists());
+        assert!(!sandbox.path("moved").exists());
+    }
+
+    #[test]
+    fn refuses_entries_that_would_land_in_denied_paths() {
+        let sandbox = Sandbox::new("transfer");
+        sandbox.file("keys/ssh/id_ed25519", "key");
+        let access = sandbox.policy(&["moved/ssh"]);
+        let result = move_to(&sandbox, "keys", "moved", &access);
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert!(sandbox.path("keys/ssh/id_ed25519").exists());
+    }
+
+    #[test]
+    fn renames_in_place() {
+        let sandbox = Sandbox::new("transfer");
+        let path = sandbox.file("notes/draft.md", "text");
+        let renamed = rename(&path, "final.md", &sandbox.policy(&[])).unwrap();
+        assert_eq!(renamed, sandbox.path("notes/final.md"));
+        assert!(renamed.exists());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn rename_refuses_names_that_leave_the_directory() {
+        let sandbox = Sandbox::new("transfer");
+        let path = sandbox.file("notes/draft.md", "text");
+        let access = sandbox.policy(&[]);
+        for name in ["../draft.md", "sub/draft.md", "..", ""] {
+            assert!(rename(&path, name, &access).is_err(), "{:?} was accepted", name);
+        }
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn rename_refuses_existing_and_denied_names() {
+        let sandbox = Sandbox::new("transfer");
+        let path = sandbox.file("notes/draft.md", "text");
+        sandbox.file("notes/taken.md", "other");
+        let access = sandbox.policy(&["notes/*.key"]);
+        let taken = rename(&path, "taken.md", &access);
+        assert!(matches!(taken, Err(e) if e.contains("already exists")));
+        let denied = rename(&path, "secret.key", &access);
+        assert!(matches!(denied, Err(e) if e.contains("disallowed")));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn rename_refuses_directories_whose_entries_would_become_denied() {
+        let sandbox = Sandbox::new("transfer");
+        sandbox.file("keys/id_ed25519", "key");
+        let access = sandbox.policy(&["ssh/**"]);
+        let result = rename(&sandbox.path("keys"), "ssh", &access);
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert!(sandbox.path("keys/id_ed25519").exists());
+    }
+}
@@ -0,0 +1,712 @@
+use super::check_access;
+use crate::policy::{Access, PathPolicy};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An item sitting in one of the user's trash directories.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItem {
+    /// Location of the item inside the trash; pass it back to restore or purge it
+    pub id: PathBuf,
+    pub name: String,
+    pub original_path: PathBuf,
+    /// Milliseconds since the Unix epoch, if the trash info was readable
+    pub deleted_at: Option<u64>,
+    pub is_directory: bool,
+    /// Only known for files; directories would need a full walk
+    pub size: Option<u64>,
+}
+
+/// The freedesktop.org Trash specification, as implemented by GNOME, KDE and
+/// friends: <https://specifications.freedesktop.org/trash-spec/latest/>
+#[cfg(all(unix, not(target_os = "macos")))]
+mod freedesktop {
+    use super::TrashItem;
+    use crate::files::to_millis;
+    use std::fs::{self, OpenOptions};
+    use std::io::{self, Write};
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const INFO_EXTENSION: &str = ".trashinfo";
+    const STICKY_BIT: u32 = 0o1000;
+
+    /// A trash directory with its `files` and `info` subdirectories.
+    struct TrashDir {
+        root: PathBuf,
+        /// Volume root for per-volume trashes, which relative `Path=` keys are relative to
+        topdir: Option<PathBuf>,
+    }
+
+    impl TrashDir {
+        fn files(&self) -> PathBuf {
+            self.root.join("files")
+        }
+
+        fn info(&self) -> PathBuf {
+            self.root.join("info")
+        }
+
+        fn info_file(&self, name: &std::ffi::OsStr) -> PathBuf {
+            let mut file = name.to_os_string();
+            file.push(INFO_EXTENSION);
+            self.info().join(file)
+        }
+
+        fn create(&self) -> io::Result<()> {
+            let mut builder = fs::DirBuilder::new();
+            builder.recursive(true).mode(0o700);
+            builder.create(self.files())?;
+            builder.create(self.info())
+        }
+    }
+
+    fn uid() -> u32 {
+        // SAFETY: getuid takes no arguments and cannot fail
+        unsafe { libc::getuid() }
+    }
+
+    /// `$XDG_DATA_HOME/Trash`, defaulting to `~/.local/share/Trash`.
+    fn home_trash() -> Result<TrashDir, String> {
+        let data_dir = dirs::data_dir().ok_or("Could not determine the data directory")?;
+        Ok(TrashDir {
+            root: data_dir.join("Trash"),
+            topdir: None,
+        })
+    }
+
+    /// Walks up from `path` to the root of the filesystem it lives on.
+    fn mount_point(path: &Path, dev: u64) -> PathBuf {
+        let mut top = path.to_path_buf();
+        while let Some(parent) = top.parent() {
+            match fs::metadata(parent) {
+                Ok(metadata) if metadata.dev() == dev => top = parent.to_path_buf(),
+                _ => break,
+            }
+        }
+        top
+    }
+
+    /// Picks the per-volume trash for `topdir`: the admin-provided `.Trash/$uid`
+    /// when `.Trash` is a sticky, non-symlinked directory, `.Trash-$uid` otherwise.
+    fn topdir_trash(topdir: &Path) -> TrashDir {
+        let shared = topdir.join(".Trash");
+        if let Ok(metadata) = fs::symlink_metadata(&shared) {
+            if metadata.is_dir() && metadata.mode() & STICKY_BIT != 0 {
+                return TrashDir {
+                    root: shared.join(uid().to_string()),
+                    topdir: Some(topdir.to_path_buf()),
+                };
+            }
+        }
+        TrashDir {
+            root: topdir.join(format!(".Trash-{}", uid())),
+            topdir: Some(topdir.to_path_buf()),
+        }
+    }
+
+    /// Every trash directory that currently exists: the home trash plus the
+    /// per-volume ones on mounted filesystems.
+    fn all_trash_dirs() -> Vec<TrashDir> {
+        let mut dirs = Vec::new();
+        if let Ok(home) = home_trash() {
+            dirs.push(home);
+        }
+        for topdir in mount_points() {
+            for root in [
+                topdir.join(".Trash").join(uid().to_string()),
+                topdir.join(format!(".Trash-{}", uid())),
+            ] {
+                let trash = TrashDir {
+                    root,
+                    topdir: Some(topdir.clone()),
+                };
+                if trash.info().is_dir() && !dirs.iter().any(|d| d.root == trash.root) {
+                    dirs.push(trash);
+                }
+            }
+        }
+        dirs.retain(|trash| trash.info().is_dir());
+        dirs
+    }
+
+    #[cfg(target_os = "linux")]
+    fn mount_points() -> Vec<PathBuf> {
+        let Ok(mounts) = fs::read("/proc/self/mounts") else {
+            return Vec::new();
+        };
+        mounts
+            .split(|&b| b == b'\n')
+            .filter_map(|line| line.split(|&b| b == b' ').nth(1))
+            .map(|field| PathBuf::from(std::ffi::OsString::from_vec(unescape_mount(field))))
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn mount_points() -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// `/proc/self/mounts` escapes spaces and friends as `\040`-style octal.
+    #[cfg(target_os = "linux")]
+    fn unescape_mount(field: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(field.len());
+        let mut i = 0;
+        while i < field.len() {
+            if field[i] == b'\\' && i + 4 <= field.len() {
+                if let Some(byte) = std::str::from_utf8(&field[i + 1..i + 4])
+                    .ok()
+                    .and_then(|octal| u8::from_str_radix(octal, 8).ok())
+                {
+                    out.push(byte);
+                    i += 4;
+                    continue;
+                }
+            }
+            out.push(field[i]);
+            i += 1;
+        }
+        out
+    }
+
+    fn encode_path(path: &Path) -> String {
+        let mut out = String::new();
+        for &b in path.as_os_str().as_bytes() {
+            if b.is_ascii_alphanumeric() || b"-_.~/".contains(&b) {
+                out.push(b as char);
+            } else {
+                out.push_str(&format!("%{:02X}", b));
+            }
+        }
+        out
+    }
+
+    fn decode_path(encoded: &str) -> PathBuf {
+        let bytes = encoded.as_bytes();
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' && i + 2 < bytes.len() {
+                if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    out.push(byte);
+                    i += 3;
+                    continue;
+                }
+            }
+            out.push(bytes[i]);
+            i += 1;
+        }
+        PathBuf::from(std::ffi::OsString::from_vec(out))
+    }
+
+    /// `DeletionDate` is local time without a zone, `YYYY-MM-DDThh:mm:ss`.
+    fn format_date(time: SystemTime) -> String {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0) as libc::time_t;
+        // SAFETY: `tm` is plain data, for which all zeroes (and a null `tm_zone`)
+        // is a valid value
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        // SAFETY: both pointers are to live locals; on failure `tm` stays zeroed
+        unsafe { libc::localtime_r(&secs, &mut tm) };
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            tm.tm_year + 1900,
+            tm.tm_mon + 1,
+            tm.tm_mday,
+            tm.tm_hour,
+            tm.tm_min,
+            tm.tm_sec
+        )
+    }
+
+    fn parse_date(value: &str) -> Option<SystemTime> {
+        let (date, time) = value.split_once('T')?;
+        let mut date = date.splitn(3, '-').map(|part| part.parse::<i32>());
+        let mut time = time.splitn(3, ':').map(|part| part.parse::<i32>());
+        // SAFETY: as in `format_date`, all zeroes is a valid `tm`
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        tm.tm_year = date.next()?.ok()? - 1900;
+        tm.tm_mon = date.next()?.ok()? - 1;
+        tm.tm_mday = date.next()?.ok()?;
+        tm.tm_hour = time.next()?.ok()?;
+        tm.tm_min = time.next()?.ok()?;
+        tm.tm_sec = time.next()?.ok()?;
+        tm.tm_isdst = -1;
+        // SAFETY: `tm` is initialised and exclusively borrowed; mktime only
+        // normalises its fields in place
+        let secs = unsafe { libc::mktime(&mut tm) };
+        u64::try_from(secs)
+            .ok()
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// Returns `(Path, DeletionDate)` from a `.trashinfo` file.
+    fn parse_info(contents: &str) -> Option<(PathBuf, Option<SystemTime>)> {
+        let mut in_group = false;
+        let mut path = None;
+        let mut date = None;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_group = line == "[Trash Info]";
+            } else if in_group {
+                if let Some(value) = line.strip_prefix("Path=") {
+                    path = Some(decode_path(value));
+                } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+                    date = parse_date(value);
+                }
+            }
+        }
+        path.map(|path| (path, date))
+    }
+
+    /// Reserves a free name in `trash` by creating its info file exclusively,
+    /// as the spec requires, and returns the name.
+    fn reserve(trash: &TrashDir, original: &Path, info: &str) -> Result<std::ffi::OsString, String> {
+        let name = original.file_name().ok_or("Path has no file name")?;
+        let (stem, extension) = match (original.file_stem(), original.extension()) {
+            (Some(stem), Some(ext)) if !stem.is_empty() => (stem.to_os_string(), Some(ext)),
+            _ => (name.to_os_string(), None),
+        };
+
+        for attempt in 1..10_000 {
+            let candidate = if attempt == 1 {
+                name.to_os_string()
+            } else {
+                let mut candidate = stem.clone();
+                candidate.push(format!(".{}", attempt));
+                if let Some(ext) = extension {
+                    candidate.push(".");
+                    candidate.push(ext);
+                }
+                candidate
+            };
+            if trash.files().join(&candidate).symlink_metadata().is_ok() {
+                continue;
+            }
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(trash.info_file(&candidate))
+            {
+                Ok(mut file) => {
+                    if let Err(e) = file.write_all(info.as_bytes()) {
+                        let _ = fs::remove_file(trash.info_file(&candidate));
+                        return Err(format!("Failed to write trash info: {}", e));
+                    }
+                    return Ok(candidate);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(format!("Failed to write trash info: {}", e)),
+            }
+        }
+        Err("Failed to find a free name in the trash".to_string())
+    }
+
+    pub fn trash(path: &Path) -> Result<TrashItem, String> {
+        let metadata = fs::symlink_metadata(path).map_err(|e| format!("Failed to delete: {}", e))?;
+        let parent = path.parent().ok_or("Cannot move the root directory to the trash")?;
+
+        let home = home_trash()?;
+        if home.root.starts_with(path) {
+            return Err(format!("Refusing to move '{}' to the trash, it contains the trash itself", path.display()));
+        }
+        home.create()
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+
+        // Items may only be renamed into a trash on their own filesystem
+        let dev = fs::metadata(parent)
+            .map_err(|e| format!("Failed to delete: {}", e))?
+            .dev();
+        let home_dev = fs::metadata(&home.root)
+            .map_err(|e| format!("Failed to delete: {}", e))?
+            .dev();
+        let trash = if dev == home_dev {
+            home
+        } else {
+            let trash = topdir_trash(&mount_point(parent, dev));
+            trash.create().map_err(|e| {
+                format!(
+                    "This volume has no usable trash ({}): {}",
+                    trash.root.display(),
+                    e
+                )
+            })?;
+            trash
+        };
+
+        let deleted_at = SystemTime::now();
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(path),
+            format_date(deleted_at)
+        );
+        let name = reserve(&trash, path, &info)?;
+        let destination = trash.files().join(&name);
+        if let Err(e) = fs::rename(path, &destination) {
+            let _ = fs::remove_file(trash.info_file(&name));
+            return Err(format!("Failed to move to the trash: {}", e));
+        }
+
+        Ok(TrashItem {
+            id: destination,
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            original_path: path.to_path_buf(),
+            deleted_at: to_millis(deleted_at),
+            is_directory: metadata.is_dir(),
+            size: metadata.is_file().then_some(metadata.len()),
+        })
+    }
+
+    pub fn list() -> Result<Vec<TrashItem>, String> {
+        let mut items = Vec::new();
+        for trash in all_trash_dirs() {
+            let Ok(entries) = fs::read_dir(trash.info()) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let file_name = entry.file_name();
+                let Some(name) = file_name
+                    .as_bytes()
+                    .strip_suffix(INFO_EXTENSION.as_bytes())
+                else {
+                    continue;
+                };
+                let name = std::ffi::OsStr::from_bytes(name);
+                let id = trash.files().join(name);
+                // Info files whose item is gone are left over from other tools; skip them
+                let Ok(metadata) = fs::symlink_metadata(&id) else {
+                    continue;
+                };
+                let Some((original_path, deleted_at)) = fs::read_to_string(entry.path())
+                    .ok()
+                    .and_then(|contents| parse_info(&contents))
+                else {
+                    continue;
+                };
+                let original_path = match &trash.topdir {
+                    Some(topdir) if original_path.is_relative() => topdir.join(original_path),
+                    _ => original_path,
+                };
+                items.push(TrashItem {
+                    id,
+                    name: original_path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| name.to_string_lossy().into_owned()),
+                    original_path,
+                    deleted_at: deleted_at.and_then(to_millis),
+                    is_directory: metadata.is_dir(),
+                    size: metadata.is_file().then_some(metadata.len()),
+                });
+            }
+        }
+        items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+        Ok(items)
+    }
+
+    /// Only ids that name an entry directly inside a known trash are honoured, so
+    /// the commands cannot be pointed at arbitrary paths.
+    fn locate(id: &Path) -> Result<(TrashDir, std::ffi::OsString), String> {
+        let name = id.file_name().ok_or("Not an item in the trash")?;
+        let parent = id.parent().ok_or("Not an item in the trash")?;
+        all_trash_dirs()
+            .into_iter()
+            .find(|trash| trash.files() == parent)
+            .map(|trash| (trash, name.to_os_string()))
+            .ok_or_else(|| "Not an item in the trash".to_string())
+    }
+
+    pub fn original_path(id: &Path) -> Result<PathBuf, String> {
+        list()?
+            .into_iter()
+            .find(|item| item.id == id)
+            .map(|item| item.original_path)
+            .ok_or_else(|| "Not an item in the trash".to_string())
+    }
+
+    pub fn restore(id: &Path, original: &Path) -> Result<(), String> {
+        let (trash, name) = locate(id)?;
+        restore_from(&trash, &name, original)
+    }
+
+    fn restore_from(trash: &TrashDir, name: &std::ffi::OsStr, original: &Path) -> Result<(), String> {
+        let id = trash.files().join(name);
+        if fs::symlink_metadata(original).is_ok() {
+            return Err(format!(
+                "Cannot restore, '{}' already exists",
+                original.display()
+            ));
+        }
+        if let Some(parent) = original.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to restore: {}", e))?;
+        }
+        fs::rename(&id, original).map_err(|e| format!("Failed to restore: {}", e))?;
+        let _ = fs::remove_file(trash.info_file(name));
+        Ok(())
+    }
+
+    fn purge(trash: &TrashDir, name: &std::ffi::OsStr) -> io::Result<()> {
+        let path = trash.files().join(name);
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(&path)?,
+            Ok(_) => fs::remove_file(&path)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        // The info file goes last, so an interrupted purge stays listed
+        match fs::remove_file(trash.info_file(name)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn empty(ids: Option<&[PathBuf]>) -> Result<usize, String> {
+        let mut removed = 0;
+        match ids {
+            Some(ids) => {
+                for id in ids {
+                    let (trash, name) = locate(id)?;
+                    purge(&trash, &name).map_err(|e| format!("Failed to empty trash: {}", e))?;
+                    removed += 1;
+                }
+            }
+            None => {
+                for trash in all_trash_dirs() {
+                    let Ok(entries) = fs::read_dir(trash.files()) else {
+                        continue;
+                    };
+                    for entry in entries.flatten() {
+                        purge(&trash, &entry.file_name())
+                            .map_err(|e| format!("Failed to empty trash: {}", e))?;
+                        removed += 1;
+                    }
+                    // Orphaned info files would otherwise linger forever
+                    if let Ok(entries) = fs::read_dir(trash.info()) {
+                        for entry in entries.flatten() {
+                            let _ = fs::remove_file(entry.path());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::test_support::Sandbox;
+        use std::ffi::OsStr;
+
+        #[test]
+        fn encodes_paths_as_the_spec_requires() {
+            let path = Path::new("/home/me/My Files/100%/ü.txt");
+            let encoded = encode_path(path);
+            assert_eq!(encoded, "/home/me/My%20Files/100%25/%C3%BC.txt");
+            assert_eq!(decode_path(&encoded), path);
+        }
+
+        #[test]
+        fn round_trips_paths_that_are_not_utf8() {
+            let path = PathBuf::from(std::ffi::OsString::from_vec(b"/tmp/caf\xe9\n".to_vec()));
+            assert_eq!(decode_path(&encode_path(&path)), path);
+        }
+
+        #[test]
+        fn decodes_malformed_escapes_literally() {
+            assert_eq!(decode_path("/tmp/a%zzb%4"), Path::new("/tmp/a%zzb%4"));
+            assert_eq!(decode_path("/tmp/%41"), Path::new("/tmp/A"));
+        }
+
+        #[test]
+        fn parses_the_dates_it_writes() {
+            let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+            assert_eq!(parse_date(&format_date(time)), Some(time));
+        }
+
+        #[test]
+        fn rejects_malformed_dates() {
+            for value in ["", "yesterday", "2024-01-01", "2024-01-01T10:00", "2024-xx-01T10:00:00"] {
+                assert_eq!(parse_date(value), None, "{:?} was accepted", value);
+            }
+        }
+
+        #[test]
+        fn reads_path_and_date_from_the_trash_info_group_only() {
+            let info = "[Other]\nPath=/wrong\n[Trash Info]\nPath=/tmp/a%20b\nDeletionDate=garbage\n";
+            assert_eq!(parse_info(info), Some((PathBuf::from("/tmp/a b"), None)));
+            assert_eq!(parse_info("[Trash Info]\nDeletionDate=2024-01-01T10:00:00\n"), None);
+        }
+
+        fn trashed(sandbox: &Sandbox, name: &str) -> TrashDir {
+            let trash = TrashDir {
+                root: sandbox.path("Trash"),
+                topdir: None,
+            };
+            trash.create().unwrap();
+            fs::write(trash.files().join(name), "trashed").unwrap();
+            fs::write(trash.info_file(OsStr::new(name)), "[Trash Info]\n").unwrap();
+            trash
+        }
+
+        #[test]
+        fn restores_to_the_original_path() {
+            let sandbox = Sandbox::new("trash");
+            let trash = trashed(&sandbox, "notes.txt");
+            let original = sandbox.path("gone/notes.txt");
+            restore_from(&trash, OsStr::new("notes.txt"), &original).unwrap();
+            assert_eq!(fs::read_to_string(&original).unwrap(), "trashed");
+            assert!(!trash.info_file(OsStr::new("notes.txt")).exists());
+        }
+
+        #[test]
+        fn restore_refuses_to_replace_an_existing_name() {
+            let sandbox = Sandbox::new("trash");
+            let trash = trashed(&sandbox, "notes.txt");
+            let original = sandbox.file("notes.txt", "newer");
+            let result = restore_from(&trash, OsStr::new("notes.txt"), &original);
+            assert!(matches!(result, Err(e) if e.contains("already exists")));
+            assert_eq!(fs::read_to_string(&original).unwrap(), "newer");
+            assert!(trash.files().join("notes.txt").exists());
+            assert!(trash.info_file(OsStr::new("notes.txt")).exists());
+        }
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+mod freedesktop {
+    use super::TrashItem;
+    use std::path::{Path, PathBuf};
+
+    const UNSUPPORTED: &str = "The trash is not supported on this platform yet";
+
+    pub fn trash(_path: &Path) -> Result<TrashItem, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn list() -> Result<Vec<TrashItem>, String> {
+        Ok(Vec::new())
+    }
+
+    pub fn original_path(_id: &Path) -> Result<PathBuf, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn restore(_id: &Path, _original: &Path) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn empty(_ids: Option<&[PathBuf]>) -> Result<usize, String> {
+        Ok(0)
+    }
+}
+
+/// Whether deletions on this platform go to a trash. Where they don't, callers
+/// fall back to [`delete`].
+pub const SUPPORTED: bool = cfg!(all(unix, not(target_os = "macos")));
+
+pub use freedesktop::trash;
+
+/// Moves `path` to the trash. Returns `None` on platforms without trash support,
+/// where nothing was touched. Refuses if the policy denies anything inside the
+/// tree, which would otherwise be carried along into the trash.
+#[tauri::command]
+pub async fn trash_item(path: String) -> Result<Option<TrashItem>, String> {
+    if !SUPPORTED {
+        return Ok(None);
+    }
+    let path = check_access(&path, Access::Write)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        PathPolicy::current().check_tree(&path, Access::Write, None)?;
+        freedesktop::trash(&path).map(Some)
+    })
+    .await
+    .map_err(|e| format!("Failed to move to the trash: {}", e))?
+}
+
+/// Deletes `path` for good, for platforms without a trash. Refuses up front if
+/// `access` denies anything inside the tree.
+pub fn delete(path: &Path, access: &PathPolicy) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|_| format!("File or directory does not exist: {}", path.display()))?;
+    access.check_tree(path, Access::Write, None)?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .map_err(|e| format!("Failed to delete: {}", e))
+}
+
+#[tauri::command]
+pub async fn delete_permanently(path: String) -> Result<(), String> {
+    let path = check_access(&path, Access::Write)?;
+    tauri::async_runtime::spawn_blocking(move || delete(&path, &PathPolicy::current()))
+        .await
+        .map_err(|e| format!("Failed to delete: {}", e))?
+}
+
+#[tauri::command]
+pub async fn list_trash() -> Result<Vec<TrashItem>, String> {
+    tauri::async_runtime::spawn_blocking(freedesktop::list)
+        .await
+        .map_err(|e| format!("Failed to list trash: {}", e))?
+}
+
+/// Puts a trashed item back where it came from and returns that path.
+#[tauri::command]
+pub async fn restore_from_trash(id: PathBuf) -> Result<PathBuf, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let original = freedesktop::original_path(&id)?;
+        let original = check_access(&original, Access::Write)?;
+        freedesktop::restore(&id, &original)?;
+        Ok(original)
+    })
+    .await
+    .map_err(|e| format!("Failed to restore: {}", e))?
+}
+
+/// Permanently deletes the given trash items, or everything in the trash when
+/// `ids` is omitted. Returns how many items were removed.
+#[tauri::command]
+pub async fn empty_trash(ids: Option<Vec<PathBuf>>) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || freedesktop::empty(ids.as_deref()))
+        .await
+        .map_err(|e| format!("Failed to empty trash: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    #[test]
+    fn delete_removes_trees_the_policy_allows() {
+        let sandbox = Sandbox::new("trash");
+        sandbox.file("build/out/app.js", "code");
+        delete(&sandbox.path("build"), &sandbox.policy(&[])).unwrap();
+        assert!(!sandbox.path("build").exists());
+    }
+
+    #[test]
+    fn delete_refuses_trees_holding_denied_entries() {
+        let sandbox = Sandbox::new("trash");
+        sandbox.file("config/chrome/Cookies", "session");
+        let result = delete(&sandbox.path("config"), &sandbox.policy(&["config/chrome"]));
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert!(sandbox.path("config/chrome/Cookies").exists());
+    }
+}
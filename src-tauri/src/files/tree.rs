@@ -0,0 +1,585 @@
+use super::check_access;
+use super::list::EntryKind;
+use crate::policy::{Access, PathPolicy};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const DEFAULT_MAX_DEPTH: usize = 5;
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".fsaiignore"];
+// Never worth showing, and big enough to eat the entry budget on their own
+const ALWAYS_SKIPPED: [&str; 1] = [".git"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TreeFormat {
+    #[default]
+    Ascii,
+    Json,
+    Both,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TreeOptions {
+    /// Levels below the root to expand; the root's children are depth 1
+    pub max_depth: usize,
+    /// Entries to include before the walk stops, counted breadth-first
+    pub max_entries: usize,
+    /// Honour `.gitignore` and `.fsaiignore` files found in the tree
+    pub respect_ignore: bool,
+    pub show_hidden: bool,
+    /// Descend into symlinked directories; cycles are detected and cut
+    pub follow_symlinks: bool,
+    pub format: TreeFormat,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            respect_ignore: true,
+            show_hidden: true,
+            follow_symlinks: false,
+            format: TreeFormat::Ascii,
+        }
+    }
+}
+
+/// Why a directory's children are missing from the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Unexpanded {
+    DepthLimit,
+    EntryLimit,
+    /// A symlink back to one of its own ancestors
+    Cycle,
+    /// A symlinked directory, with `follow_symlinks` off
+    Symlink,
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeNode {
+    pub name: String,
+    pub kind: EntryKind,
+    /// Only set for files
+    pub size: Option<u64>,
+    pub symlink_target: Option<PathBuf>,
+    pub children: Vec<TreeNode>,
+    pub unexpanded: Option<Unexpanded>,
+    /// Children left out once the entry limit was reached
+    pub omitted: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tree {
+    pub path: PathBuf,
+    pub root: Option<TreeNode>,
+    pub ascii: Option<String>,
+    pub entries: usize,
+    /// Whether the entry limit cut the walk short
+    pub truncated: bool,
+}
+
+/// Ignore rules in effect for a directory: its own files plus its parent's chain.
+struct IgnoreChain {
+    matcher: Gitignore,
+    parent: Option<Arc<IgnoreChain>>,
+}
+
+impl IgnoreChain {
+    /// The closest directory with an opinion wins, as in git.
+    fn is_ignored(chain: &Option<Arc<IgnoreChain>>, path: &Path, is_dir: bool) -> bool {
+        let mut current = chain.as_deref();
+        while let Some(level) = current {
+            match level.matcher.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => current = level.parent.as_deref(),
+            }
+        }
+        false
+    }
+
+    fn extend(parent: &Option<Arc<IgnoreChain>>, dir: &Path) -> Option<Arc<IgnoreChain>> {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+        for name in IGNORE_FILES {
+            let file = dir.join(name);
+            // A broken line should not hide the rest of the file's rules
+            if file.is_file() {
+                found = true;
+                let _ = builder.add(file);
+            }
+        }
+        match builder.build() {
+            Ok(matcher) if found => Some(Arc::new(IgnoreChain {
+                matcher,
+                parent: parent.clone(),
+            })),
+            _ => parent.clone(),
+        }
+    }
+}
+
+/// A node under construction; children are indices into the arena.
+struct Slot {
+    node: TreeNode,
+    path: PathBuf,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Identity of the directory, for cycle detection
+    identity: Option<(u64, u64)>,
+}
+
+#[cfg(unix)]
+fn identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+fn is_cycle(slots: &[Slot], parent: usize, path: &Path, id: Option<(u64, u64)>) -> bool {
+    let canonical = fs::canonicalize(path).ok();
+    let mut current = Some(parent);
+    while let Some(index) = current {
+        let slot = &slots[index];
+        let same = match (id, slot.identity) {
+            (Some(a), Some(b)) => a == b,
+            _ => canonical.is_some() && fs::canonicalize(&slot.path).ok() == canonical,
+        };
+        if same {
+            return true;
+        }
+        current = slot.parent;
+    }
+    false
+}
+
+/// Walks `root` breadth-first, so a limit leaves a shallow but complete picture
+/// instead of one deep branch. Entries `access` denies are left out, names
+/// included.
+pub fn build(root: &Path, options: &TreeOptions, access: &PathPolicy) -> Result<Tree, String> {
+    let metadata =
+        fs::metadata(root).map_err(|_| format!("Directory does not exist: {}", root.display()))?;
+    if !metadata.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let mut slots = vec![Slot {
+        node: TreeNode {
+            name: root
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| root.to_string_lossy().into_owned()),
+            kind: EntryKind::Directory,
+            size: None,
+            symlink_target: None,
+            children: Vec::new(),
+            unexpanded: None,
+            omitted: 0,
+        },
+        path: root.to_path_buf(),
+        parent: None,
+        children: Vec::new(),
+        identity: identity(&metadata),
+    }];
+    let mut queue = VecDeque::from([(0usize, 0usize, None::<Arc<IgnoreChain>>)]);
+    let mut entries = 0;
+    let mut truncated = false;
+
+    while let Some((index, depth, ignores)) = queue.pop_front() {
+        if truncated {
+            slots[index].node.unexpanded = Some(Unexpanded::EntryLimit);
+            continue;
+        }
+        if depth >= options.max_depth {
+            slots[index].node.unexpanded = Some(Unexpanded::DepthLimit);
+            continue;
+        }
+        let dir = slots[index].path.clone();
+        let ignores = if options.respect_ignore {
+            IgnoreChain::extend(&ignores, &dir)
+        } else {
+            None
+        };
+        let Ok(read) = fs::read_dir(&dir) else {
+            slots[index].node.unexpanded = Some(Unexpanded::Unreadable);
+            continue;
+        };
+
+        let mut children = Vec::new();
+        for entry in read.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if ALWAYS_SKIPPED.contains(&name.as_str())
+                || (!options.show_hidden && name.starts_with('.'))
+            {
+                continue;
+            }
+            let path = entry.path();
+            if access.check(&path, Access::Read).is_err() {
+                continue;
+            }
+            let Ok(link_metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            let is_symlink = link_metadata.file_type().is_symlink();
+            let target_metadata = if is_symlink {
+                fs::metadata(&path).ok()
+            } else {
+                Some(link_metadata)
+            };
+            let is_dir = target_metadata.as_ref().is_some_and(|m| m.is_dir());
+            if IgnoreChain::is_ignored(&ignores, &path, is_dir) {
+                continue;
+            }
+            children.push((name, path, is_symlink, target_metadata));
+        }
+        // Directories first, then case-insensitive by name, like the file browser
+        children.sort_by(|a, b| {
+            let a_dir = a.3.as_ref().is_some_and(|m| m.is_dir());
+            let b_dir = b.3.as_ref().is_some_and(|m| m.is_dir());
+            b_dir
+                .cmp(&a_dir)
+                .then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase()))
+        });
+
+        let total = children.len();
+        for (taken, (name, path, is_symlink, target_metadata)) in children.into_iter().enumerate() {
+            if entries >= options.max_entries {
+                truncated = true;
+                slots[index].node.omitted = total - taken;
+                break;
+            }
+            entries += 1;
+
+            let kind = match &target_metadata {
+                Some(m) if m.is_dir() => EntryKind::Directory,
+                Some(m) if m.is_file() => EntryKind::File,
+                _ => EntryKind::Other,
+            };
+            let id = target_metadata.as_ref().and_then(identity);
+            let unexpanded = match kind {
+                EntryKind::Directory if is_symlink && !options.follow_symlinks => {
+                    Some(Unexpanded::Symlink)
+                }
+                EntryKind::Directory if is_symlink && is_cycle(&slots, index, &path, id) => {
+                    Some(Unexpanded::Cycle)
+                }
+                _ => None,
+            };
+            let child = slots.len();
+            slots.push(Slot {
+                node: TreeNode {
+                    name,
+                    kind,
+                    size: target_metadata
+                        .as_ref()
+                        .filter(|m| m.is_file())
+                        .map(|m| m.len()),
+                    symlink_target: if is_symlink {
+                        fs::read_link(&path).ok()
+                    } else {
+                        None
+                    },
+                    children: Vec::new(),
+                    unexpanded,
+                    omitted: 0,
+                },
+                path,
+                parent: Some(index),
+                children: Vec::new(),
+                identity: id,
+            });
+            slots[index].children.push(child);
+            if kind == EntryKind::Directory && unexpanded.is_none() {
+                queue.push_back((child, depth + 1, ignores.clone()));
+            }
+        }
+    }
+
+    let root_node = assemble(&mut slots, 0);
+    let ascii = matches!(options.format, TreeFormat::Ascii | TreeFormat::Both)
+        .then(|| render(&root_node, truncated));
+    Ok(Tree {
+        path: root.to_path_buf(),
+        root: matches!(options.format, TreeFormat::Json | TreeFormat::Both).then_some(root_node),
+        ascii,
+        entries,
+        truncated,
+    })
+}
+
+fn assemble(slots: &mut [Slot], index: usize) -> TreeNode {
+    let children = std::mem::take(&mut slots[index].children);
+    let mut node = slots[index].node.clone();
+    node.children = children
+        .into_iter()
+        .map(|child| assemble(slots, child))
+        .collect();
+    node
+}
+
+/// Renders the same shape `generateTree` in the sidecar did, with markers for
+/// anything the limits left out.
+pub fn render(root: &TreeNode, truncated: bool) -> String {
+    let mut out = String::new();
+    render_children(root, "", &mut out);
+    if truncated {
+        out.push_str("[entry limit reached; some entries are not shown]\n");
+    }
+    out
+}
+
+fn render_children(node: &TreeNode, prefix: &str, out: &mut String) {
+    let count = node.children.len() + usize::from(node.omitted > 0);
+    for (i, child) in node.children.iter().enumerate() {
+        let is_last = i + 1 == count;
+        let connector = if is_last { "└── " } else { "├── " };
+        let _ = write!(out, "{}{}{}", prefix, connector, child.name);
+        if child.kind == EntryKind::Directory {
+            out.push('/');
+        }
+        if let Some(target) = &child.symlink_target {
+            let _ = write!(out, " -> {}", target.display());
+        }
+        match child.unexpanded {
+            Some(Unexpanded::DepthLimit) | Some(Unexpanded::EntryLimit) => out.push_str(" [...]"),
+            Some(Unexpanded::Cycle) => out.push_str(" [cycle]"),
+            Some(Unexpanded::Unreadable) => out.push_str(" [error reading directory]"),
+            Some(Unexpanded::Symlink) | None => {}
+        }
+        out.push('\n');
+        let child_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
+        render_children(child, &child_prefix, out);
+    }
+    if node.omitted > 0 {
+        let _ = writeln!(out, "{}└── ... {} more", prefix, node.omitted);
+    }
+}
+
+#[tauri::command]
+pub async fn get_tree(path: String, options: Option<TreeOptions>) -> Result<Tree, String> {
+    let path = check_access(&path, Access::Read)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        build(&path, &options.unwrap_or_default(), &PathPolicy::current())
+    })
+    .await
+    .map_err(|e| format!("Failed to build tree: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    #[test]
+    fn leaves_out_denied_entries() {
+        let sandbox = Sandbox::new("tree");
+        sandbox.file(".ssh/id_ed25519", "key");
+        sandbox.file(".gnupg/pubring.kbx", "keyring");
+        sandbox.file("projects/server.pem", "certificate");
+        sandbox.file("projects/README.md", "hello");
+        let access = sandbox.policy(&[".ssh", ".gnupg", "**/*.pem"]);
+        let tree = build(&sandbox.base, &TreeOptions::default(), &access).unwrap();
+        let ascii = tree.ascii.unwrap();
+        assert!(ascii.contains("README.md"));
+        for hidden in [".ssh", "id_ed25519", ".gnupg", "pubring.kbx", "server.pem"] {
+            assert!(!ascii.contains(hidden), "{} is listed:\n{}", hidden, ascii);
+        }
+        assert_eq!(tree.entries, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn does_not_follow_symlinks_into_denied_entries() {
+        let sandbox = Sandbox::new("tree");
+        sandbox.file(".ssh/id_ed25519", "key");
+        sandbox.link(sandbox.path(".ssh"), sandbox.path("keys"));
+        let options = TreeOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let tree = build(&sandbox.base, &options, &sandbox.policy(&[".ssh"])).unwrap();
+        assert!(!tree.ascii.unwrap().contains("id_ed25519"));
+    }
+
+    /// Every entry's path relative to the root, depth-first.
+    fn paths(tree: &Tree) -> Vec<String> {
+        fn walk(node: &TreeNode, prefix: &str, out: &mut Vec<String>) {
+            for child in &node.children {
+                let path = format!("{}{}", prefix, child.name);
+                out.push(path.clone());
+                walk(child, &format!("{}/", path), out);
+            }
+        }
+        let mut out = Vec::new();
+        walk(tree.root.as_ref().unwrap(), "", &mut out);
+        out
+    }
+
+    fn child<'a>(node: &'a TreeNode, name: &str) -> &'a TreeNode {
+        node.children
+            .iter()
+            .find(|child| child.name == name)
+            .unwrap()
+    }
+
+    fn with_format(format: TreeFormat) -> TreeOptions {
+        TreeOptions {
+            format,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stops_expanding_at_max_depth() {
+        let sandbox = Sandbox::new("tree");
+        sandbox.file("a/b/c/deep.txt", "");
+        sandbox.file("a/top.txt", "");
+        let options = TreeOptions {
+            max_depth: 2,
+            ..with_format(TreeFormat::Both)
+        };
+        let tree = build(&sandbox.base, &options, &sandbox.policy(&[])).unwrap();
+        assert_eq!(paths(&tree), ["a", "a/b", "a/top.txt"]);
+        let b = child(child(tree.root.as_ref().unwrap(), "a"), "b");
+        assert_eq!(b.unexpanded, Some(Unexpanded::DepthLimit));
+        assert!(!tree.truncated);
+        assert_eq!(
+            tree.ascii.unwrap(),
+            "└── a/\n    ├── b/ [...]\n    └── top.txt\n"
+        );
+    }
+
+    #[test]
+    fn stops_the_walk_at_max_entries() {
+        let sandbox = Sandbox::new("tree");
+        for name in ["docs/one.md", "docs/two.md", "x.txt", "y.txt", "z.txt"] {
+            sandbox.file(name, "");
+        }
+        let options = TreeOptions {
+            max_entries: 3,
+            ..with_format(TreeFormat::Both)
+        };
+        let tree = build(&sandbox.base, &options, &sandbox.policy(&[])).unwrap();
+        // Breadth-first, so the root's entries come before anything in `docs`
+        assert_eq!(paths(&tree), ["docs", "x.txt", "y.txt"]);
+        assert_eq!(tree.entries, 3);
+        assert!(tree.truncated);
+        let root = tree.root.as_ref().unwrap();
+        assert_eq!(root.omitted, 1);
+        assert_eq!(child(root, "docs").unexpanded, Some(Unexpanded::EntryLimit));
+        assert_eq!(
+            tree.ascii.unwrap(),
+            "├── docs/ [...]\n\
+             ├── x.txt\n\
+             ├── y.txt\n\
+             └── ... 1 more\n\
+             [entry limit reached; some entries are not shown]\n"
+        );
+    }
+
+    #[test]
+    fn the_closest_ignore_file_wins() {
+        let sandbox = Sandbox::new("tree");
+        sandbox.file(".gitignore", "*.log\nbuild/\n");
+        // Read after `.gitignore`, so its rules come last
+        sandbox.file(".fsaiignore", "!keep.log\n");
+        sandbox.file("app.log", "");
+        sandbox.file("keep.log", "");
+        sandbox.file("build/out.bin", "");
+        sandbox.file("sub/.gitignore", "!debug.log\n");
+        sandbox.file("sub/.fsaiignore", "notes.txt\n");
+        sandbox.file("sub/debug.log", "");
+        sandbox.file("sub/trace.log", "");
+        sandbox.file("sub/notes.txt", "");
+        sandbox.file("sub/plan.txt", "");
+        let options = TreeOptions {
+            show_hidden: false,
+            ..with_format(TreeFormat::Json)
+        };
+        let tree = build(&sandbox.base, &options, &sandbox.policy(&[])).unwrap();
+        assert_eq!(
+            paths(&tree),
+            ["sub", "sub/debug.log", "sub/plan.txt", "keep.log"]
+        );
+
+        let options = TreeOptions {
+            respect_ignore: false,
+            ..options
+        };
+        let tree = build(&sandbox.base, &options, &sandbox.policy(&[])).unwrap();
+        assert_eq!(tree.entries, 9);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cuts_a_symlink_loop() {
+        let sandbox = Sandbox::new("tree");
+        sandbox.file("a/file.txt", "");
+        sandbox.link(&sandbox.base, sandbox.path("a/loop"));
+        let options = TreeOptions {
+            follow_symlinks: true,
+            ..with_format(TreeFormat::Both)
+        };
+        let tree = build(&sandbox.base, &options, &sandbox.policy(&[])).unwrap();
+        let looped = child(child(tree.root.as_ref().unwrap(), "a"), "loop");
+        assert_eq!(looped.unexpanded, Some(Unexpanded::Cycle));
+        assert!(looped.children.is_empty());
+        assert!(tree
+            .ascii
+            .unwrap()
+            .contains(&format!("loop/ -> {} [cycle]", sandbox.base.display())));
+
+        // Not followed at all unless asked
+        let tree = build(
+            &sandbox.base,
+            &with_format(TreeFormat::Json),
+            &sandbox.policy(&[]),
+        )
+        .unwrap();
+        let looped = child(child(tree.root.as_ref().unwrap(), "a"), "loop");
+        assert_eq!(looped.unexpanded, Some(Unexpanded::Symlink));
+    }
+
+    #[test]
+    fn renders_directories_first_with_tree_connectors() {
+        let sandbox = Sandbox::new("tree");
+        sandbox.file("README.md", "");
+        sandbox.file("src/main.rs", "");
+        sandbox.file("src/lib/mod.rs", "");
+        sandbox.file("docs/guide.md", "");
+        let tree = build(
+            &sandbox.base,
+            &with_format(TreeFormat::Ascii),
+            &sandbox.policy(&[]),
+        )
+        .unwrap();
+        assert!(tree.root.is_none());
+        assert_eq!(
+            tree.ascii.unwrap(),
+            "├── docs/\n\
+             │   └── guide.md\n\
+             ├── src/\n\
+             │   ├── lib/\n\
+             │   │   └── mod.rs\n\
+             │   └── main.rs\n\
+             └── README.md\n"
+        );
+    }
+}
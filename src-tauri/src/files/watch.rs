@@ -0,0 +1,348 @@
+use crate::policy::{Access, PathPolicy};
+use crate::AppState;
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+// Long enough to fold an editor's save dance or a burst of writes into one update
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
+
+pub(crate) type DirectoryWatcher = Debouncer<RecommendedWatcher, RecommendedCache>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FsChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsChange {
+    pub kind: FsChangeKind,
+    pub path: PathBuf,
+    /// Previous path, for renames within the watched directory
+    pub old_path: Option<PathBuf>,
+}
+
+/// Payload of the `fs-changed` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsChanged {
+    /// The watched directory, as returned by `watch_path`
+    pub path: PathBuf,
+    pub changes: Vec<FsChange>,
+    /// The OS dropped events; the listing should be reloaded in full
+    pub rescan: bool,
+}
+
+fn change(kind: FsChangeKind, path: &Path) -> FsChange {
+    FsChange {
+        kind,
+        path: path.to_path_buf(),
+        old_path: None,
+    }
+}
+
+/// Turns a debounced batch into the changes the file browser cares about.
+fn collect(batch: Vec<notify_debouncer_full::DebouncedEvent>) -> (Vec<FsChange>, bool) {
+    let mut changes: Vec<FsChange> = Vec::new();
+    let mut rescan = false;
+    for event in batch {
+        rescan |= event.need_rescan();
+        let Some(path) = event.paths.first() else {
+            continue;
+        };
+        let next = match event.kind {
+            EventKind::Access(_) => continue,
+            EventKind::Create(_) => change(FsChangeKind::Created, path),
+            EventKind::Remove(_) => change(FsChangeKind::Removed, path),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match event.paths.get(1) {
+                Some(to) => FsChange {
+                    kind: FsChangeKind::Renamed,
+                    path: to.clone(),
+                    old_path: Some(path.clone()),
+                },
+                None => change(FsChangeKind::Modified, path),
+            },
+            // One half of a rename across the edge of the watched directory
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => change(FsChangeKind::Removed, path),
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => change(FsChangeKind::Created, path),
+            EventKind::Modify(ModifyKind::Name(_)) if path.symlink_metadata().is_ok() => {
+                change(FsChangeKind::Created, path)
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => change(FsChangeKind::Removed, path),
+            EventKind::Modify(_) | EventKind::Any | EventKind::Other => {
+                change(FsChangeKind::Modified, path)
+            }
+        };
+        // A new file is written to right after it is created; one entry is enough
+        let redundant = changes.iter().any(|seen| {
+            seen == &next
+                || (next.kind == FsChangeKind::Modified
+                    && seen.path == next.path
+                    && matches!(seen.kind, FsChangeKind::Created | FsChangeKind::Renamed))
+        });
+        if !redundant {
+            changes.push(next);
+        }
+    }
+    (changes, rescan)
+}
+
+fn start(app: AppHandle, path: PathBuf) -> Result<DirectoryWatcher, String> {
+    let root = path.clone();
+    let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, None, move |result: DebounceEventResult| {
+        match result {
+            Ok(batch) => {
+                let (changes, rescan) = collect(batch);
+                if changes.is_empty() && !rescan {
+                    return;
+                }
+                let _ = app.emit(
+                    "fs-changed",
+                    FsChanged {
+                        path: root.clone(),
+                        changes,
+                        rescan,
+                    },
+                );
+            }
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("Watcher error for {}: {}", root.display(), error);
+                }
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to start watcher: {}", e))?;
+    // Only the listing the user is looking at, not everything below it
+    debouncer
+        .watch(&path, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch '{}': {}", path.display(), e))?;
+    Ok(debouncer)
+}
+
+/// Resolves `path` to the key of its watch, the way `access` resolves it for
+/// the access check.
+fn watch_key(path: &str, access: &PathPolicy) -> Result<PathBuf, String> {
+    access.check(path, Access::Read)
+}
+
+/// Starts emitting `fs-changed` for the entries of a directory. Returns the
+/// resolved path, which identifies the watch in events and in `unwatch_path`.
+#[tauri::command]
+pub async fn watch_path(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<PathBuf, String> {
+    let path = watch_key(&path, &PathPolicy::current())?;
+    if !path.is_dir() {
+        return Err(format!("Not a directory: {}", path.display()));
+    }
+    let mut watchers = state.watchers.lock().unwrap();
+    if !watchers.contains_key(&path) {
+        let watcher = start(app.clone(), path.clone())?;
+        watchers.insert(path.clone(), watcher);
+    }
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn unwatch_path(state: State<'_, AppState>, path: String) -> Result<bool, String> {
+    // Unrestricted, so a watch can still be dropped after the policy has changed
+    let path = watch_key(&path, &PathPolicy::unrestricted())?;
+    // Dropping the debouncer stops its thread and removes the inotify watch
+    Ok(state.watchers.lock().unwrap().remove(&path).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+    use notify_debouncer_full::notify::event::{
+        AccessKind, CreateKind, DataChange, Event, Flag, RemoveKind,
+    };
+    use notify_debouncer_full::DebouncedEvent;
+    use std::time::Instant;
+
+    fn event(kind: EventKind, paths: &[&Path]) -> DebouncedEvent {
+        let event = paths.iter().fold(Event::new(kind), |event, path| {
+            event.add_path(path.to_path_buf())
+        });
+        DebouncedEvent::new(event, Instant::now())
+    }
+
+    fn renamed(from: &Path, to: &Path) -> FsChange {
+        FsChange {
+            kind: FsChangeKind::Renamed,
+            path: to.to_path_buf(),
+            old_path: Some(from.to_path_buf()),
+        }
+    }
+
+    const WRITE: EventKind = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+
+    #[test]
+    fn pairs_both_halves_of_a_rename() {
+        let (from, to) = (Path::new("/w/draft.txt"), Path::new("/w/final.txt"));
+        let (changes, rescan) = collect(vec![event(
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &[from, to],
+        )]);
+        assert_eq!(changes, [renamed(from, to)]);
+        assert!(!rescan);
+    }
+
+    #[test]
+    fn a_rename_across_the_edge_is_a_removal_or_a_creation() {
+        let (left, arrived) = (Path::new("/w/left.txt"), Path::new("/w/arrived.txt"));
+        let (changes, _) = collect(vec![
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+                &[left],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+                &[arrived],
+            ),
+            // Missing its destination, so only the source is known to have changed
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &[left],
+            ),
+        ]);
+        assert_eq!(
+            changes,
+            [
+                change(FsChangeKind::Removed, left),
+                change(FsChangeKind::Created, arrived),
+                change(FsChangeKind::Modified, left),
+            ]
+        );
+    }
+
+    #[test]
+    fn an_unpaired_rename_is_judged_by_what_is_on_disk() {
+        let sandbox = Sandbox::new("watch");
+        let present = sandbox.file("present.txt", "");
+        let gone = sandbox.path("gone.txt");
+        let (changes, _) = collect(vec![
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Any)),
+                &[&present],
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Other)),
+                &[&gone],
+            ),
+        ]);
+        assert_eq!(
+            changes,
+            [
+                change(FsChangeKind::Created, &present),
+                change(FsChangeKind::Removed, &gone),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_to_a_new_file_fold_into_its_creation() {
+        let (new, old) = (Path::new("/w/new.txt"), Path::new("/w/old.txt"));
+        let (moved, target) = (Path::new("/w/a.txt"), Path::new("/w/b.txt"));
+        let (changes, _) = collect(vec![
+            event(EventKind::Create(CreateKind::File), &[new]),
+            event(WRITE, &[new]),
+            event(EventKind::Access(AccessKind::Any), &[old]),
+            event(WRITE, &[old]),
+            event(WRITE, &[old]),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &[moved, target],
+            ),
+            event(WRITE, &[target]),
+        ]);
+        assert_eq!(
+            changes,
+            [
+                change(FsChangeKind::Created, new),
+                change(FsChangeKind::Modified, old),
+                renamed(moved, target),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_write_before_a_creation_is_kept() {
+        let path = Path::new("/w/notes.txt");
+        let (changes, _) = collect(vec![
+            event(WRITE, &[path]),
+            event(EventKind::Create(CreateKind::File), &[path]),
+        ]);
+        assert_eq!(
+            changes,
+            [
+                change(FsChangeKind::Modified, path),
+                change(FsChangeKind::Created, path),
+            ]
+        );
+    }
+
+    #[test]
+    fn removing_the_watched_directory_reports_it_removed() {
+        let (root, entry) = (Path::new("/w"), Path::new("/w/notes.txt"));
+        let (changes, rescan) = collect(vec![
+            event(EventKind::Remove(RemoveKind::File), &[entry]),
+            event(EventKind::Remove(RemoveKind::Folder), &[root]),
+        ]);
+        assert_eq!(
+            changes,
+            [
+                change(FsChangeKind::Removed, entry),
+                change(FsChangeKind::Removed, root),
+            ]
+        );
+        assert!(!rescan);
+    }
+
+    #[test]
+    fn dropped_events_ask_for_a_rescan() {
+        let rescan = DebouncedEvent::new(
+            Event::new(EventKind::Other).set_flag(Flag::Rescan),
+            Instant::now(),
+        );
+        let (changes, rescan) = collect(vec![rescan]);
+        assert!(changes.is_empty());
+        assert!(rescan);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unwatching_resolves_paths_the_way_watching_does() {
+        let sandbox = Sandbox::new("watch");
+        sandbox.dir("real/photos");
+        sandbox.link(sandbox.path("real"), sandbox.path("shortcut"));
+        let watched = watch_key(
+            sandbox.path("shortcut/photos").to_str().unwrap(),
+            &sandbox.policy(&[]),
+        )
+        .unwrap();
+        assert_eq!(watched, sandbox.path("real/photos"));
+        let spelled_differently = sandbox.path("real/../shortcut/./photos");
+        assert_eq!(
+            watch_key(
+                spelled_differently.to_str().unwrap(),
+                &PathPolicy::unrestricted()
+            )
+            .unwrap(),
+            watched
+        );
+    }
+}
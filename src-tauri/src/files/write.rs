@@ -0,0 +1,373 @@
+use super::check_access;
+use crate::policy::{Access, PathPolicy};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WriteOptions {
+    /// Fail instead of replacing a file that already exists
+    pub create_only: bool,
+    /// SHA-256 (hex) the file must still have, so edits based on a stale read are refused
+    pub expected_hash: Option<String>,
+    /// Create missing parent directories, as the sidecar's write endpoint always did
+    pub create_dirs: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            create_only: false,
+            expected_hash: None,
+            create_dirs: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteResult {
+    pub path: PathBuf,
+    pub bytes_written: u64,
+    /// SHA-256 of the new contents, to pass as `expectedHash` on the next write
+    pub hash: String,
+    pub created: bool,
+}
+
+/// Removes the temp file unless it was successfully moved into place.
+struct TempFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// SHA-256 of the file at `path`, hex encoded.
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Creates an empty, uniquely named file next to `target`, so the final rename
+/// never crosses a filesystem.
+fn create_temp(target: &Path) -> Result<(File, TempFile), String> {
+    let dir = target.parent().ok_or("Path has no parent directory")?;
+    let name = target
+        .file_name()
+        .ok_or("Path has no file name")?
+        .to_string_lossy();
+    for _ in 0..8 {
+        let mut suffix = [0u8; 6];
+        getrandom::fill(&mut suffix).map_err(|e| format!("Failed to create temp file: {}", e))?;
+        let path = dir.join(format!(".{}.fsai-{}.tmp", name, to_hex(&suffix)));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, TempFile { path, persisted: false })),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create temp file: {}", e)),
+        }
+    }
+    Err("Failed to create temp file: too many name collisions".to_string())
+}
+
+/// Gives the replacement the original's owner, where we are allowed to, and its
+/// mode. The mode goes last: a chown by anyone but root clears setuid and setgid.
+fn copy_attributes(file: &File, original: &fs::Metadata) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let current = file.metadata()?;
+        if current.uid() != original.uid() || current.gid() != original.gid() {
+            // Only root may give files away; a group we belong to still works
+            if let Err(e) = std::os::unix::fs::fchown(file, Some(original.uid()), Some(original.gid())) {
+                if e.kind() != io::ErrorKind::PermissionDenied {
+                    return Err(e);
+                }
+                let _ = std::os::unix::fs::fchown(file, None, Some(original.gid()));
+            }
+        }
+    }
+    file.set_permissions(original.permissions())
+}
+
+/// Makes the rename itself durable. Windows has no directory handles to sync.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Moves the finished temp file to `target`. With `create_only` a hard link
+/// refuses to replace anything, which closes the race with a file appearing
+/// after the existence check.
+fn persist(temp: &mut TempFile, target: &Path, create_only: bool) -> Result<(), String> {
+    if !create_only {
+        fs::rename(&temp.path, target).map_err(|e| format!("Failed to write file: {}", e))?;
+        temp.persisted = true;
+        return Ok(());
+    }
+    match fs::hard_link(&temp.path, target) {
+        // The temp name is unlinked when `temp` drops
+        Ok(()) => Ok(()),
+        // Filesystems without hard links (FAT, some network shares) fall back to a rename
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists && !target.exists() => {
+            fs::rename(&temp.path, target).map_err(|e| format!("Failed to write file: {}", e))?;
+            temp.persisted = true;
+            Ok(())
+        }
+        Err(_) => Err(format!("File '{}' already exists", target.display())),
+    }
+}
+
+/// Writes `content` to `path` without ever leaving a half-written file behind:
+/// the data goes to a temp file in the same directory, is fsynced, and is then
+/// renamed over the target. `path` itself is expected to be checked already;
+/// `access` vets where a symlink at `path` leads.
+pub fn write(
+    path: &Path,
+    content: &[u8],
+    options: &WriteOptions,
+    access: &PathPolicy,
+) -> Result<WriteResult, String> {
+    // Replacing a symlink would detach it from its target; write through it instead
+    let target = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => match fs::canonicalize(path) {
+            Ok(resolved) => access.check(&resolved, Access::Write)?,
+            Err(_) => return Err("Path is a dangling symlink".to_string()),
+        },
+        _ => path.to_path_buf(),
+    };
+
+    let existing = match fs::metadata(&target) {
+        Ok(metadata) if metadata.is_dir() => return Err("Path is a directory".to_string()),
+        Ok(metadata) => Some(metadata),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to write file: {}", e)),
+    };
+
+    if options.create_only && existing.is_some() {
+        return Err(format!("File '{}' already exists", target.display()));
+    }
+    if let Some(expected) = &options.expected_hash {
+        if existing.is_none() {
+            return Err(format!(
+                "File '{}' no longer exists; it was removed after it was read",
+                target.display()
+            ));
+        }
+        let actual = hash_file(&target).map_err(|e| format!("Failed to write file: {}", e))?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "File '{}' has changed since it was read; read it again before writing",
+                target.display()
+            ));
+        }
+    }
+
+    let dir = target.parent().ok_or("Path has no parent directory")?;
+    if options.create_dirs {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let (mut file, mut temp) = create_temp(&target)?;
+    file.write_all(content)
+        .and_then(|_| match &existing {
+            Some(metadata) => copy_attributes(&file, metadata),
+            None => Ok(()),
+        })
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    drop(file);
+
+    persist(&mut temp, &target, options.create_only)?;
+    drop(temp);
+
+    sync_dir(dir).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(WriteResult {
+        path: target,
+        bytes_written: content.len() as u64,
+        hash: to_hex(&Sha256::digest(content)),
+        created: existing.is_none(),
+    })
+}
+
+/// Creates the directory `path`, with any missing parents.
+pub fn create_dir(path: &Path) -> Result<PathBuf, String> {
+    if fs::symlink_metadata(path).is_ok() {
+        return Err(format!("'{}' already exists", path.display()));
+    }
+    fs::create_dir_all(path).map_err(|e| format!("Failed to create directory: {}", e))?;
+    Ok(path.to_path_buf())
+}
+
+#[tauri::command]
+pub async fn create_directory(path: String) -> Result<PathBuf, String> {
+    let path = check_access(&path, Access::Write)?;
+    tauri::async_runtime::spawn_blocking(move || create_dir(&path))
+        .await
+        .map_err(|e| format!("Failed to create directory: {}", e))?
+}
+
+#[tauri::command]
+pub async fn write_file(
+    path: String,
+    content: String,
+    options: Option<WriteOptions>,
+) -> Result<WriteResult, String> {
+    let path = check_access(&path, Access::Write)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        write(
+            &path,
+            content.as_bytes(),
+            &options.unwrap_or_default(),
+            &PathPolicy::current(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to write file: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    fn write_to(
+        sandbox: &Sandbox,
+        path: &Path,
+        content: &str,
+        options: &WriteOptions,
+    ) -> Result<WriteResult, String> {
+        write(path, content.as_bytes(), options, &sandbox.policy(&[]))
+    }
+
+    fn create_only() -> WriteOptions {
+        WriteOptions {
+            create_only: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn creates_and_replaces_files() {
+        let sandbox = Sandbox::new("write");
+        let path = sandbox.path("notes/todo.md");
+        let created = write_to(&sandbox, &path, "first", &WriteOptions::default()).unwrap();
+        assert!(created.created);
+        let replaced = write_to(&sandbox, &path, "second", &WriteOptions::default()).unwrap();
+        assert!(!replaced.created);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(replaced.hash, hash_file(&path).unwrap());
+    }
+
+    #[test]
+    fn create_only_refuses_existing_files() {
+        let sandbox = Sandbox::new("write");
+        let path = sandbox.file("todo.md", "mine");
+        let result = write_to(&sandbox, &path, "theirs", &create_only());
+        assert!(matches!(result, Err(e) if e.contains("already exists")));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "mine");
+    }
+
+    #[test]
+    fn create_only_refuses_a_file_that_appears_after_the_check() {
+        let sandbox = Sandbox::new("write");
+        let target = sandbox.path("todo.md");
+        let (_, mut temp) = create_temp(&target).unwrap();
+        let temp_path = temp.path.clone();
+        fs::write(&target, "raced").unwrap();
+
+        let result = persist(&mut temp, &target, true);
+        assert!(matches!(result, Err(e) if e.contains("already exists")));
+        drop(temp);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "raced");
+        assert!(!temp_path.exists());
+    }
+
+    #[test]
+    fn refuses_writes_based_on_a_stale_hash() {
+        let sandbox = Sandbox::new("write");
+        let path = sandbox.file("todo.md", "read by the agent");
+        let hash = hash_file(&path).unwrap();
+        fs::write(&path, "edited by the user").unwrap();
+
+        let stale = WriteOptions {
+            expected_hash: Some(hash),
+            ..Default::default()
+        };
+        let result = write_to(&sandbox, &path, "agent edit", &stale);
+        assert!(matches!(result, Err(e) if e.contains("has changed")));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "edited by the user");
+
+        let fresh = WriteOptions {
+            expected_hash: Some(hash_file(&path).unwrap().to_uppercase()),
+            ..Default::default()
+        };
+        write_to(&sandbox, &path, "agent edit", &fresh).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_through_symlinks() {
+        let sandbox = Sandbox::new("write");
+        let target = sandbox.file("real/config.toml", "old");
+        let link = sandbox.path("config.toml");
+        sandbox.link(&target, &link);
+
+        let written = write_to(&sandbox, &link, "new", &WriteOptions::default()).unwrap();
+        assert_eq!(written.path, target);
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symlinks_into_denied_paths() {
+        let sandbox = Sandbox::new("write");
+        let target = sandbox.file("secrets/token", "old");
+        let link = sandbox.path("token");
+        sandbox.link(&target, &link);
+
+        let access = sandbox.policy(&["secrets/**"]);
+        let result = write(&link, b"new", &WriteOptions::default(), &access);
+        assert!(matches!(result, Err(e) if e.contains("disallowed")));
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_the_mode_of_the_file_it_replaces() {
+        use std::os::unix::fs::PermissionsExt;
+        let sandbox = Sandbox::new("write");
+        let path = sandbox.file("run.sh", "#!/bin/sh\n");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o4750)).unwrap();
+
+        write_to(&sandbox, &path, "#!/bin/sh\nexit 0\n", &WriteOptions::default()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o4750);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::backend::{self, BackendStatus};
+use crate::logs::{self, LogLevel, LogSource};
+use crate::AppState;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackendHealth {
+    healthy: bool,
+    latency_ms: Option<u64>,
+    consecutive_failures: u32,
+    error: Option<String>,
+}
+
+/// Consecutive failed probes, and the point at which they make a backend that
+/// is still running count as unresponsive.
+struct Failures {
+    count: u32,
+    max: u32,
+}
+
+impl Failures {
+    fn new(max: u32) -> Self {
+        Self {
+            count: 0,
+            max: max.max(1),
+        }
+    }
+
+    /// Counts a probe result and turns it into the `backend-health` payload.
+    /// A single answer clears the count.
+    fn record(&mut self, result: Result<Duration, String>) -> BackendHealth {
+        match result {
+            Ok(latency) => {
+                self.count = 0;
+                BackendHealth {
+                    healthy: true,
+                    latency_ms: Some(latency.as_millis() as u64),
+                    consecutive_failures: 0,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                self.count += 1;
+                BackendHealth {
+                    healthy: false,
+                    latency_ms: None,
+                    consecutive_failures: self.count,
+                    error: Some(e),
+                }
+            }
+        }
+    }
+
+    /// Whether enough probes in a row have failed to restart the backend.
+    fn unresponsive(&self) -> bool {
+        self.count >= self.max
+    }
+}
+
+/// Polls the sidecar's `/health` route for as long as the child with `pid` is the
+/// running backend. If it stops answering while the process is still alive, the
+/// child is killed so the supervisor relaunches it.
+pub async fn monitor(app_handle: AppHandle, generation: u64, pid: u32, port: u16) {
+    let (interval, mut failures) = {
+        let config = &app_handle.state::<AppState>().backend_config;
+        (config.health_interval, Failures::new(config.max_health_failures))
+    };
+
+    loop {
+        tokio::time::sleep(interval).await;
+        if !is_running(&app_handle, generation, pid) {
+            break;
+        }
+
+        let token = &app_handle.state::<AppState>().backend_token;
+        let result = backend::probe_health(port, token).await;
+        // The child may have been replaced while the probe was in flight
+        if !is_running(&app_handle, generation, pid) {
+            break;
+        }
+
+        let health = failures.record(result);
+        if let Some(e) = &health.error {
+            backend::set_status(&app_handle, BackendStatus::Unhealthy);
+            logs::record(
+                &app_handle,
+                LogLevel::Warn,
+                LogSource::Supervisor,
+                format!(
+                    "Backend health check failed ({}/{}): {}",
+                    failures.count, failures.max, e
+                ),
+            );
+        } else {
+            backend::set_status(&app_handle, BackendStatus::Ready);
+        }
+        let _ = app_handle.emit("backend-health", health);
+
+        if failures.unresponsive() {
+            restart_unresponsive(&app_handle, pid);
+            break;
+        }
+    }
+}
+
+fn is_running(app_handle: &AppHandle, generation: u64, pid: u32) -> bool {
+    let app_state = app_handle.state::<AppState>();
+    let child_process = app_state.child_process.lock().unwrap();
+    backend::is_current(app_handle, generation)
+        && child_process.as_ref().map(|child| child.pid()) == Some(pid)
+}
+
+fn restart_unresponsive(app_handle: &AppHandle, pid: u32) {
+    let app_state = app_handle.state::<AppState>();
+    let mut child_process = app_state.child_process.lock().unwrap();
+    if child_process.as_ref().map(|child| child.pid()) != Some(pid) {
+        return;
+    }
+    if let Some(child) = child_process.take() {
+        // The supervisor sees the termination and relaunches with its usual backoff
+        let message = match child.kill() {
+            Ok(()) => "Backend stopped answering health checks, restarting it".to_string(),
+            Err(e) => format!("Failed to kill unresponsive backend process: {}", e),
+        };
+        logs::record(app_handle, LogLevel::Error, LogSource::Supervisor, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed() -> Result<Duration, String> {
+        Err("No response within 2s".to_string())
+    }
+
+    #[test]
+    fn restarts_after_the_configured_failures_in_a_row() {
+        let mut failures = Failures::new(3);
+        for count in 1..3 {
+            let health = failures.record(failed());
+            assert!(!health.healthy);
+            assert_eq!(health.consecutive_failures, count);
+            assert!(!failures.unresponsive());
+        }
+        let health = failures.record(failed());
+        assert_eq!(health.consecutive_failures, 3);
+        assert_eq!(health.error.as_deref(), Some("No response within 2s"));
+        assert!(failures.unresponsive());
+    }
+
+    #[test]
+    fn an_answer_clears_the_count() {
+        let mut failures = Failures::new(3);
+        failures.record(failed());
+        failures.record(failed());
+        let health = failures.record(Ok(Duration::from_millis(12)));
+        assert!(health.healthy);
+        assert_eq!(health.latency_ms, Some(12));
+        assert_eq!(health.consecutive_failures, 0);
+        failures.record(failed());
+        failures.record(failed());
+        assert!(!failures.unresponsive());
+    }
+
+    #[test]
+    fn a_threshold_of_zero_still_waits_for_one_failure() {
+        let mut failures = Failures::new(0);
+        assert!(!failures.unresponsive());
+        failures.record(failed());
+        assert!(failures.unresponsive());
+    }
+}
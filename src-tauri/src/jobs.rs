@@ -0,0 +1,871 @@
+use crate::files::copy::{
+    self, CancelToken, ConflictAnswer, ConflictPolicy, CopyConflict, CopyObserver, CopyOptions,
+    CopyProgress, CopyResult,
+};
+use crate::files::transfer::{self, MoveResult};
+use crate::files::trash::{self, TrashItem};
+use crate::files::{check_access, to_millis};
+use crate::policy::{Access, PathPolicy};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Enough to overlap a slow copy with quick deletes without thrashing the disk
+const MAX_RUNNING_JOBS: usize = 2;
+// Finished jobs are kept around for the UI, oldest dropped first
+const MAX_FINISHED_JOBS: usize = 100;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// What a job does. Destinations are final paths, already resolved against
+/// existing directories.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum JobSpec {
+    Copy {
+        source: PathBuf,
+        destination: PathBuf,
+        #[serde(default)]
+        conflict: ConflictPolicy,
+    },
+    Move {
+        source: PathBuf,
+        destination: PathBuf,
+    },
+    /// Moves to the trash
+    Delete { path: PathBuf },
+    /// Walks a tree to count its files and total size
+    Index { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobState {
+    Queued,
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobState::Completed | JobState::Failed | JobState::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub items_done: u64,
+    pub items_total: u64,
+    pub current: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSummary {
+    pub path: PathBuf,
+    pub files: u64,
+    pub directories: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum JobOutput {
+    Copy(CopyResult),
+    Move(MoveResult),
+    Delete(TrashItem),
+    Index(IndexSummary),
+}
+
+/// A job as reported to the frontend in `list_jobs` and `job-updated`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: u64,
+    #[serde(flatten)]
+    pub spec: JobSpec,
+    pub state: JobState,
+    pub progress: JobProgress,
+    pub result: Option<JobOutput>,
+    pub error: Option<String>,
+    pub created_at: Option<u64>,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+}
+
+struct Entry {
+    job: Job,
+    control: CancelToken,
+}
+
+#[derive(Default)]
+struct Queue {
+    entries: Vec<Entry>,
+    next_id: u64,
+    running: usize,
+}
+
+impl Queue {
+    fn entry(&mut self, id: u64) -> Result<&mut Entry, String> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.job.id == id)
+            .ok_or_else(|| format!("No job with id {}", id))
+    }
+
+    fn push(&mut self, spec: JobSpec) -> Job {
+        self.next_id += 1;
+        let job = Job {
+            id: self.next_id,
+            spec,
+            state: JobState::Queued,
+            progress: JobProgress::default(),
+            result: None,
+            error: None,
+            created_at: now(),
+            started_at: None,
+            finished_at: None,
+        };
+        self.entries.push(Entry {
+            job: job.clone(),
+            control: CancelToken::default(),
+        });
+        job
+    }
+
+    /// Marks queued jobs as running while there are free slots and returns them.
+    fn start(&mut self) -> Vec<(Job, CancelToken)> {
+        let mut started = Vec::new();
+        while self.running < MAX_RUNNING_JOBS {
+            let Some(entry) = self
+                .entries
+                .iter_mut()
+                .find(|entry| entry.job.state == JobState::Queued)
+            else {
+                break;
+            };
+            entry.job.state = JobState::Running;
+            entry.job.started_at = now();
+            started.push((entry.job.clone(), entry.control.clone()));
+            self.running += 1;
+        }
+        started
+    }
+
+    /// Records the outcome of a running job and frees its slot.
+    fn finish(
+        &mut self,
+        id: u64,
+        outcome: Result<JobOutput, String>,
+        control: &CancelToken,
+    ) -> Option<Job> {
+        self.running -= 1;
+        let snapshot = self.entry(id).ok().map(|entry| {
+            let job = &mut entry.job;
+            match outcome {
+                Ok(output) => {
+                    job.state = JobState::Completed;
+                    job.result = Some(output);
+                }
+                Err(_) if control.is_cancelled() => job.state = JobState::Cancelled,
+                Err(e) => {
+                    job.state = JobState::Failed;
+                    job.error = Some(e);
+                }
+            }
+            job.finished_at = now();
+            job.clone()
+        });
+        self.prune();
+        snapshot
+    }
+
+    fn prune(&mut self) {
+        let finished = self
+            .entries
+            .iter()
+            .filter(|entry| entry.job.state.is_finished())
+            .count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        self.entries.retain(|entry| {
+            if excess > 0 && entry.job.state.is_finished() {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+/// Queue of long-running file operations, at most [`MAX_RUNNING_JOBS`] at a time.
+#[derive(Default)]
+pub struct JobManager {
+    queue: Mutex<Queue>,
+    // Signalled whenever a job finishes, for callers waiting on one
+    finished: Condvar,
+}
+
+impl JobManager {
+    fn with_entry<T>(
+        &self,
+        id: u64,
+        f: impl FnOnce(&mut Entry) -> Result<T, String>,
+    ) -> Result<T, String> {
+        f(self.queue.lock().unwrap().entry(id)?)
+    }
+
+    fn list(&self) -> Vec<Job> {
+        let queue = self.queue.lock().unwrap();
+        queue
+            .entries
+            .iter()
+            .map(|entry| entry.job.clone())
+            .collect()
+    }
+
+    fn update(&self, id: u64, change: impl FnOnce(&mut Job)) -> Option<Job> {
+        self.with_entry(id, |entry| {
+            change(&mut entry.job);
+            Ok(entry.job.clone())
+        })
+        .ok()
+    }
+
+    fn finish(
+        &self,
+        id: u64,
+        outcome: Result<JobOutput, String>,
+        control: &CancelToken,
+    ) -> Option<Job> {
+        let snapshot = self.queue.lock().unwrap().finish(id, outcome, control);
+        self.finished.notify_all();
+        snapshot
+    }
+
+    /// Blocks until job `id` has finished and returns its final state.
+    fn wait(&self, id: u64) -> Result<Job, String> {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            match queue.entries.iter().find(|entry| entry.job.id == id) {
+                Some(entry) if entry.job.state.is_finished() => return Ok(entry.job.clone()),
+                Some(_) => queue = self.finished.wait(queue).unwrap(),
+                None => return Err(format!("No job with id {}", id)),
+            }
+        }
+    }
+
+    /// Returns the job if it was cancelled outright, or `None` if its worker
+    /// has yet to notice and report back.
+    fn cancel(&self, id: u64) -> Result<Option<Job>, String> {
+        let snapshot = self.with_entry(id, |entry| match entry.job.state {
+            state if state.is_finished() => Err(format!("Job {} has already finished", id)),
+            // Never started, so there is no worker to report back
+            JobState::Queued | JobState::Paused if entry.job.started_at.is_none() => {
+                entry.control.cancel();
+                entry.job.state = JobState::Cancelled;
+                entry.job.finished_at = now();
+                Ok(Some(entry.job.clone()))
+            }
+            _ => {
+                entry.control.cancel();
+                Ok(None)
+            }
+        })?;
+        if snapshot.is_some() {
+            self.finished.notify_all();
+        }
+        Ok(snapshot)
+    }
+
+    fn pause(&self, id: u64) -> Result<Job, String> {
+        self.with_entry(id, |entry| match entry.job.state {
+            JobState::Queued | JobState::Running => {
+                entry.control.pause();
+                entry.job.state = JobState::Paused;
+                Ok(entry.job.clone())
+            }
+            _ => Err(format!("Job {} is not running", id)),
+        })
+    }
+
+    fn resume(&self, id: u64) -> Result<Job, String> {
+        self.with_entry(id, |entry| match entry.job.state {
+            JobState::Paused => {
+                entry.control.resume();
+                entry.job.state = if entry.job.started_at.is_some() {
+                    JobState::Running
+                } else {
+                    JobState::Queued
+                };
+                Ok(entry.job.clone())
+            }
+            _ => Err(format!("Job {} is not paused", id)),
+        })
+    }
+}
+
+fn now() -> Option<u64> {
+    to_millis(SystemTime::now())
+}
+
+fn emit(app: &AppHandle, job: &Job) {
+    let _ = app.emit("job-updated", job);
+}
+
+/// Applies `change` to job `id` and announces the result.
+fn update(app: &AppHandle, id: u64, change: impl FnOnce(&mut Job)) {
+    if let Some(job) = app.state::<AppState>().jobs.update(id, change) {
+        emit(app, &job);
+    }
+}
+
+/// Queues a job and returns it as first reported.
+pub fn submit(app: &AppHandle, spec: JobSpec) -> Job {
+    let manager = &app.state::<AppState>().jobs;
+    let job = manager.queue.lock().unwrap().push(spec);
+    emit(app, &job);
+    pump(app);
+    job
+}
+
+/// Starts queued jobs while there are free slots.
+fn pump(app: &AppHandle) {
+    let manager = &app.state::<AppState>().jobs;
+    let started = manager.queue.lock().unwrap().start();
+    for (job, control) in started {
+        emit(app, &job);
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let outcome = execute(&app, job.id, &job.spec, &control);
+            finish(&app, job.id, outcome, &control);
+        });
+    }
+}
+
+fn finish(app: &AppHandle, id: u64, outcome: Result<JobOutput, String>, control: &CancelToken) {
+    if let Some(job) = app.state::<AppState>().jobs.finish(id, outcome, control) {
+        emit(app, &job);
+    }
+    pump(app);
+}
+
+/// Blocks until job `id` has finished and returns its final state.
+pub fn wait(app: &AppHandle, id: u64) -> Result<Job, String> {
+    app.state::<AppState>().jobs.wait(id)
+}
+
+/// Queues a job and waits for it, for callers that need the outcome.
+pub fn run(app: &AppHandle, spec: JobSpec) -> Result<JobOutput, String> {
+    let job = wait(app, submit(app, spec).id)?;
+    match (job.state, job.result) {
+        (JobState::Completed, Some(output)) => Ok(output),
+        (JobState::Cancelled, _) => Err("Cancelled by the user".to_string()),
+        _ => Err(job.error.unwrap_or_else(|| "Job failed".to_string())),
+    }
+}
+
+/// Throttles progress updates for one job.
+struct Reporter<'a> {
+    app: &'a AppHandle,
+    id: u64,
+    last_emit: Option<Instant>,
+}
+
+impl Reporter<'_> {
+    fn report(&mut self, progress: JobProgress, force: bool) {
+        if force || self.last_emit.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+            self.last_emit = Some(Instant::now());
+            update(self.app, self.id, |job| job.progress = progress);
+        }
+    }
+}
+
+impl CopyObserver for Reporter<'_> {
+    fn progress(&mut self, progress: &CopyProgress, force: bool) {
+        self.report(
+            JobProgress {
+                bytes_done: progress.bytes_done,
+                bytes_total: progress.bytes_total,
+                items_done: progress.files_done,
+                items_total: progress.files_total,
+                current: Some(progress.current.clone()),
+            },
+            force,
+        );
+    }
+
+    // Jobs run unattended; `submit_job` refuses the ask policy
+    fn conflict(&mut self, _conflict: &CopyConflict) -> Option<ConflictAnswer> {
+        None
+    }
+}
+
+fn execute(app: &AppHandle, id: u64, spec: &JobSpec, control: &CancelToken) -> Result<JobOutput, String> {
+    let mut reporter = Reporter {
+        app,
+        id,
+        last_emit: None,
+    };
+    match spec {
+        JobSpec::Copy {
+            source,
+            destination,
+            conflict,
+        } => copy::copy_path(
+            source,
+            destination,
+            &CopyOptions {
+                id: Some(id.to_string()),
+                conflict: *conflict,
+                verify: false,
+            },
+            &PathPolicy::current(),
+            control,
+            &mut reporter,
+        )
+        .map(JobOutput::Copy),
+        JobSpec::Move {
+            source,
+            destination,
+        } => transfer::move_path(
+            source,
+            destination,
+            &PathPolicy::current(),
+            control,
+            &mut |progress, force| {
+                reporter.report(
+                    JobProgress {
+                        bytes_done: progress.bytes_done,
+                        bytes_total: progress.bytes_total,
+                        items_done: progress.files_done,
+                        items_total: progress.files_total,
+                        current: None,
+                    },
+                    force,
+                )
+            },
+        )
+        .map(JobOutput::Move),
+        JobSpec::Delete { path } => {
+            if !trash::SUPPORTED {
+                return Err("Moving to the trash is not supported on this platform".to_string());
+            }
+            control.check().map_err(|e| e.to_string())?;
+            reporter.report(
+                JobProgress {
+                    items_total: 1,
+                    current: Some(path.clone()),
+                    ..Default::default()
+                },
+                true,
+            );
+            PathPolicy::current().check_tree(path, Access::Write, None)?;
+            trash::trash(path).map(JobOutput::Delete)
+        }
+        JobSpec::Index { path } => index(
+            path,
+            &PathPolicy::current(),
+            control,
+            &mut |progress, force| reporter.report(progress, force),
+        )
+            .map(JobOutput::Index)
+            .map_err(|e| {
+                if copy::is_cancelled(&e) {
+                    "Index cancelled".to_string()
+                } else {
+                    format!("Failed to index: {}", e)
+                }
+            }),
+    }
+}
+
+/// Counts what is under `path`, leaving out whatever `access` denies.
+fn index(
+    path: &Path,
+    access: &PathPolicy,
+    control: &CancelToken,
+    report: &mut dyn FnMut(JobProgress, bool),
+) -> io::Result<IndexSummary> {
+    let mut summary = IndexSummary {
+        path: path.to_path_buf(),
+        files: 0,
+        directories: 0,
+        bytes: 0,
+    };
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        control.check()?;
+        let metadata = fs::symlink_metadata(&current)?;
+        if metadata.is_dir() {
+            summary.directories += 1;
+            // Unreadable subdirectories are counted but not entered
+            if let Ok(entries) = fs::read_dir(&current) {
+                pending.extend(
+                    entries
+                        .flatten()
+                        .map(|entry| entry.path())
+                        .filter(|path| access.permits_entry(path, Access::Read)),
+                );
+            }
+        } else if metadata.is_file() {
+            summary.files += 1;
+            summary.bytes += metadata.len();
+        }
+        report(
+            JobProgress {
+                bytes_done: summary.bytes,
+                items_done: summary.files + summary.directories,
+                current: Some(current),
+                ..Default::default()
+            },
+            false,
+        );
+    }
+    report(
+        JobProgress {
+            bytes_done: summary.bytes,
+            bytes_total: summary.bytes,
+            items_done: summary.files + summary.directories,
+            items_total: summary.files + summary.directories,
+            current: None,
+        },
+        true,
+    );
+    Ok(summary)
+}
+
+/// Checks access and resolves destinations before anything is queued.
+fn prepare(spec: JobSpec) -> Result<JobSpec, String> {
+    Ok(match spec {
+        JobSpec::Copy {
+            source,
+            destination,
+            conflict,
+        } => {
+            if conflict == ConflictPolicy::Ask {
+                return Err("Jobs run unattended; choose skip, overwrite or rename".to_string());
+            }
+            let source = check_access(&source, Access::Read)?;
+            let destination = transfer::check_destination(&source, &destination)?;
+            JobSpec::Copy {
+                source,
+                destination,
+                conflict,
+            }
+        }
+        JobSpec::Move {
+            source,
+            destination,
+        } => {
+            let source = check_access(&source, Access::Write)?;
+            let destination = transfer::check_destination(&source, &destination)?;
+            JobSpec::Move {
+                source,
+                destination,
+            }
+        }
+        JobSpec::Delete { path } => JobSpec::Delete {
+            path: check_access(&path, Access::Write)?,
+        },
+        JobSpec::Index { path } => JobSpec::Index {
+            path: check_access(&path, Access::Read)?,
+        },
+    })
+}
+
+#[tauri::command]
+pub async fn submit_job(app: AppHandle, spec: JobSpec) -> Result<Job, String> {
+    Ok(submit(&app, prepare(spec)?))
+}
+
+#[tauri::command]
+pub async fn list_jobs(state: State<'_, AppState>) -> Result<Vec<Job>, String> {
+    Ok(state.jobs.list())
+}
+
+#[tauri::command]
+pub async fn cancel_job(app: AppHandle, state: State<'_, AppState>, id: u64) -> Result<(), String> {
+    if let Some(job) = state.jobs.cancel(id)? {
+        emit(&app, &job);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn pause_job(app: AppHandle, state: State<'_, AppState>, id: u64) -> Result<(), String> {
+    let job = state.jobs.pause(id)?;
+    emit(&app, &job);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_job(app: AppHandle, state: State<'_, AppState>, id: u64) -> Result<(), String> {
+    let job = state.jobs.resume(id)?;
+    emit(&app, &job);
+    pump(&app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+    use std::thread;
+
+    #[test]
+    fn index_leaves_out_denied_entries() {
+        let sandbox = Sandbox::new("jobs");
+        sandbox.file("notes.txt", "12345");
+        sandbox.file(".ssh/id_ed25519", "key");
+        sandbox.file(".ssh/known_hosts", "hosts");
+        sandbox.file("projects/server.pem", "certificate");
+        let access = sandbox.policy(&[".ssh", "**/*.pem"]);
+        let summary = index(
+            &sandbox.base,
+            &access,
+            &CancelToken::default(),
+            &mut |_, _| {},
+        )
+        .unwrap();
+        assert_eq!(summary.files, 1);
+        assert_eq!(summary.bytes, 5);
+        // The sandbox and `projects`
+        assert_eq!(summary.directories, 2);
+    }
+
+    fn spec(name: &str) -> JobSpec {
+        JobSpec::Index {
+            path: PathBuf::from(name),
+        }
+    }
+
+    fn output() -> Result<JobOutput, String> {
+        Ok(JobOutput::Index(IndexSummary {
+            path: PathBuf::from("a"),
+            files: 1,
+            directories: 0,
+            bytes: 3,
+        }))
+    }
+
+    fn push(manager: &JobManager, name: &str) -> u64 {
+        manager.queue.lock().unwrap().push(spec(name)).id
+    }
+
+    fn start(manager: &JobManager) -> Vec<(u64, CancelToken)> {
+        let started = manager.queue.lock().unwrap().start();
+        started
+            .into_iter()
+            .map(|(job, control)| (job.id, control))
+            .collect()
+    }
+
+    fn job(manager: &JobManager, id: u64) -> Job {
+        manager.list().into_iter().find(|job| job.id == id).unwrap()
+    }
+
+    fn ids(started: &[(u64, CancelToken)]) -> Vec<u64> {
+        started.iter().map(|(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn jobs_move_from_queued_to_running_to_finished() {
+        let manager = JobManager::default();
+        let first = push(&manager, "a");
+        let second = push(&manager, "b");
+        assert_eq!(job(&manager, first).state, JobState::Queued);
+        assert!(job(&manager, first).started_at.is_none());
+
+        let started = start(&manager);
+        assert_eq!(ids(&started), [first, second]);
+        assert_eq!(job(&manager, first).state, JobState::Running);
+        assert!(job(&manager, first).started_at.is_some());
+
+        let done = manager.finish(first, output(), &started[0].1).unwrap();
+        assert_eq!(done.state, JobState::Completed);
+        assert!(done.result.is_some());
+        assert!(done.finished_at.is_some());
+
+        let failed = manager
+            .finish(second, Err("Disk full".to_string()), &started[1].1)
+            .unwrap();
+        assert_eq!(failed.state, JobState::Failed);
+        assert_eq!(failed.error.as_deref(), Some("Disk full"));
+    }
+
+    #[test]
+    fn a_job_that_fails_after_being_cancelled_is_cancelled() {
+        let manager = JobManager::default();
+        let id = push(&manager, "a");
+        let started = start(&manager);
+        started[0].1.cancel();
+        let job = manager
+            .finish(id, Err("Index cancelled".to_string()), &started[0].1)
+            .unwrap();
+        assert_eq!(job.state, JobState::Cancelled);
+        assert_eq!(job.error, None);
+    }
+
+    #[test]
+    fn at_most_two_jobs_run_at_once() {
+        let manager = JobManager::default();
+        let all: Vec<u64> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| push(&manager, name))
+            .collect();
+        let started = start(&manager);
+        assert_eq!(ids(&started), all[..MAX_RUNNING_JOBS]);
+        assert!(start(&manager).is_empty());
+        assert_eq!(job(&manager, all[2]).state, JobState::Queued);
+
+        manager.finish(all[0], output(), &started[0].1);
+        assert_eq!(ids(&start(&manager)), [all[2]]);
+        assert!(start(&manager).is_empty());
+    }
+
+    #[test]
+    fn a_paused_queued_job_is_skipped_until_resumed() {
+        let manager = JobManager::default();
+        let id = push(&manager, "a");
+        assert_eq!(manager.pause(id).unwrap().state, JobState::Paused);
+        assert!(start(&manager).is_empty());
+        assert_eq!(
+            manager.pause(id).unwrap_err(),
+            format!("Job {} is not running", id)
+        );
+
+        assert_eq!(manager.resume(id).unwrap().state, JobState::Queued);
+        assert_eq!(ids(&start(&manager)), [id]);
+    }
+
+    #[test]
+    fn a_paused_running_job_keeps_its_slot() {
+        let manager = JobManager::default();
+        let first = push(&manager, "a");
+        push(&manager, "b");
+        let third = push(&manager, "c");
+        start(&manager);
+        assert_eq!(manager.pause(first).unwrap().state, JobState::Paused);
+        assert!(start(&manager).is_empty());
+        assert_eq!(job(&manager, third).state, JobState::Queued);
+
+        assert_eq!(manager.resume(first).unwrap().state, JobState::Running);
+        assert_eq!(
+            manager.resume(first).unwrap_err(),
+            format!("Job {} is not paused", first)
+        );
+    }
+
+    #[test]
+    fn cancelling_a_paused_job_that_never_started_finishes_it() {
+        let manager = JobManager::default();
+        let id = push(&manager, "a");
+        manager.pause(id).unwrap();
+        let job = manager.cancel(id).unwrap().unwrap();
+        assert_eq!(job.state, JobState::Cancelled);
+        assert!(job.finished_at.is_some());
+        assert!(start(&manager).is_empty());
+        assert_eq!(
+            manager.cancel(id).unwrap_err(),
+            format!("Job {} has already finished", id)
+        );
+        assert_eq!(
+            manager.resume(id).unwrap_err(),
+            format!("Job {} is not paused", id)
+        );
+    }
+
+    #[test]
+    fn cancelling_a_paused_running_job_leaves_it_to_its_worker() {
+        let manager = JobManager::default();
+        let id = push(&manager, "a");
+        let started = start(&manager);
+        let control = &started[0].1;
+        manager.pause(id).unwrap();
+        assert!(manager.cancel(id).unwrap().is_none());
+        assert_eq!(job(&manager, id).state, JobState::Paused);
+        // The worker is let go of rather than left waiting on the pause
+        assert!(control.check().is_err());
+
+        let job = manager
+            .finish(id, Err("Index cancelled".to_string()), control)
+            .unwrap();
+        assert_eq!(job.state, JobState::Cancelled);
+    }
+
+    #[test]
+    fn only_the_newest_finished_jobs_are_kept() {
+        let manager = JobManager::default();
+        // Never started, so never pruned
+        let queued = push(&manager, "queued");
+        manager.pause(queued).unwrap();
+        for _ in 0..=MAX_FINISHED_JOBS {
+            let id = push(&manager, "a");
+            let started = start(&manager);
+            manager.finish(id, output(), &started[0].1);
+        }
+        let jobs = manager.list();
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+        assert_eq!(jobs[0].id, queued);
+        // The oldest finished job went first
+        assert_eq!(jobs[1].id, queued + 2);
+        assert_eq!(
+            manager.resume(queued + 1).unwrap_err(),
+            format!("No job with id {}", queued + 1)
+        );
+    }
+
+    #[test]
+    fn wait_returns_the_finished_job() {
+        let manager = JobManager::default();
+        let id = push(&manager, "a");
+        let started = start(&manager);
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| manager.wait(id));
+            thread::sleep(Duration::from_millis(50));
+            manager.finish(id, output(), &started[0].1);
+            assert_eq!(waiter.join().unwrap().unwrap().state, JobState::Completed);
+        });
+    }
+
+    #[test]
+    fn wait_fails_when_the_job_was_pruned_before_it_looked_again() {
+        let manager = JobManager::default();
+        let id = push(&manager, "a");
+        let started = start(&manager);
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| manager.wait(id));
+            thread::sleep(Duration::from_millis(50));
+            {
+                // Finish it and enough after it to be pruned, all before the
+                // waiter gets the lock back
+                let mut queue = manager.queue.lock().unwrap();
+                queue.finish(id, output(), &started[0].1);
+                for _ in 0..MAX_FINISHED_JOBS {
+                    let job = queue.push(spec("b"));
+                    let (_, control) = queue.start().pop().unwrap();
+                    queue.finish(job.id, output(), &control);
+                }
+            }
+            manager.finished.notify_all();
+            assert_eq!(
+                waiter.join().unwrap().unwrap_err(),
+                format!("No job with id {}", id)
+            );
+        });
+    }
+}
@@ -1,10 +1,52 @@
-use tauri::{Manager, State, Emitter};
-use tauri_plugin_shell::{ShellExt, process::CommandEvent, process::CommandChild};
-use std::sync::Mutex;
+use tauri::Manager;
+use tauri_plugin_shell::process::CommandChild;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+mod audit;
+mod backend;
+mod files;
+mod health;
+mod jobs;
+pub mod lifecycle;
+mod logs;
+mod permissions;
+pub mod policy;
+mod protocol;
+mod risk;
+mod settings;
+mod single_instance;
+#[cfg(test)]
+pub(crate) mod test_support;
+mod tools;
+
+use audit::AuditLog;
+use backend::{BackendConfig, BackendStatus};
+use files::copy::CopyHandle;
+use files::watch::DirectoryWatcher;
+use jobs::JobManager;
+use logs::BackendLogs;
+use lifecycle::ProcessGuard;
 
 struct AppState {
     backend_port: Mutex<Option<u16>>,
     child_process: Mutex<Option<CommandChild>>,
+    // Last line of defence: kills the sidecar when the state is dropped or the main thread panics
+    sidecar_guard: Arc<ProcessGuard>,
+    // Incremented on every start/stop so a stale supervisor knows to stand down
+    backend_generation: Mutex<u64>,
+    backend_status: Mutex<BackendStatus>,
+    backend_config: BackendConfig,
+    // Per-launch secret the sidecar requires on every request
+    backend_token: String,
+    backend_logs: BackendLogs,
+    // Running copies by id, so they can be cancelled or asked about conflicts
+    copy_operations: Mutex<HashMap<String, CopyHandle>>,
+    jobs: JobManager,
+    // Directories the UI is showing; dropping a watcher stops it
+    watchers: Mutex<HashMap<PathBuf, DirectoryWatcher>>,
+    audit: AuditLog,
 }
 
 impl Default for AppState {
@@ -12,6 +54,16 @@ impl Default for AppState {
         Self {
             backend_port: Mutex::new(None),
             child_process: Mutex::new(None),
+            sidecar_guard: Arc::new(ProcessGuard::default()),
+            backend_generation: Mutex::new(0),
+            backend_status: Mutex::new(BackendStatus::Stopped),
+            backend_config: BackendConfig::from_env(),
+            backend_token: backend::generate_token(),
+            backend_logs: BackendLogs::default(),
+            copy_operations: Mutex::new(HashMap::new()),
+            jobs: JobManager::default(),
+            watchers: Mutex::new(HashMap::new()),
+            audit: AuditLog::default(),
         }
     }
 }
@@ -22,103 +74,111 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-#[tauri::command]
-async fn get_backend_port(state: State<'_, AppState>) -> Result<Option<u16>, String> {
-    let port = state.backend_port.lock().unwrap();
-    Ok(*port)
-}
-
-#[tauri::command] 
-async fn start_backend(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let sidecar_command = app_handle
-        .shell()
-        .sidecar("server")
-        .expect("failed to create `server` binary command");
-    
-    let (mut rx, child) = sidecar_command
-        .spawn()
-        .expect("Failed to spawn sidecar");
-    
-    // Store the child process in the state
-    let app_state = app_handle.state::<AppState>();
-    *app_state.child_process.lock().unwrap() = Some(child);
-    
-    // Listen for port output
-    let app_handle_clone = app_handle.clone();
-    tauri::async_runtime::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            if let CommandEvent::Stdout(line) = event {
-                let line_str = String::from_utf8_lossy(&line);
-                if line_str.starts_with("BACKEND_PORT:") {
-                    if let Ok(port) = line_str.replace("BACKEND_PORT:", "").trim().parse::<u16>() {
-                        {
-                            let app_state = app_handle_clone.state::<AppState>();
-                            let mut backend_port = app_state.backend_port.lock().unwrap();
-                            *backend_port = Some(port);
-                        }
-                        
-                        // Emit event to frontend
-                        app_handle_clone.emit("backend-ready", port).unwrap();
-                        println!("Backend started on port: {}", port);
-                    }
-                } else {
-                    println!("[sidecar stdout]: {}", line_str);
-                }
-            } else if let CommandEvent::Stderr(line) = event {
-                let line_str = String::from_utf8_lossy(&line);
-                eprintln!("[sidecar stderr]: {}", line_str);
-            }
-        }
-    });
-    
-    Ok(())
-}
-
-#[tauri::command]
-async fn stop_backend(state: State<'_, AppState>) -> Result<(), String> {
-    let mut child_process = state.child_process.lock().unwrap();
-    if let Some(child) = child_process.take() {
-        child.kill().map_err(|e| format!("Failed to kill backend process: {}", e))?;
-        println!("Backend process terminated");
-    }
-    Ok(())
-}
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let app = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .manage(AppState::default())
-        .invoke_handler(tauri::generate_handler![greet, start_backend, get_backend_port, stop_backend])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            backend::start_backend,
+            backend::get_backend_port,
+            backend::get_backend_status,
+            backend::get_backend_token,
+            backend::stop_backend,
+            logs::get_backend_logs,
+            files::list::list_directory,
+            files::list::stream_directory,
+            files::read::read_file,
+            files::detect::detect_file_type,
+            files::write::write_file,
+            files::write::create_directory,
+            files::copy::copy_item,
+            files::copy::cancel_copy,
+            files::copy::resolve_copy_conflict,
+            files::transfer::move_item,
+            files::transfer::rename_item,
+            files::trash::trash_item,
+            files::trash::list_trash,
+            files::trash::restore_from_trash,
+            files::trash::empty_trash,
+            files::trash::delete_permanently,
+            files::tree::get_tree,
+            files::watch::watch_path,
+            files::watch::unwatch_path,
+            jobs::submit_job,
+            jobs::list_jobs,
+            jobs::cancel_job,
+            jobs::pause_job,
+            jobs::resume_job,
+            policy::get_access_policy,
+            policy::set_access_policy,
+            permissions::get_permission_profiles,
+            permissions::set_permission_profiles,
+            permissions::evaluate_tool_calls,
+            risk::assess_tool_calls,
+            audit::record_tool_decision,
+            audit::query_audit_log,
+            audit::verify_audit_log,
+            tools::execute_tool
+        ])
         .setup(|app| {
+            // Must run before the backend starts, so a second launch never spawns one
+            match single_instance::acquire(app.handle()) {
+                Ok(single_instance::Instance::Primary(lock)) => {
+                    app.manage(lock);
+                }
+                Ok(single_instance::Instance::Secondary) => {
+                    println!("FSai is already running, handed over to the existing window");
+                    std::process::exit(0);
+                }
+                Err(e) => eprintln!("Single-instance check failed, continuing anyway: {}", e),
+            }
+
+            match app.path().app_data_dir() {
+                Ok(dir) => {
+                    let state = app.state::<AppState>();
+                    state.backend_logs.persist_to(dir.join("logs"));
+                    let audit_dir = dir.join("audit");
+                    policy::protect(audit_dir.clone());
+                    state.audit.persist_to(audit_dir);
+                }
+                Err(e) => eprintln!(
+                    "Failed to resolve app data dir, backend logs stay in memory and tool calls are not audited: {}",
+                    e
+                ),
+            }
+
+            lifecycle::kill_on_panic(&app.state::<AppState>().sidecar_guard);
+            lifecycle::exit_on_signals(app.handle().clone());
+
             let app_handle = app.handle().clone();
             
             // Auto-start backend
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = start_backend(app_handle).await {
+                if let Err(e) = backend::start_backend(app_handle).await {
                     eprintln!("Failed to start backend: {}", e);
                 }
             });
             
             Ok(())
         })
-        .on_window_event(|window, event| {
-            match event {
-                tauri::WindowEvent::CloseRequested { .. } => {
-                    let app_state = window.state::<AppState>();
-                    let mut child_process = app_state.child_process.lock().unwrap();
-                    if let Some(child) = child_process.take() {
-                        if let Err(e) = child.kill() {
-                            eprintln!("Failed to kill backend process on window close: {}", e);
-                        } else {
-                            println!("Backend process terminated on window close");
-                        }
-                    }
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application");
+
+    app.run(|app_handle, event| {
+        // Fires once on every regular exit path: last window closed, app.exit(), signals
+        if let tauri::RunEvent::Exit = event {
+            let app_state = app_handle.state::<AppState>();
+            if let Some(child) = backend::detach_child(&app_state) {
+                let grace = app_state.backend_config.shutdown_grace;
+                if let Err(e) = backend::shutdown_child(child, grace) {
+                    eprintln!("Failed to kill backend process on exit: {}", e);
+                } else {
+                    println!("Backend process terminated on exit");
                 }
-                _ => {}
             }
-        })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        }
+    });
 }
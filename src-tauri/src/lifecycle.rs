@@ -0,0 +1,139 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tauri::AppHandle;
+
+// How often to check whether a process has exited during a graceful shutdown
+#[cfg(unix)]
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Remembers the pid of a child process and kills it when dropped, so the child
+/// cannot outlive its owner even if the normal shutdown path never runs.
+#[derive(Debug, Default)]
+pub struct ProcessGuard {
+    // 0 means no process is being tracked
+    pid: AtomicU32,
+}
+
+impl ProcessGuard {
+    /// Starts tracking `pid`, replacing any previously tracked process.
+    pub fn track(&self, pid: u32) {
+        self.pid.store(pid, Ordering::SeqCst);
+    }
+
+    /// Stops tracking without touching the process, e.g. once it has exited on its own.
+    pub fn release(&self) {
+        self.pid.store(0, Ordering::SeqCst);
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        match self.pid.load(Ordering::SeqCst) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// Force-kills the tracked process, if any.
+    pub fn kill(&self) {
+        let pid = self.pid.swap(0, Ordering::SeqCst);
+        if pid != 0 {
+            kill_pid(pid);
+        }
+    }
+}
+
+impl Drop for ProcessGuard {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+#[cfg(unix)]
+fn kill_pid(pid: u32) {
+    // SAFETY: plain syscall; an error just means the process is already gone
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_pid(pid: u32) {
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .status();
+}
+
+/// Asks `pid` to exit with SIGTERM and waits up to `grace` for it to go away,
+/// then falls back to `kill`. The process has to be reaped by someone else in
+/// the meantime: an exited but unreaped process still counts as running. Other
+/// platforms have no SIGTERM and go straight to `kill`.
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub fn terminate<E>(
+    pid: u32,
+    grace: Duration,
+    kill: impl FnOnce() -> Result<(), E>,
+) -> Result<(), E> {
+    #[cfg(unix)]
+    {
+        let pid = pid as libc::pid_t;
+        // SAFETY: plain syscall; a failure just means the process is already gone
+        if unsafe { libc::kill(pid, libc::SIGTERM) } == 0 {
+            let deadline = std::time::Instant::now() + grace;
+            while std::time::Instant::now() < deadline {
+                // SAFETY: signal 0 only checks whether the pid still exists
+                if unsafe { libc::kill(pid, 0) } != 0 {
+                    return Ok(());
+                }
+                std::thread::sleep(SHUTDOWN_POLL_INTERVAL);
+            }
+            eprintln!("Process {} did not exit within {:?} of SIGTERM, killing it", pid, grace);
+        }
+    }
+    kill()
+}
+
+/// Kills the sidecar if the main thread panics, before the default hook runs.
+/// Panics on other threads are left alone since they do not take the app down.
+pub fn kill_on_panic(guard: &Arc<ProcessGuard>) {
+    // Weak so the hook does not keep the guard alive past the state that owns it
+    let guard = Arc::downgrade(guard);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            if let Some(guard) = Weak::upgrade(&guard) {
+                guard.kill();
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Turns Ctrl+C (and SIGTERM on unix) into a regular app exit, so `RunEvent::Exit`
+/// gets a chance to shut the sidecar down.
+pub(crate) fn exit_on_signals(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut terminate = match signal(SignalKind::terminate()) {
+                Ok(terminate) => terminate,
+                Err(e) => {
+                    eprintln!("Failed to install SIGTERM handler: {}", e);
+                    return;
+                }
+            };
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+        }
+        #[cfg(not(unix))]
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            eprintln!("Failed to install Ctrl+C handler: {}", e);
+            return;
+        }
+
+        println!("Received termination signal, shutting down");
+        app_handle.exit(0);
+    });
+}
@@ -0,0 +1,258 @@
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Entries kept in memory for `get_backend_logs`
+const RING_CAPACITY: usize = 2000;
+// Rotate `backend.log` once it grows past this, keeping `MAX_LOG_FILES` files in total
+const MAX_LOG_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_LOG_FILES: usize = 5;
+const LOG_FILE: &str = "backend.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// Where a log line came from: the sidecar's own output or the Rust supervisor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogSource {
+    Stdout,
+    Stderr,
+    Supervisor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub level: LogLevel,
+    pub source: LogSource,
+    pub message: String,
+}
+
+struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(dir: PathBuf) -> std::io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(LOG_FILE))?;
+        let size = file.metadata()?.len();
+        Ok(Self { dir, file, size })
+    }
+
+    fn append(&mut self, entry: &LogEntry) -> std::io::Result<()> {
+        if self.size >= MAX_LOG_FILE_SIZE {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shifts `backend.log.N` to `backend.log.N+1`, dropping the oldest, and starts
+    /// a fresh `backend.log`.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let rotated = |n: usize| self.dir.join(format!("{}.{}", LOG_FILE, n));
+        let _ = fs::remove_file(rotated(MAX_LOG_FILES - 1));
+        for n in (1..MAX_LOG_FILES - 1).rev() {
+            let _ = fs::rename(rotated(n), rotated(n + 1));
+        }
+        fs::rename(self.dir.join(LOG_FILE), rotated(1))?;
+
+        *self = Self::open(self.dir.clone())?;
+        Ok(())
+    }
+}
+
+/// Sidecar log capture: a bounded in-memory ring plus rotated JSON-lines files
+/// under the app data dir.
+#[derive(Default)]
+pub struct BackendLogs {
+    entries: Mutex<VecDeque<LogEntry>>,
+    next_seq: Mutex<u64>,
+    file: Mutex<Option<LogFile>>,
+}
+
+impl BackendLogs {
+    /// Starts persisting entries to `dir`. Entries recorded before this are kept in memory only.
+    pub fn persist_to(&self, dir: PathBuf) {
+        match LogFile::open(dir) {
+            Ok(file) => *self.file.lock().unwrap() = Some(file),
+            Err(e) => eprintln!("Failed to open backend log file: {}", e),
+        }
+    }
+
+    fn push(&self, level: LogLevel, source: LogSource, message: String) -> LogEntry {
+        let seq = {
+            let mut next_seq = self.next_seq.lock().unwrap();
+            *next_seq += 1;
+            *next_seq
+        };
+        let entry = LogEntry {
+            seq,
+            timestamp: now_millis(),
+            level,
+            source,
+            message,
+        };
+
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if entries.len() == RING_CAPACITY {
+                entries.pop_front();
+            }
+            entries.push_back(entry.clone());
+        }
+
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            if let Err(e) = file.append(&entry) {
+                eprintln!("Failed to write backend log file: {}", e);
+            }
+        }
+
+        entry
+    }
+
+    fn query(&self, since: Option<u64>, level: Option<LogLevel>) -> Vec<LogEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| since.is_none_or(|since| entry.timestamp >= since))
+            .filter(|entry| level.is_none_or(|level| entry.level >= level))
+            .cloned()
+            .collect()
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Records a line in the backend log, echoes it to the console and emits `backend-log`.
+pub fn record(app_handle: &AppHandle, level: LogLevel, source: LogSource, message: impl Into<String>) {
+    let message = message.into();
+    match source {
+        LogSource::Stdout => println!("[sidecar stdout]: {}", message),
+        LogSource::Stderr => eprintln!("[sidecar stderr]: {}", message),
+        LogSource::Supervisor if level == LogLevel::Info => println!("{}", message),
+        LogSource::Supervisor => eprintln!("{}", message),
+    }
+
+    let entry = app_handle
+        .state::<AppState>()
+        .backend_logs
+        .push(level, source, message);
+    let _ = app_handle.emit("backend-log", entry);
+}
+
+/// Returns buffered log entries at or after `since` (ms since the Unix epoch) and at
+/// or above `level`.
+#[tauri::command]
+pub async fn get_backend_logs(
+    since: Option<u64>,
+    level: Option<LogLevel>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LogEntry>, String> {
+    Ok(state.backend_logs.query(since, level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    fn first_seq(path: &std::path::Path) -> u64 {
+        let content = fs::read_to_string(path).unwrap();
+        let first: LogEntry = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+        first.seq
+    }
+
+    #[test]
+    fn keeps_the_most_recent_entries_in_memory() {
+        let logs = BackendLogs::default();
+        for n in 0..RING_CAPACITY + 5 {
+            logs.push(LogLevel::Info, LogSource::Stdout, format!("line {}", n));
+        }
+        let entries = logs.query(None, None);
+        assert_eq!(entries.len(), RING_CAPACITY);
+        assert_eq!(entries[0].seq, 6);
+        assert_eq!(entries[RING_CAPACITY - 1].seq, RING_CAPACITY as u64 + 5);
+    }
+
+    #[test]
+    fn filters_by_level() {
+        let logs = BackendLogs::default();
+        logs.push(LogLevel::Info, LogSource::Stdout, "ready".to_string());
+        logs.push(LogLevel::Error, LogSource::Stderr, "crashed".to_string());
+        let errors = logs.query(None, Some(LogLevel::Warn));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "crashed");
+    }
+
+    #[test]
+    fn rotates_files_and_drops_the_oldest() {
+        let sandbox = Sandbox::new("logs");
+        let logs = BackendLogs::default();
+        logs.persist_to(sandbox.base.clone());
+        let message = "x".repeat(16 * 1024);
+        // Enough for every file to fill up, and then some
+        let lines = (MAX_LOG_FILES as u64 + 1) * MAX_LOG_FILE_SIZE / message.len() as u64;
+        for _ in 0..lines {
+            logs.push(LogLevel::Info, LogSource::Stdout, message.clone());
+        }
+
+        let rotated = |n: usize| sandbox.path(format!("{}.{}", LOG_FILE, n));
+        assert!(sandbox.path(LOG_FILE).exists());
+        for n in 1..MAX_LOG_FILES {
+            let size = fs::metadata(rotated(n)).unwrap().len();
+            assert!(size >= MAX_LOG_FILE_SIZE, "{} is only {} bytes", n, size);
+            assert!(size < MAX_LOG_FILE_SIZE + message.len() as u64 + 256);
+        }
+        assert!(!rotated(MAX_LOG_FILES).exists());
+        // Older files hold older entries, and the very first ones are gone
+        for n in 1..MAX_LOG_FILES - 1 {
+            assert!(first_seq(&rotated(n)) > first_seq(&rotated(n + 1)));
+        }
+        assert!(first_seq(&rotated(MAX_LOG_FILES - 1)) > 1);
+    }
+
+    #[test]
+    fn picks_up_the_size_of_an_existing_file() {
+        let sandbox = Sandbox::new("logs");
+        sandbox.file(LOG_FILE, vec![b'\n'; MAX_LOG_FILE_SIZE as usize]);
+        let logs = BackendLogs::default();
+        logs.persist_to(sandbox.base.clone());
+        logs.push(LogLevel::Info, LogSource::Supervisor, "restarted".to_string());
+        assert_eq!(
+            fs::metadata(sandbox.path(format!("{}.1", LOG_FILE))).unwrap().len(),
+            MAX_LOG_FILE_SIZE
+        );
+        assert_eq!(first_seq(&sandbox.path(LOG_FILE)), 1);
+    }
+}
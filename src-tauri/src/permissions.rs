@@ -0,0 +1,575 @@
+use crate::audit;
+use crate::files::footprint;
+use crate::files::write::{self, WriteOptions};
+use crate::policy::{self, PathPolicy};
+use crate::settings;
+use crate::tools::{self, ToolCall, ToolContext};
+use globset::{GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+// Sizing a directory means walking it; past this many entries the size counts as unknown
+const MAX_SIZED_ENTRIES: usize = 10_000;
+
+/// What happens to a tool call the model proposes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Decision {
+    /// Runs without asking
+    Allow,
+    /// Waits for the user to confirm it
+    Ask,
+    /// Is refused without asking
+    Deny,
+}
+
+impl Decision {
+    fn as_str(self) -> &'static str {
+        match self {
+            Decision::Allow => "allow",
+            Decision::Ask => "ask",
+            Decision::Deny => "deny",
+        }
+    }
+}
+
+/// Overrides a tool's decision for calls within a scope. A call is in scope
+/// when every path it touches matches one of `paths` and the item it acts on
+/// is within the size bounds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRule {
+    /// Globs like the access policy's deny patterns; empty matches any path
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// In bytes; directories count with everything in them
+    #[serde(default)]
+    pub min_size: Option<u64>,
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    pub decision: Decision,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolPermission {
+    /// Applies when none of the rules match
+    pub decision: Decision,
+    /// Checked in order; the first match decides
+    #[serde(default)]
+    pub rules: Vec<PermissionRule>,
+}
+
+/// The user-editable permission profiles, stored as `permissions.json` next to
+/// the access policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionProfiles {
+    /// Keyed by tool type, e.g. `move_item`
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolPermission>,
+    /// For tools without a profile
+    pub default: Decision,
+}
+
+impl Default for PermissionProfiles {
+    // Everything is confirmed until the user decides otherwise
+    fn default() -> Self {
+        Self {
+            tools: BTreeMap::new(),
+            default: Decision::Ask,
+        }
+    }
+}
+
+impl PermissionProfiles {
+    fn path() -> Option<PathBuf> {
+        Some(settings::config_dir()?.join("permissions.json"))
+    }
+
+    /// The saved profiles, or the defaults if there are none.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Could not parse {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Could not determine the config directory")?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize permission profiles: {}", e))?;
+        // App configuration, outside what the access policy governs
+        write::write(
+            &path,
+            content.as_bytes(),
+            &WriteOptions::default(),
+            &PathPolicy::unrestricted(),
+        )?;
+        Ok(())
+    }
+}
+
+/// The outcome of evaluating one tool call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permission {
+    pub decision: Decision,
+    /// Which profile or rule decided, for the confirmation bar
+    pub reason: String,
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// `None` when the rule applies to any path
+    paths: Option<GlobSet>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    decision: Decision,
+    scope: String,
+}
+
+impl Rule {
+    fn compile(rule: &PermissionRule, home: Option<&Path>) -> Result<Self, String> {
+        let paths = if rule.paths.is_empty() {
+            None
+        } else {
+            let mut globs = GlobSetBuilder::new();
+            for pattern in &rule.paths {
+                policy::add_path_glob(&mut globs, pattern, home)
+                    .map_err(|e| format!("Invalid path pattern '{}': {}", pattern, e))?;
+            }
+            Some(
+                globs
+                    .build()
+                    .map_err(|e| format!("Invalid path patterns: {}", e))?,
+            )
+        };
+
+        let mut scope = Vec::new();
+        if !rule.paths.is_empty() {
+            scope.push(format!("in {}", rule.paths.join(" or ")));
+        }
+        if let Some(min) = rule.min_size {
+            scope.push(format!("of at least {} bytes", min));
+        }
+        if let Some(max) = rule.max_size {
+            scope.push(format!("of at most {} bytes", max));
+        }
+        Ok(Self {
+            paths,
+            min_size: rule.min_size,
+            max_size: rule.max_size,
+            decision: rule.decision,
+            scope: scope.join(" "),
+        })
+    }
+
+    fn matches(&self, paths: &[PathBuf], size: Option<u64>) -> bool {
+        if let Some(globs) = &self.paths {
+            if paths.is_empty() || !paths.iter().all(|path| globs.is_match(path)) {
+                return false;
+            }
+        }
+        if self.min_size.is_none() && self.max_size.is_none() {
+            return true;
+        }
+        // A rule about sizes says nothing about items whose size is unknown
+        size.is_some_and(|size| {
+            self.min_size.is_none_or(|min| size >= min)
+                && self.max_size.is_none_or(|max| size <= max)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Profile {
+    decision: Decision,
+    rules: Vec<Rule>,
+}
+
+/// Compiled [`PermissionProfiles`], deciding whether a tool call runs right
+/// away, waits for confirmation or is refused. Calls touching paths outside
+/// the access policy are always refused.
+#[derive(Debug)]
+pub struct Permissions {
+    tools: BTreeMap<String, Profile>,
+    default: Decision,
+    paths: PathPolicy,
+}
+
+impl Permissions {
+    /// Compiles `profiles`, failing on globs that do not parse.
+    pub fn compile(profiles: &PermissionProfiles, paths: PathPolicy) -> Result<Self, String> {
+        let home = policy::home_dir();
+        let mut tools = BTreeMap::new();
+        for (tool, permission) in &profiles.tools {
+            let rules = permission
+                .rules
+                .iter()
+                .map(|rule| Rule::compile(rule, home.as_deref()))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("{}: {}", tool, e))?;
+            tools.insert(
+                tool.clone(),
+                Profile {
+                    decision: permission.decision,
+                    rules,
+                },
+            );
+        }
+        Ok(Self {
+            tools,
+            default: profiles.default,
+            paths,
+        })
+    }
+
+    /// The profiles in effect right now, as saved by `set_permission_profiles`.
+    pub fn current() -> Self {
+        let paths = PathPolicy::current();
+        let profiles = PermissionProfiles::load();
+        Permissions::compile(&profiles, paths.clone()).unwrap_or_else(|e| {
+            eprintln!(
+                "Permission profiles are invalid, falling back to defaults: {}",
+                e
+            );
+            Self {
+                tools: BTreeMap::new(),
+                default: Decision::Ask,
+                paths,
+            }
+        })
+    }
+
+    pub fn evaluate(&self, call: &ToolCall, context: &ToolContext) -> Permission {
+        let tool = call.tool_type.as_str();
+        let mut paths = Vec::new();
+        for (path, access) in tools::targets(call, context) {
+            match self.paths.check(&path, access) {
+                Ok(path) => paths.push(path),
+                Err(e) => {
+                    return Permission {
+                        decision: Decision::Deny,
+                        reason: e,
+                    }
+                }
+            }
+        }
+
+        let Some(profile) = self.tools.get(tool) else {
+            return Permission {
+                decision: self.default,
+                reason: format!(
+                    "Tools without a profile are set to {}",
+                    self.default.as_str()
+                ),
+            };
+        };
+        // Only worth walking a directory for if some rule asks about sizes
+        let size = if profile
+            .rules
+            .iter()
+            .any(|rule| rule.min_size.is_some() || rule.max_size.is_some())
+        {
+            subject_size(call, &paths)
+        } else {
+            None
+        };
+        match profile.rules.iter().find(|rule| rule.matches(&paths, size)) {
+            Some(rule) => Permission {
+                decision: rule.decision,
+                reason: format!(
+                    "{} {} is set to {}",
+                    tool,
+                    rule.scope,
+                    rule.decision.as_str()
+                ),
+            },
+            None => Permission {
+                decision: profile.decision,
+                reason: format!("{} is set to {}", tool, profile.decision.as_str()),
+            },
+        }
+    }
+}
+
+/// The size of what the call acts on: the content for writes, otherwise the
+/// first path it touches.
+fn subject_size(call: &ToolCall, paths: &[PathBuf]) -> Option<u64> {
+    if call.tool_type == "write_file" {
+        return call
+            .parameters
+            .content
+            .as_ref()
+            .map(|content| content.len() as u64);
+    }
+    let footprint = footprint(paths.first()?, MAX_SIZED_ENTRIES).ok()?;
+    footprint.complete.then_some(footprint.bytes)
+}
+
+#[tauri::command]
+pub async fn get_permission_profiles() -> Result<PermissionProfiles, String> {
+    Ok(PermissionProfiles::load())
+}
+
+/// Validates and saves new profiles, which apply from the next tool call.
+#[tauri::command]
+pub async fn set_permission_profiles(
+    profiles: PermissionProfiles,
+) -> Result<PermissionProfiles, String> {
+    Permissions::compile(&profiles, PathPolicy::unrestricted())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        profiles.save()?;
+        Ok(profiles)
+    })
+    .await
+    .map_err(|e| format!("Failed to save permission profiles: {}", e))?
+}
+
+/// Decides, for each proposed call, whether it runs right away, needs
+/// confirmation or is refused, and records the proposal in the audit log.
+/// `execute_tool` repeats the check, so skipping this does not get a denied
+/// call through.
+#[tauri::command]
+pub async fn evaluate_tool_calls(
+    app: AppHandle,
+    tool_calls: Vec<ToolCall>,
+    context: Option<ToolContext>,
+) -> Result<Vec<Permission>, String> {
+    let context = context.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let permissions = Permissions::current();
+        let decided: Vec<Permission> = tool_calls
+            .iter()
+            .map(|call| permissions.evaluate(call, &context))
+            .collect();
+        audit::record_proposed(&app, &tool_calls, &context, &decided);
+        decided
+    })
+    .await
+    .map_err(|e| format!("Failed to evaluate tool calls: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{call, Sandbox};
+    use crate::tools::ToolParameters;
+
+    /// `Downloads` with a few files and photos, and an empty `Documents`.
+    fn sandbox() -> Sandbox {
+        let sandbox = Sandbox::new("permissions");
+        sandbox.dir("Documents");
+        sandbox.file("Downloads/small.txt", "tiny");
+        sandbox.file("Downloads/big.bin", vec![0; 4096]);
+        sandbox.file("Downloads/photos/a.jpg", vec![0; 1000]);
+        sandbox.file("Downloads/photos/b.jpg", vec![0; 1000]);
+        sandbox
+    }
+
+    fn glob(sandbox: &Sandbox, dir: &str) -> String {
+        sandbox.path(dir).to_string_lossy().into_owned()
+    }
+
+    fn permissions(sandbox: &Sandbox, profiles: &PermissionProfiles) -> Permissions {
+        Permissions::compile(profiles, PathPolicy::new([sandbox.base.clone()])).unwrap()
+    }
+
+    fn moving(from: &str, to: &str) -> ToolCall {
+        call(
+            "move_item",
+            ToolParameters {
+                source_path: Some(from.to_string()),
+                destination_path: Some(to.to_string()),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn deleting(path: &str) -> ToolCall {
+        call(
+            "delete_item",
+            ToolParameters {
+                path: Some(path.to_string()),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn profiles(tool: &str, decision: Decision, rules: Vec<PermissionRule>) -> PermissionProfiles {
+        PermissionProfiles {
+            tools: BTreeMap::from([(tool.to_string(), ToolPermission { decision, rules })]),
+            default: Decision::Ask,
+        }
+    }
+
+    fn rule(paths: &[String], decision: Decision) -> PermissionRule {
+        PermissionRule {
+            paths: paths.to_vec(),
+            min_size: None,
+            max_size: None,
+            decision,
+        }
+    }
+
+    #[test]
+    fn tools_without_a_profile_use_the_default() {
+        let sandbox = sandbox();
+        let permissions = permissions(&sandbox, &PermissionProfiles::default());
+        let permission = permissions.evaluate(&deleting("Downloads/small.txt"), &sandbox.context());
+        assert_eq!(permission.decision, Decision::Ask);
+    }
+
+    #[test]
+    fn scoped_rules_override_the_tool_decision() {
+        let sandbox = sandbox();
+        let permissions = permissions(&sandbox, &profiles(
+            "move_item",
+            Decision::Ask,
+            vec![
+                rule(&[glob(&sandbox, "Documents")], Decision::Ask),
+                rule(&[glob(&sandbox, "Downloads")], Decision::Allow),
+            ],
+        ));
+        let context = sandbox.context();
+        let inside =
+            permissions.evaluate(&moving("Downloads/small.txt", "Downloads/photos"), &context);
+        assert_eq!(inside.decision, Decision::Allow);
+        let into_documents =
+            permissions.evaluate(&moving("Downloads/small.txt", "Documents"), &context);
+        assert_eq!(into_documents.decision, Decision::Ask);
+        let elsewhere = permissions.evaluate(&moving("Downloads/small.txt", "small.txt"), &context);
+        assert_eq!(elsewhere.decision, Decision::Ask);
+    }
+
+    #[test]
+    fn the_first_matching_rule_wins() {
+        let sandbox = sandbox();
+        let permissions = permissions(&sandbox, &profiles(
+            "delete_item",
+            Decision::Allow,
+            vec![
+                rule(&[glob(&sandbox, "Downloads/photos")], Decision::Deny),
+                rule(&[glob(&sandbox, "Downloads")], Decision::Ask),
+            ],
+        ));
+        let context = sandbox.context();
+        let photo = permissions.evaluate(&deleting("Downloads/photos/a.jpg"), &context);
+        assert_eq!(photo.decision, Decision::Deny);
+        assert!(photo.reason.contains("photos"));
+        let download = permissions.evaluate(&deleting("Downloads/small.txt"), &context);
+        assert_eq!(download.decision, Decision::Ask);
+        let document = permissions.evaluate(&deleting("Documents"), &context);
+        assert_eq!(document.decision, Decision::Allow);
+    }
+
+    #[test]
+    fn size_bounds_limit_rules() {
+        let sandbox = sandbox();
+        let permissions = permissions(&sandbox, &profiles(
+            "delete_item",
+            Decision::Ask,
+            vec![PermissionRule {
+                paths: vec![],
+                min_size: None,
+                max_size: Some(2048),
+                decision: Decision::Allow,
+            }],
+        ));
+        let context = sandbox.context();
+        let small = permissions.evaluate(&deleting("Downloads/small.txt"), &context);
+        assert_eq!(small.decision, Decision::Allow);
+        let big = permissions.evaluate(&deleting("Downloads/big.bin"), &context);
+        assert_eq!(big.decision, Decision::Ask);
+        let photos = permissions.evaluate(&deleting("Downloads/photos"), &context);
+        assert_eq!(photos.decision, Decision::Allow);
+        let everything = permissions.evaluate(&deleting("Downloads"), &context);
+        assert_eq!(everything.decision, Decision::Ask);
+    }
+
+    #[test]
+    fn writes_are_sized_by_their_content() {
+        let sandbox = sandbox();
+        let permissions = permissions(&sandbox, &profiles(
+            "write_file",
+            Decision::Allow,
+            vec![PermissionRule {
+                paths: vec![],
+                min_size: Some(100),
+                max_size: None,
+                decision: Decision::Ask,
+            }],
+        ));
+        let write = |content: &str| {
+            call(
+                "write_file",
+                ToolParameters {
+                    path: Some("Downloads/new.txt".to_string()),
+                    content: Some(content.to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+        let context = sandbox.context();
+        assert_eq!(
+            permissions.evaluate(&write("short"), &context).decision,
+            Decision::Allow
+        );
+        assert_eq!(
+            permissions
+                .evaluate(&write(&"x".repeat(200)), &context)
+                .decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn paths_outside_the_access_policy_are_denied() {
+        let sandbox = sandbox();
+        let permissions = permissions(&sandbox, &profiles("move_item", Decision::Allow, vec![]));
+        let permission = permissions.evaluate(
+            &moving("Downloads/small.txt", "../escaped.txt"),
+            &sandbox.context(),
+        );
+        assert_eq!(permission.decision, Decision::Deny);
+        assert!(permission.reason.contains("disallowed"));
+    }
+
+    #[test]
+    fn compile_rejects_invalid_patterns() {
+        let sandbox = sandbox();
+        let invalid = profiles(
+            "move_item",
+            Decision::Ask,
+            vec![rule(&["[unclosed".to_string()], Decision::Allow)],
+        );
+        assert!(Permissions::compile(&invalid, PathPolicy::new([sandbox.base.clone()])).is_err());
+    }
+
+    #[test]
+    fn profiles_use_the_frontend_shape() {
+        let profiles: PermissionProfiles = serde_json::from_str(
+            r#"{"tools":{"move_item":{"decision":"ask","rules":[{"paths":["~/Downloads"],"maxSize":1024,"decision":"allow"}]}},"default":"ask"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            profiles.tools["move_item"].rules,
+            vec![PermissionRule {
+                paths: vec!["~/Downloads".to_string()],
+                min_size: None,
+                max_size: Some(1024),
+                decision: Decision::Allow,
+            }]
+        );
+    }
+}
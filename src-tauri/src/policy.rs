@@ -0,0 +1,830 @@
+use crate::files::write::{self, WriteOptions};
+use crate::settings;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+// Same limit Linux applies before failing with ELOOP
+const MAX_SYMLINK_HOPS: usize = 40;
+
+// Denied under every policy; filled in at setup, once the app's own directories are known
+static PROTECTED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Denies `dir` and everything in it under every policy compiled from now on.
+/// For directories that are only known from the `AppHandle`, like the audit log's.
+pub fn protect(dir: PathBuf) {
+    PROTECTED.lock().unwrap().push(dir);
+}
+
+enum Part {
+    Root(PathBuf),
+    Parent,
+    Name(OsString),
+}
+
+/// Queues the components of `path` on `stack`, which is consumed from the end.
+fn push_parts(stack: &mut Vec<Part>, path: &Path) {
+    let mut root = PathBuf::new();
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => root.push(component.as_os_str()),
+            Component::CurDir => {}
+            Component::ParentDir => parts.push(Part::Parent),
+            Component::Normal(name) => parts.push(Part::Name(name.to_os_string())),
+        }
+    }
+    stack.extend(parts.into_iter().rev());
+    if !root.as_os_str().is_empty() {
+        stack.push(Part::Root(root));
+    }
+}
+
+/// Resolves `path` the way the OS would: every symlink is followed and every
+/// `..` applied to the directory it actually lands in. Unlike
+/// [`fs::canonicalize`], components that do not exist yet are allowed, so the
+/// destination of a write or move can be checked before it is created.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    let mut stack = Vec::new();
+    push_parts(&mut stack, &std::path::absolute(path)?);
+
+    let mut resolved = PathBuf::new();
+    let mut hops = 0;
+    while let Some(part) = stack.pop() {
+        match part {
+            Part::Root(root) => resolved = root,
+            Part::Parent => {
+                resolved.pop();
+            }
+            Part::Name(name) => {
+                let candidate = resolved.join(&name);
+                match fs::symlink_metadata(&candidate) {
+                    Ok(metadata) if metadata.file_type().is_symlink() => {
+                        hops += 1;
+                        if hops > MAX_SYMLINK_HOPS {
+                            return Err(io::Error::other("Too many levels of symbolic links"));
+                        }
+                        // Relative targets continue from the link's own directory
+                        push_parts(&mut stack, &fs::read_link(&candidate)?);
+                    }
+                    _ => resolved = candidate,
+                }
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Resolves the parent directories but not the item itself, which is what a
+/// rename, move or delete acts on when it is a symlink. Falls back to `path`
+/// as given when it cannot be resolved.
+pub(crate) fn canonicalize_entry(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// What a command is about to do with a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    /// Creating, changing, moving or deleting
+    Write,
+}
+
+/// A directory the policy opens up, with everything below it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessRoot {
+    /// May start with `~` for the home directory
+    pub path: String,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// The user-editable access policy, stored as `access-policy.json` next to the
+/// sidecar's settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessPolicy {
+    pub roots: Vec<AccessRoot>,
+    /// Globs for paths that stay off limits inside the roots. Each one also
+    /// covers everything below what it matches.
+    pub deny: Vec<String>,
+}
+
+// Credentials, keyrings and browser profiles, on every platform
+const DEFAULT_DENY: [&str; 24] = [
+    "~/.ssh",
+    "~/.gnupg",
+    "~/.aws",
+    "~/.azure",
+    "~/.kube",
+    "~/.docker",
+    "~/.password-store",
+    "~/.netrc",
+    "~/.pgpass",
+    "~/.git-credentials",
+    "~/.config/gcloud",
+    "~/.local/share/keyrings",
+    "~/.mozilla",
+    "~/.config/google-chrome",
+    "~/.config/chromium",
+    "~/.config/BraveSoftware",
+    "~/.config/microsoft-edge",
+    "~/Library/Keychains",
+    "~/Library/Application Support/Google/Chrome",
+    "~/Library/Application Support/Firefox",
+    "~/Library/Application Support/BraveSoftware",
+    "~/AppData/Roaming/Mozilla",
+    "~/AppData/Local/Google/Chrome/User Data",
+    "~/AppData/Local/Microsoft/Edge/User Data",
+];
+
+impl AccessPolicy {
+    /// What applies before the user saves a policy: the old `allowRootAccess`
+    /// choice between the home directory and everything, minus credentials.
+    fn from_settings() -> Self {
+        let root = if settings::load().allow_root_access {
+            filesystem_root()
+        } else {
+            "~".to_string()
+        };
+        Self {
+            roots: vec![AccessRoot {
+                path: root,
+                read_only: false,
+            }],
+            deny: DEFAULT_DENY.iter().map(|glob| glob.to_string()).collect(),
+        }
+    }
+
+    fn path() -> Option<PathBuf> {
+        Some(settings::config_dir()?.join("access-policy.json"))
+    }
+
+    /// The saved policy, or the one derived from the settings if there is none.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::from_settings();
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                // Falling back to the defaults beats locking the user out entirely
+                eprintln!("Could not parse {}: {}", path.display(), e);
+                Self::from_settings()
+            }),
+            Err(_) => Self::from_settings(),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("Could not determine the config directory")?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize access policy: {}", e))?;
+        // The policy protects its own file, so it cannot be the judge here
+        write::write(
+            &path,
+            content.as_bytes(),
+            &WriteOptions::default(),
+            &PathPolicy::unrestricted(),
+        )?;
+        Ok(())
+    }
+}
+
+fn filesystem_root() -> String {
+    if cfg!(windows) {
+        std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string()) + "\\"
+    } else {
+        "/".to_string()
+    }
+}
+
+/// Expands a leading `~` to the (canonical) home directory.
+fn expand_home(path: &str, home: Option<&Path>) -> Result<PathBuf, String> {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => rest,
+        _ => return Ok(PathBuf::from(path)),
+    };
+    let home = home.ok_or("Could not determine the home directory")?;
+    Ok(home.join(rest.trim_start_matches(['/', '\\'])))
+}
+
+/// Why a path was refused.
+enum Refusal {
+    OutsideRoots,
+    Denied,
+    ReadOnly,
+}
+
+impl Refusal {
+    fn message(&self, path: &Path) -> String {
+        match self {
+            Refusal::ReadOnly => format!(
+                "Path '{}' is read-only under the access policy.",
+                path.display()
+            ),
+            Refusal::Denied => format!(
+                "Access to path '{}' is disallowed by the access policy.",
+                path.display()
+            ),
+            Refusal::OutsideRoots => format!(
+                "Access to path '{}' is disallowed by settings.",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// The home directory, canonicalized so it compares equal to checked paths.
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| canonicalize(&home).unwrap_or(home))
+}
+
+/// Adds a path pattern to `globs`, expanding a leading `~` to the (escaped)
+/// home directory. The pattern also covers everything below what it matches.
+pub(crate) fn add_path_glob(
+    globs: &mut GlobSetBuilder,
+    pattern: &str,
+    home: Option<&Path>,
+) -> Result<(), String> {
+    let pattern = pattern.trim_end_matches(['/', '\\']);
+    let expanded = match pattern.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            let home = home.ok_or("Could not determine the home directory")?;
+            format!("{}{}", globset::escape(&home.to_string_lossy()), rest)
+        }
+        _ => pattern.to_string(),
+    };
+    for glob in [expanded.clone(), format!("{}/**", expanded)] {
+        globs.add(
+            GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .case_insensitive(cfg!(any(windows, target_os = "macos")))
+                .build()
+                .map_err(|e| e.to_string())?,
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct Root {
+    path: PathBuf,
+    read_only: bool,
+}
+
+/// Decides which paths native commands and agent tools may touch. Paths are
+/// compared after canonicalization and by whole components, so neither `..`,
+/// a symlink pointing elsewhere, nor a sibling sharing a name prefix gets out
+/// of the allowed roots or into a denied path.
+#[derive(Debug, Clone)]
+pub struct PathPolicy {
+    /// Canonical roots
+    roots: Vec<Root>,
+    deny: GlobSet,
+}
+
+impl PathPolicy {
+    pub fn unrestricted() -> Self {
+        Self::new([PathBuf::from(filesystem_root())])
+    }
+
+    /// Read-write access to `roots`, nothing denied.
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            roots: roots
+                .into_iter()
+                .map(|root| Root {
+                    path: canonicalize(&root).unwrap_or(root),
+                    read_only: false,
+                })
+                .collect(),
+            deny: GlobSet::empty(),
+        }
+    }
+
+    /// Compiles an [`AccessPolicy`], failing on globs that do not parse.
+    pub fn compile(policy: &AccessPolicy) -> Result<Self, String> {
+        let home = home_dir();
+        let mut roots = Vec::new();
+        for root in &policy.roots {
+            let path = expand_home(&root.path, home.as_deref())?;
+            if !path.is_absolute() {
+                return Err(format!("Root '{}' is not an absolute path", root.path));
+            }
+            roots.push(Root {
+                path: canonicalize(&path).unwrap_or(path),
+                read_only: root.read_only,
+            });
+        }
+
+        let mut deny = GlobSetBuilder::new();
+        let mut add = |pattern: &str| {
+            add_path_glob(&mut deny, pattern, home.as_deref())
+                .map_err(|e| format!("Invalid deny pattern '{}': {}", pattern, e))
+        };
+        for pattern in &policy.deny {
+            add(pattern)?;
+        }
+        // Otherwise the agent could rewrite its own policy or its audit trail
+        let protected = PROTECTED.lock().unwrap().clone();
+        for protected in settings::config_dir().into_iter().chain(protected) {
+            let protected = canonicalize(&protected).unwrap_or(protected);
+            add(&globset::escape(&protected.to_string_lossy()))?;
+        }
+        let deny = deny
+            .build()
+            .map_err(|e| format!("Invalid deny patterns: {}", e))?;
+        Ok(Self { roots, deny })
+    }
+
+    /// The policy in effect right now, as saved by `set_access_policy`.
+    pub fn current() -> Self {
+        PathPolicy::compile(&AccessPolicy::load()).unwrap_or_else(|e| {
+            eprintln!("Access policy is invalid, falling back to defaults: {}", e);
+            PathPolicy::compile(&AccessPolicy::from_settings()).unwrap_or_else(|_| Self::new([]))
+        })
+    }
+
+    /// The most specific root containing `path`, so a read-only folder inside
+    /// a writable one stays read-only.
+    fn root_for(&self, path: &Path) -> Option<&Root> {
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(&root.path))
+            .max_by_key(|root| root.path.components().count())
+    }
+
+    fn permits(&self, path: &Path, access: Access) -> Result<(), Refusal> {
+        if self.deny.is_match(path) {
+            return Err(Refusal::Denied);
+        }
+        match self.root_for(path) {
+            None => Err(Refusal::OutsideRoots),
+            Some(root) if root.read_only && access == Access::Write => Err(Refusal::ReadOnly),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Returns the path to operate on if `path` may be accessed. Both the entry
+    /// itself and whatever it resolves to must be allowed. The returned path
+    /// has its parent directories resolved but keeps the final component, so a
+    /// symlink is renamed, moved or trashed as a link rather than as the file
+    /// it points to.
+    pub fn check(&self, path: impl AsRef<Path>, access: Access) -> Result<PathBuf, String> {
+        let path = path.as_ref();
+        if path.as_os_str().is_empty() {
+            return Err("Path is required".to_string());
+        }
+        let invalid = |e: io::Error| format!("Invalid path '{}': {}", path.display(), e);
+        let absolute = std::path::absolute(path).map_err(invalid)?;
+
+        let target = canonicalize(&absolute).map_err(invalid)?;
+        let entry = match (absolute.parent(), absolute.file_name()) {
+            (Some(parent), Some(name)) => canonicalize(parent).map_err(invalid)?.join(name),
+            _ => target.clone(),
+        };
+        self.permits(&entry, access)
+            .and_then(|()| self.permits(&target, access))
+            .map_err(|refusal| refusal.message(path))?;
+        Ok(entry)
+    }
+
+    /// Whether an entry met while walking a directory that passed
+    /// [`check`](Self::check) may be accessed. Its parents are taken as
+    /// resolved already, and a symlink is judged as the link itself, the way a
+    /// walk that does not follow links sees it.
+    pub fn permits_entry(&self, path: &Path, access: Access) -> bool {
+        self.permits(path, access).is_ok()
+    }
+
+    /// Checks everything below `root`, which passed [`check`](Self::check)
+    /// itself, so a copy or move cannot carry a denied path along with its
+    /// parent. With a `destination`, every entry must also be writable where
+    /// it would land below it. Symlinks are not followed.
+    pub fn check_tree(
+        &self,
+        root: &Path,
+        access: Access,
+        destination: Option<&Path>,
+    ) -> Result<(), String> {
+        let mut pending = vec![(root.to_path_buf(), destination.map(Path::to_path_buf))];
+        while let Some((dir, landing)) = pending.pop() {
+            // Not a directory, or unreadable, which the operation itself reports
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                self.permits(&path, access)
+                    .map_err(|refusal| refusal.message(&path))?;
+                let landing = landing.as_ref().map(|landing| landing.join(entry.file_name()));
+                if let Some(landing) = &landing {
+                    self.permits(landing, Access::Write)
+                        .map_err(|refusal| refusal.message(landing))?;
+                }
+                if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    pending.push((path, landing));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn get_access_policy() -> Result<AccessPolicy, String> {
+    Ok(AccessPolicy::load())
+}
+
+/// Validates and saves a new policy, which applies from the next file operation.
+#[tauri::command]
+pub async fn set_access_policy(policy: AccessPolicy) -> Result<AccessPolicy, String> {
+    PathPolicy::compile(&policy)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        policy.save()?;
+        Ok(policy)
+    })
+    .await
+    .map_err(|e| format!("Failed to save access policy: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    /// A `home` root with an `outside` sibling.
+    fn sandbox() -> Sandbox {
+        let sandbox = Sandbox::new("policy");
+        sandbox.dir("home/projects");
+        sandbox.file("home/notes.txt", "mine");
+        sandbox.file("outside/secret.txt", "not mine");
+        sandbox
+    }
+
+    fn policy(sandbox: &Sandbox) -> PathPolicy {
+        PathPolicy::new([sandbox.path("home")])
+    }
+
+    fn denied(result: Result<PathBuf, String>) -> bool {
+        matches!(result, Err(e) if e.contains("disallowed"))
+    }
+
+    #[test]
+    fn allows_paths_inside_the_root() {
+        let sandbox = sandbox();
+        let policy = policy(&sandbox);
+        assert_eq!(policy.check(sandbox.path("home"), Access::Read).unwrap(), sandbox.path("home"));
+        assert_eq!(
+            policy.check(sandbox.path("home/notes.txt"), Access::Read).unwrap(),
+            sandbox.path("home/notes.txt")
+        );
+    }
+
+    #[test]
+    fn rejects_siblings_that_share_a_name_prefix() {
+        let sandbox = sandbox();
+        let sibling = sandbox.path("home2");
+        fs::create_dir_all(&sibling).unwrap();
+        assert!(denied(policy(&sandbox).check(&sibling, Access::Read)));
+        assert!(denied(policy(&sandbox).check(sibling.join("file.txt"), Access::Read)));
+        assert!(denied(policy(&sandbox).check(sandbox.path("home.bak"), Access::Read)));
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_root() {
+        let sandbox = sandbox();
+        assert!(denied(policy(&sandbox).check(&sandbox.base, Access::Read)));
+        assert!(denied(policy(&sandbox).check(sandbox.path("outside/secret.txt"), Access::Read)));
+        assert!(denied(policy(&sandbox).check("/", Access::Read)));
+    }
+
+    #[test]
+    fn rejects_parent_directory_escapes() {
+        let sandbox = sandbox();
+        let policy = policy(&sandbox);
+        assert!(denied(policy.check(sandbox.path("home/../outside/secret.txt"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/projects/../../outside"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/.."), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/projects/../../../../../.."), Access::Read)));
+    }
+
+    #[test]
+    fn allows_parent_directories_that_stay_inside() {
+        let sandbox = sandbox();
+        let policy = policy(&sandbox);
+        assert_eq!(policy.check(sandbox.path("home/projects/.."), Access::Read).unwrap(), sandbox.path("home"));
+        assert_eq!(
+            policy.check(sandbox.path("home/projects/./../notes.txt"), Access::Read).unwrap(),
+            sandbox.path("home/notes.txt")
+        );
+    }
+
+    #[test]
+    fn rejects_escapes_through_paths_that_do_not_exist_yet() {
+        let sandbox = sandbox();
+        let policy = policy(&sandbox);
+        assert!(denied(policy.check(sandbox.path("home/missing/../../outside/new.txt"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/a/b/../../../home2/x"), Access::Read)));
+    }
+
+    #[test]
+    fn allows_new_paths_inside_the_root() {
+        let sandbox = sandbox();
+        let policy = policy(&sandbox);
+        assert_eq!(
+            policy.check(sandbox.path("home/new.txt"), Access::Read).unwrap(),
+            sandbox.path("home/new.txt")
+        );
+        assert_eq!(
+            policy.check(sandbox.path("home/new/dir/file.txt"), Access::Read).unwrap(),
+            sandbox.path("home/new/dir/file.txt")
+        );
+    }
+
+    #[test]
+    fn rejects_empty_paths() {
+        assert_eq!(
+            PathPolicy::unrestricted().check("", Access::Read),
+            Err("Path is required".to_string())
+        );
+    }
+
+    #[test]
+    fn unrestricted_allows_everything() {
+        let sandbox = sandbox();
+        let policy = PathPolicy::unrestricted();
+        assert!(policy.check(sandbox.path("outside/secret.txt"), Access::Read).is_ok());
+        assert!(policy.check("/", Access::Read).is_ok());
+    }
+
+    #[test]
+    fn no_roots_allows_nothing() {
+        let sandbox = sandbox();
+        assert!(denied(PathPolicy::new([]).check(sandbox.path("home"), Access::Read)));
+    }
+
+    #[test]
+    fn filesystem_root_allows_everything() {
+        let sandbox = sandbox();
+        let policy = PathPolicy::new([PathBuf::from("/")]);
+        assert!(policy.check(sandbox.path("outside/secret.txt"), Access::Read).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_pointing_outside() {
+        let sandbox = sandbox();
+        sandbox.link(sandbox.path("outside"), sandbox.path("home/escape"));
+        sandbox.link(sandbox.path("outside/secret.txt"), sandbox.path("home/secret.txt"));
+        let policy = policy(&sandbox);
+        assert!(denied(policy.check(sandbox.path("home/escape"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/escape/secret.txt"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/escape/new.txt"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/secret.txt"), Access::Read)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_relative_symlinks_pointing_outside() {
+        let sandbox = sandbox();
+        sandbox.link("../../outside", sandbox.path("home/projects/up"));
+        sandbox.link("..", sandbox.path("home/parent"));
+        let policy = policy(&sandbox);
+        assert!(denied(policy.check(sandbox.path("home/projects/up/secret.txt"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/parent"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/parent/outside"), Access::Read)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_chains_of_symlinks_ending_outside() {
+        let sandbox = sandbox();
+        sandbox.link(sandbox.path("outside"), sandbox.path("home/third"));
+        sandbox.link("third", sandbox.path("home/second"));
+        sandbox.link(sandbox.path("home/second"), sandbox.path("home/first"));
+        assert!(denied(policy(&sandbox).check(sandbox.path("home/first/secret.txt"), Access::Read)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_dangling_symlinks_pointing_outside() {
+        let sandbox = sandbox();
+        sandbox.link(sandbox.path("outside/not-yet"), sandbox.path("home/dangling"));
+        assert!(denied(policy(&sandbox).check(sandbox.path("home/dangling"), Access::Read)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_followed_by_parent_directories() {
+        let sandbox = sandbox();
+        fs::create_dir_all(sandbox.path("outside/deep/er")).unwrap();
+        // `..` applies to where the link lands, not to the link's own directory
+        sandbox.link(sandbox.path("outside/deep/er"), sandbox.path("home/jump"));
+        assert!(denied(policy(&sandbox).check(sandbox.path("home/jump/../secret.txt"), Access::Read)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn allows_symlinks_that_stay_inside_and_keeps_the_link() {
+        let sandbox = sandbox();
+        sandbox.link(sandbox.path("home/notes.txt"), sandbox.path("home/shortcut"));
+        sandbox.link("projects", sandbox.path("home/work"));
+        let policy = policy(&sandbox);
+        assert_eq!(
+            policy.check(sandbox.path("home/shortcut"), Access::Read).unwrap(),
+            sandbox.path("home/shortcut")
+        );
+        assert_eq!(
+            policy.check(sandbox.path("home/work/todo.txt"), Access::Read).unwrap(),
+            sandbox.path("home/projects/todo.txt")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_symlink_loops_instead_of_hanging() {
+        let sandbox = sandbox();
+        sandbox.link("pong", sandbox.path("home/ping"));
+        sandbox.link("ping", sandbox.path("home/pong"));
+        let result = policy(&sandbox).check(sandbox.path("home/ping/file.txt"), Access::Read);
+        assert!(matches!(result, Err(e) if e.contains("symbolic links")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn accepts_a_root_given_through_a_symlink() {
+        let sandbox = sandbox();
+        let alias = sandbox.path("home-link");
+        sandbox.link(sandbox.path("home"), &alias);
+        let policy = PathPolicy::new([alias.clone()]);
+        assert!(policy.check(alias.join("notes.txt"), Access::Read).is_ok());
+        assert!(policy.check(sandbox.path("home/notes.txt"), Access::Read).is_ok());
+        assert!(denied(policy.check(alias.join("../outside"), Access::Read)));
+    }
+
+    fn compiled(roots: &[(&Path, bool)], deny: &[&str]) -> PathPolicy {
+        PathPolicy::compile(&AccessPolicy {
+            roots: roots
+                .iter()
+                .map(|(path, read_only)| AccessRoot {
+                    path: path.to_string_lossy().into_owned(),
+                    read_only: *read_only,
+                })
+                .collect(),
+            deny: deny.iter().map(|glob| glob.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn rejects_paths_matching_deny_patterns() {
+        let sandbox = sandbox();
+        fs::create_dir_all(sandbox.path("home/.ssh")).unwrap();
+        let ssh = sandbox.path("home/.ssh").to_string_lossy().into_owned();
+        let policy = compiled(&[(&sandbox.path("home"), false)], &[&ssh, "**/*.pem"]);
+        assert!(denied(policy.check(sandbox.path("home/.ssh"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/.ssh/id_ed25519"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/.ssh/new/key"), Access::Write)));
+        assert!(denied(policy.check(sandbox.path("home/projects/server.pem"), Access::Read)));
+        assert!(policy.check(sandbox.path("home/.sshrc"), Access::Read).is_ok());
+        assert!(policy.check(sandbox.path("home/notes.txt"), Access::Write).is_ok());
+    }
+
+    #[test]
+    fn rejects_deny_pattern_escapes_through_parent_directories() {
+        let sandbox = sandbox();
+        fs::create_dir_all(sandbox.path("home/.gnupg")).unwrap();
+        let gnupg = sandbox.path("home/.gnupg").to_string_lossy().into_owned();
+        let policy = compiled(&[(&sandbox.path("home"), false)], &[&gnupg]);
+        assert!(denied(policy.check(sandbox.path("home/projects/../.gnupg/pubring.kbx"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/missing/../.gnupg"), Access::Read)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_into_denied_paths() {
+        let sandbox = sandbox();
+        fs::create_dir_all(sandbox.path("home/.ssh")).unwrap();
+        sandbox.link(sandbox.path("home/.ssh"), sandbox.path("home/keys"));
+        let ssh = sandbox.path("home/.ssh").to_string_lossy().into_owned();
+        let policy = compiled(&[(&sandbox.path("home"), false)], &[&ssh]);
+        assert!(denied(policy.check(sandbox.path("home/keys"), Access::Read)));
+        assert!(denied(policy.check(sandbox.path("home/keys/id_rsa"), Access::Read)));
+    }
+
+    #[test]
+    fn read_only_roots_reject_writes() {
+        let sandbox = sandbox();
+        let policy = compiled(&[(&sandbox.path("home"), true)], &[]);
+        assert!(policy.check(sandbox.path("home/notes.txt"), Access::Read).is_ok());
+        let result = policy.check(sandbox.path("home/notes.txt"), Access::Write);
+        assert!(matches!(result, Err(e) if e.contains("read-only")));
+    }
+
+    #[test]
+    fn the_most_specific_root_decides_whether_writes_are_allowed() {
+        let sandbox = sandbox();
+        let projects = sandbox.path("home/projects");
+        let policy = compiled(&[(&sandbox.path("home"), false), (&projects, true)], &[]);
+        assert!(policy.check(sandbox.path("home/notes.txt"), Access::Write).is_ok());
+        assert!(policy.check(projects.join("todo.txt"), Access::Write).is_err());
+
+        let policy = compiled(&[(&sandbox.path("home"), true), (&projects, false)], &[]);
+        assert!(policy.check(projects.join("todo.txt"), Access::Write).is_ok());
+        assert!(policy.check(sandbox.path("home/notes.txt"), Access::Write).is_err());
+    }
+
+    #[test]
+    fn read_only_roots_cannot_be_written_through_parent_directories() {
+        let sandbox = sandbox();
+        let projects = sandbox.path("home/projects");
+        let policy = compiled(&[(&sandbox.path("home"), true), (&projects, false)], &[]);
+        assert!(policy.check(projects.join("../notes.txt"), Access::Write).is_err());
+    }
+
+    #[test]
+    fn expands_the_home_directory() {
+        let Some(home) = dirs::home_dir() else {
+            return;
+        };
+        let policy = PathPolicy::compile(&AccessPolicy {
+            roots: vec![AccessRoot {
+                path: "~".to_string(),
+                read_only: false,
+            }],
+            deny: DEFAULT_DENY.iter().map(|glob| glob.to_string()).collect(),
+        })
+        .unwrap();
+        assert!(policy.check(home.join("Downloads/report.pdf"), Access::Write).is_ok());
+        assert!(denied(policy.check(home.join(".ssh/id_rsa"), Access::Read)));
+        assert!(denied(policy.check(home.join(".gnupg"), Access::Read)));
+        assert!(denied(policy.check(home.join(".config/google-chrome/Default/Cookies"), Access::Read)));
+    }
+
+    #[test]
+    fn compile_rejects_invalid_policies() {
+        let invalid_glob = AccessPolicy {
+            roots: vec![],
+            deny: vec!["[unclosed".to_string()],
+        };
+        assert!(PathPolicy::compile(&invalid_glob).is_err());
+        let relative_root = AccessPolicy {
+            roots: vec![AccessRoot {
+                path: "relative/dir".to_string(),
+                read_only: false,
+            }],
+            deny: vec![],
+        };
+        assert!(PathPolicy::compile(&relative_root).is_err());
+    }
+
+    #[test]
+    fn protected_directories_are_denied_under_every_policy() {
+        let sandbox = sandbox();
+        protect(sandbox.path("home/.fsai/audit"));
+        let log = sandbox.file("home/.fsai/audit/audit.log", "");
+        let open = sandbox.policy(&[]);
+        assert!(denied(open.check(&log, Access::Read)));
+        assert!(denied(open.check(sandbox.path("home/.fsai/audit"), Access::Write)));
+        assert!(open.check(sandbox.path("home/notes.txt"), Access::Write).is_ok());
+        assert!(open.check(sandbox.path("home/.fsai"), Access::Write).is_ok());
+    }
+
+    #[test]
+    fn access_policy_uses_the_frontend_shape() {
+        let policy: AccessPolicy =
+            serde_json::from_str(r#"{"roots":[{"path":"~/Downloads"},{"path":"/srv/share","readOnly":true}],"deny":["~/.ssh"]}"#)
+                .unwrap();
+        assert_eq!(
+            policy.roots,
+            vec![
+                AccessRoot {
+                    path: "~/Downloads".to_string(),
+                    read_only: false
+                },
+                AccessRoot {
+                    path: "/srv/share".to_string(),
+                    read_only: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn canonicalize_matches_the_os_for_existing_paths() {
+        let sandbox = sandbox();
+        let path = sandbox.path("home/projects/../notes.txt");
+        assert_eq!(canonicalize(&path).unwrap(), fs::canonicalize(&path).unwrap());
+    }
+}
@@ -0,0 +1,239 @@
+use serde::Deserialize;
+
+/// Version of the `FSAI:` control protocol spoken by this build.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+const CONTROL_PREFIX: &str = "FSAI:";
+// Sidecar builds from before the control protocol only announce their port
+const LEGACY_PORT_PREFIX: &str = "BACKEND_PORT:";
+// Longer runs without a newline are flushed as a line of their own
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Reassembles lines from raw output chunks, which may split a line or carry
+/// several at once.
+#[derive(Debug, Default)]
+pub struct LineDecoder {
+    buf: Vec<u8>,
+}
+
+impl LineDecoder {
+    /// Feeds a chunk and returns every line it completes, without line terminators.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for &byte in chunk {
+            if byte == b'\n' {
+                lines.push(self.take_line());
+            } else {
+                self.buf.push(byte);
+                if self.buf.len() >= MAX_LINE_LENGTH {
+                    lines.push(self.take_line());
+                }
+            }
+        }
+        lines
+    }
+
+    /// Returns whatever is left once the stream has ended.
+    pub fn finish(&mut self) -> Option<String> {
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(self.take_line())
+        }
+    }
+
+    fn take_line(&mut self) -> String {
+        if self.buf.last() == Some(&b'\r') {
+            self.buf.pop();
+        }
+        let line = String::from_utf8_lossy(&self.buf).into_owned();
+        self.buf.clear();
+        line
+    }
+}
+
+/// Structured messages the sidecar writes to stdout as `FSAI:{"v":1,"type":...}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ControlMessage {
+    Port { port: u16 },
+    Version { version: String },
+    Capabilities { capabilities: Vec<String> },
+    Fatal { message: String },
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    v: u32,
+    #[serde(flatten)]
+    message: ControlMessage,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SidecarLine {
+    Control(ControlMessage),
+    /// A line that claims to be a control message but could not be understood
+    Invalid { line: String, reason: String },
+    Output(String),
+}
+
+pub fn parse_line(line: String) -> SidecarLine {
+    if let Some(payload) = line.strip_prefix(CONTROL_PREFIX) {
+        return match serde_json::from_str::<Envelope>(payload) {
+            Ok(envelope) if envelope.v > PROTOCOL_VERSION => SidecarLine::Invalid {
+                reason: format!(
+                    "unsupported protocol version {} (expected {})",
+                    envelope.v, PROTOCOL_VERSION
+                ),
+                line,
+            },
+            Ok(envelope) => SidecarLine::Control(envelope.message),
+            Err(e) => SidecarLine::Invalid {
+                reason: e.to_string(),
+                line,
+            },
+        };
+    }
+
+    if let Some(port) = line.strip_prefix(LEGACY_PORT_PREFIX) {
+        if let Ok(port) = port.trim().parse() {
+            return SidecarLine::Control(ControlMessage::Port { port });
+        }
+    }
+
+    SidecarLine::Output(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(chunks: &[&[u8]]) -> Vec<String> {
+        let mut decoder = LineDecoder::default();
+        let mut lines: Vec<String> = chunks.iter().flat_map(|chunk| decoder.push(chunk)).collect();
+        lines.extend(decoder.finish());
+        lines
+    }
+
+    #[test]
+    fn joins_a_line_split_across_chunks() {
+        assert_eq!(
+            decode(&[b"FSAI:{\"v\":1,\"ty", b"pe\":\"port\",", b"\"port\":4242}\n"]),
+            vec!["FSAI:{\"v\":1,\"type\":\"port\",\"port\":4242}"]
+        );
+    }
+
+    #[test]
+    fn splits_several_lines_in_one_chunk() {
+        assert_eq!(
+            decode(&[b"first\nsecond\r\nthird\n"]),
+            vec!["first", "second", "third"]
+        );
+    }
+
+    #[test]
+    fn handles_crlf_split_between_chunks() {
+        assert_eq!(decode(&[b"one\r", b"\ntwo\r\n"]), vec!["one", "two"]);
+    }
+
+    #[test]
+    fn keeps_empty_lines() {
+        assert_eq!(decode(&[b"a\n\nb\n"]), vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn flushes_unterminated_tail_on_finish() {
+        let mut decoder = LineDecoder::default();
+        assert!(decoder.push(b"no newline").is_empty());
+        assert_eq!(decoder.finish().as_deref(), Some("no newline"));
+        assert_eq!(decoder.finish(), None);
+    }
+
+    #[test]
+    fn reassembles_multibyte_characters_split_across_chunks() {
+        let text = "héllo wörld\n".as_bytes();
+        let (head, tail) = text.split_at(2);
+        assert_eq!(decode(&[head, tail]), vec!["héllo wörld"]);
+    }
+
+    #[test]
+    fn caps_runaway_lines() {
+        let long = vec![b'x'; MAX_LINE_LENGTH + 10];
+        let lines = decode(&[&long]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), MAX_LINE_LENGTH);
+        assert_eq!(lines[1].len(), 10);
+    }
+
+    #[test]
+    fn decodes_byte_at_a_time() {
+        let input = b"FSAI:{\"v\":1,\"type\":\"version\",\"version\":\"1.2.3\"}\nhello\n";
+        let chunks: Vec<&[u8]> = input.chunks(1).collect();
+        let lines: Vec<SidecarLine> = decode(&chunks).into_iter().map(parse_line).collect();
+        assert_eq!(
+            lines,
+            vec![
+                SidecarLine::Control(ControlMessage::Version {
+                    version: "1.2.3".to_string()
+                }),
+                SidecarLine::Output("hello".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_control_messages() {
+        assert_eq!(
+            parse_line(r#"FSAI:{"v":1,"type":"port","port":8080}"#.to_string()),
+            SidecarLine::Control(ControlMessage::Port { port: 8080 })
+        );
+        assert_eq!(
+            parse_line(r#"FSAI:{"v":1,"type":"capabilities","capabilities":["fs","ai"]}"#.to_string()),
+            SidecarLine::Control(ControlMessage::Capabilities {
+                capabilities: vec!["fs".to_string(), "ai".to_string()]
+            })
+        );
+        assert_eq!(
+            parse_line(r#"FSAI:{"v":1,"type":"fatal","message":"EADDRINUSE"}"#.to_string()),
+            SidecarLine::Control(ControlMessage::Fatal {
+                message: "EADDRINUSE".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn accepts_legacy_port_announcement() {
+        assert_eq!(
+            parse_line("BACKEND_PORT: 5173".to_string()),
+            SidecarLine::Control(ControlMessage::Port { port: 5173 })
+        );
+        assert_eq!(
+            parse_line("BACKEND_PORT:not-a-port".to_string()),
+            SidecarLine::Output("BACKEND_PORT:not-a-port".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_and_future_messages() {
+        assert!(matches!(
+            parse_line("FSAI:{not json".to_string()),
+            SidecarLine::Invalid { .. }
+        ));
+        assert!(matches!(
+            parse_line(r#"FSAI:{"v":1,"type":"teleport"}"#.to_string()),
+            SidecarLine::Invalid { .. }
+        ));
+        assert!(matches!(
+            parse_line(r#"FSAI:{"v":2,"type":"port","port":1}"#.to_string()),
+            SidecarLine::Invalid { .. }
+        ));
+    }
+
+    #[test]
+    fn leaves_ordinary_output_alone() {
+        assert_eq!(
+            parse_line("Server listening, FSAI: ready".to_string()),
+            SidecarLine::Output("Server listening, FSAI: ready".to_string())
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use crate::files::trash;
+use crate::files::{footprint, is_hidden};
+use crate::policy::{self, Access};
+use crate::tools::{self, ToolCall, ToolContext};
+use serde::Serialize;
+use std::fs::Metadata;
+use std::path::Path;
+
+// Enough to tell a handful of files from a whole project without walking a disk
+const MAX_COUNTED_ENTRIES: usize = 10_000;
+const LARGE_FILE: u64 = 100 * 1024 * 1024;
+const HUGE_FILE: u64 = 1024 * 1024 * 1024;
+
+const CONFIG_NAMES: [&str; 16] = [
+    ".bashrc",
+    ".bash_profile",
+    ".zshrc",
+    ".zprofile",
+    ".profile",
+    ".gitconfig",
+    ".npmrc",
+    ".vimrc",
+    ".env",
+    "config",
+    "crontab",
+    "hosts",
+    "fstab",
+    "sudoers",
+    "authorized_keys",
+    "known_hosts",
+];
+const CONFIG_EXTENSIONS: [&str; 9] = [
+    "conf", "cfg", "ini", "toml", "yaml", "yml", "plist", "reg", "env",
+];
+const CONFIG_DIRS: [&str; 4] = ["~/.config", "~/Library/Preferences", "~/AppData", "/etc"];
+const EXECUTABLE_EXTENSIONS: [&str; 12] = [
+    "exe", "msi", "bat", "cmd", "ps1", "sh", "command", "app", "dll", "so", "dylib", "appimage",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// How much could go wrong if a tool call does something unintended, with the
+/// reasons behind it for the confirmation bar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskAssessment {
+    /// 0 to 100
+    pub score: u8,
+    pub level: RiskLevel,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Default)]
+struct Scorer {
+    score: u32,
+    reasons: Vec<String>,
+}
+
+impl Scorer {
+    fn add(&mut self, points: u32, reason: String) {
+        self.score += points;
+        self.reasons.push(reason);
+    }
+
+    fn finish(self) -> RiskAssessment {
+        let score = self.score.min(100) as u8;
+        let level = match score {
+            0..25 => RiskLevel::Low,
+            25..50 => RiskLevel::Medium,
+            _ => RiskLevel::High,
+        };
+        RiskAssessment {
+            score,
+            level,
+            reasons: self.reasons,
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+fn is_config(path: &Path) -> bool {
+    let name = name_of(path).to_lowercase();
+    if CONFIG_NAMES.contains(&name.as_str())
+        || name.starts_with(".env.")
+        || extension_of(path)
+            .is_some_and(|extension| CONFIG_EXTENSIONS.contains(&extension.as_str()))
+    {
+        return true;
+    }
+    let home = policy::home_dir();
+    CONFIG_DIRS.iter().any(|dir| {
+        let dir = match (dir.strip_prefix("~/"), &home) {
+            (Some(rest), Some(home)) => home.join(rest),
+            (Some(_), None) => return false,
+            (None, _) => Path::new(dir).to_path_buf(),
+        };
+        path.starts_with(dir)
+    })
+}
+
+fn is_executable(path: &Path, metadata: Option<&Metadata>) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.is_some_and(|metadata| {
+            metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+        }) {
+            return true;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+    extension_of(path).is_some_and(|extension| EXECUTABLE_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// What the tool does on its own, before looking at the paths.
+fn base(call: &ToolCall, scorer: &mut Scorer) {
+    let (points, reason) = match call.tool_type.as_str() {
+        "read_directory" | "get_tree" => (0, "Lists a folder"),
+        "navigate_user" => (0, "Opens a folder in the file browser"),
+        "read_file" => (5, "Reads a file and shares it with the model"),
+        "process_file" => (5, "Uploads a file to the model"),
+        "create_directory" => (10, "Creates a folder"),
+        "copy_file" => (15, "Copies files"),
+        "write_file" => (20, "Writes a file"),
+        "rename_file" => (25, "Renames an item"),
+        "move_item" => (30, "Moves an item"),
+        "delete_item" if trash::SUPPORTED => (35, "Moves an item to the trash"),
+        "delete_item" => (50, "Deletes an item permanently"),
+        _ => (50, "Unknown tool"),
+    };
+    scorer.add(points, reason.to_string());
+}
+
+/// Scores a proposed tool call from its type and what its parameters point
+/// at: whether targets exist, how much a directory operation touches and
+/// whether the paths are hidden, configuration, executable or large.
+pub fn assess(call: &ToolCall, context: &ToolContext) -> RiskAssessment {
+    let mut scorer = Scorer::default();
+    base(call, &mut scorer);
+    let tool = call.tool_type.as_str();
+
+    for (index, (path, access)) in tools::targets(call, context).into_iter().enumerate() {
+        let path = policy::canonicalize_entry(Path::new(&path));
+        let name = name_of(&path);
+        let metadata = path.symlink_metadata().ok();
+        let writes = access == Access::Write;
+        // The item acted on, as opposed to a destination or new name
+        let subject = index == 0;
+
+        match (&metadata, subject) {
+            (None, true) if matches!(tool, "delete_item" | "move_item" | "rename_file") => {
+                scorer.add(0, format!("{} does not exist", name));
+            }
+            (Some(metadata), true) if tool == "write_file" && metadata.is_file() => {
+                scorer.add(
+                    25,
+                    format!("Overwrites {} ({})", name, format_size(metadata.len())),
+                );
+                if call.parameters.expected_hash.is_none() {
+                    scorer.add(
+                        10,
+                        "Changes made since the file was read would be lost".to_string(),
+                    );
+                }
+            }
+            (Some(existing), false)
+                if !existing.is_dir()
+                    && matches!(tool, "move_item" | "rename_file" | "copy_file") =>
+            {
+                scorer.add(15, format!("{} already exists", name));
+            }
+            _ => {}
+        }
+
+        let metadata = metadata.as_ref();
+        if metadata.is_some_and(|metadata| metadata.is_dir())
+            && subject
+            && matches!(
+                tool,
+                "delete_item" | "move_item" | "rename_file" | "copy_file"
+            )
+        {
+            if let Ok(footprint) = footprint(&path, MAX_COUNTED_ENTRIES) {
+                let files = if footprint.complete {
+                    format!("{} file(s)", footprint.files)
+                } else {
+                    format!("more than {} file(s)", footprint.files)
+                };
+                let points = match footprint.files {
+                    _ if !footprint.complete => 30,
+                    0 => 0,
+                    1..100 => 10,
+                    100..1000 => 20,
+                    _ => 30,
+                };
+                scorer.add(
+                    points,
+                    format!(
+                        "Affects {} ({}) in {}",
+                        files,
+                        format_size(footprint.bytes),
+                        name
+                    ),
+                );
+            }
+        } else if let Some(metadata) = metadata.filter(|metadata| subject && metadata.is_file()) {
+            let size = metadata.len();
+            if size >= HUGE_FILE {
+                scorer.add(
+                    20,
+                    format!("{} is very large ({})", name, format_size(size)),
+                );
+            } else if size >= LARGE_FILE {
+                scorer.add(10, format!("{} is large ({})", name, format_size(size)));
+            }
+        }
+
+        let hidden = match metadata {
+            Some(metadata) => is_hidden(&path, metadata),
+            None => name.starts_with('.'),
+        };
+        if hidden {
+            scorer.add(if writes { 15 } else { 5 }, format!("{} is hidden", name));
+        }
+        if is_config(&path) {
+            scorer.add(
+                if writes { 20 } else { 10 },
+                format!("{} is a configuration file", name),
+            );
+        }
+        if writes && is_executable(&path, metadata) {
+            scorer.add(20, format!("{} is executable", name));
+        }
+    }
+
+    if tool == "write_file" {
+        if let Some(content) = &call.parameters.content {
+            let size = content.len() as u64;
+            if size >= LARGE_FILE {
+                scorer.add(10, format!("Writes {}", format_size(size)));
+            }
+        }
+    }
+    scorer.finish()
+}
+
+/// Scores each proposed call; the sidecar leaves risk to the Rust side since
+/// only it can look at the files involved.
+#[tauri::command]
+pub async fn assess_tool_calls(
+    tool_calls: Vec<ToolCall>,
+    context: Option<ToolContext>,
+) -> Result<Vec<RiskAssessment>, String> {
+    let context = context.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        tool_calls
+            .iter()
+            .map(|call| assess(call, &context))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to assess tool calls: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{call, Sandbox};
+    use crate::tools::ToolParameters;
+
+    /// A `project` with many sources, and a couple of loose files.
+    fn sandbox() -> Sandbox {
+        let sandbox = Sandbox::new("risk");
+        for i in 0..120 {
+            sandbox.file(format!("project/src/{}.rs", i), "fn main() {}");
+        }
+        sandbox.file("notes.txt", "notes");
+        sandbox.file(".bashrc", "export PATH");
+        sandbox
+    }
+
+    fn writing(path: &str) -> ToolCall {
+        call(
+            "write_file",
+            ToolParameters {
+                path: Some(path.to_string()),
+                content: Some("hello".to_string()),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn new_scratch_files_are_low_risk() {
+        let sandbox = sandbox();
+        let risk = assess(&writing("scratch.txt"), &sandbox.context());
+        assert_eq!(risk.level, RiskLevel::Low);
+        assert_eq!(risk.reasons, vec!["Writes a file"]);
+    }
+
+    #[test]
+    fn overwriting_a_shell_config_is_high_risk() {
+        let sandbox = sandbox();
+        let context = sandbox.context();
+        let risk = assess(&writing(".bashrc"), &context);
+        assert_eq!(risk.level, RiskLevel::High);
+        assert!(risk
+            .reasons
+            .iter()
+            .any(|reason| reason.starts_with("Overwrites .bashrc")));
+        assert!(risk
+            .reasons
+            .iter()
+            .any(|reason| reason == ".bashrc is hidden"));
+        assert!(risk
+            .reasons
+            .iter()
+            .any(|reason| reason == ".bashrc is a configuration file"));
+        assert!(risk.score > assess(&writing("notes.txt"), &context).score);
+    }
+
+    #[test]
+    fn directory_operations_count_the_files_they_touch() {
+        let sandbox = sandbox();
+        let delete = |path: &str| {
+            call(
+                "delete_item",
+                ToolParameters {
+                    path: Some(path.to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+        let context = sandbox.context();
+        let project = assess(&delete("project"), &context);
+        assert!(project
+            .reasons
+            .iter()
+            .any(|reason| reason.starts_with("Affects 120 file(s)")));
+        assert!(project.score > assess(&delete("notes.txt"), &context).score);
+    }
+
+    #[test]
+    fn reads_stay_low_risk() {
+        let sandbox = sandbox();
+        let read = call(
+            "read_file",
+            ToolParameters {
+                path: Some("notes.txt".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(assess(&read, &sandbox.context()).level, RiskLevel::Low);
+    }
+
+    #[test]
+    fn moving_onto_an_existing_file_is_flagged() {
+        let sandbox = sandbox();
+        let moving = call(
+            "move_item",
+            ToolParameters {
+                source_path: Some("notes.txt".to_string()),
+                destination_path: Some(".bashrc".to_string()),
+                ..Default::default()
+            },
+        );
+        let risk = assess(&moving, &sandbox.context());
+        assert!(risk
+            .reasons
+            .iter()
+            .any(|reason| reason == ".bashrc already exists"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn writing_to_executables_is_flagged() {
+        use std::os::unix::fs::PermissionsExt;
+        let sandbox = sandbox();
+        let script = sandbox.file("run", "#!/bin/sh");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let risk = assess(&writing("run"), &sandbox.context());
+        assert!(risk
+            .reasons
+            .iter()
+            .any(|reason| reason == "run is executable"));
+    }
+
+    #[test]
+    fn formats_sizes_for_people() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
@@ -0,0 +1,36 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+const APP_NAME: &str = "FSai";
+
+/// The subset of the sidecar's `settings.json` that the Rust side needs. The
+/// sidecar owns the file; we only ever read it.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub allow_root_access: bool,
+}
+
+/// Where the app keeps its configuration; the same location `getAppDataPath()`
+/// resolves to in `server.ts`.
+pub fn config_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join(APP_NAME))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("settings.json"))
+}
+
+/// Reads the current settings, falling back to defaults if the file is missing or malformed.
+pub fn load() -> Settings {
+    let Some(path) = settings_path() else {
+        return Settings::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Could not parse {}: {}", path.display(), e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
@@ -0,0 +1,363 @@
+use crate::backend;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const LOCK_FILE: &str = "instance.lock";
+const PORT_FILE: &str = "instance.port";
+const TOKEN_FILE: &str = "instance.token";
+// The first instance may still be writing its port file when we start
+const CONNECT_ATTEMPTS: u32 = 10;
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+// A second instance sends its launch right after connecting
+const HANDOVER_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_HANDOVER_BYTES: u64 = 64 * 1024;
+
+/// Launch details a second instance hands over to the running one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceLaunch {
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl InstanceLaunch {
+    fn current() -> Self {
+        Self {
+            args: std::env::args().skip(1).collect(),
+            cwd: std::env::current_dir().ok(),
+        }
+    }
+}
+
+/// What goes over the socket. Any local process can connect to it; only one
+/// that can read the primary's token file gets its launch accepted.
+#[derive(Debug, Serialize, Deserialize)]
+struct Handover {
+    token: String,
+    launch: InstanceLaunch,
+}
+
+/// Held by the primary instance for as long as it runs. The OS releases the lock
+/// when the process exits, however it exits.
+pub struct InstanceLock {
+    _file: File,
+}
+
+pub enum Instance {
+    Primary(InstanceLock),
+    /// Another instance is running and has been handed our launch arguments
+    Secondary,
+}
+
+/// Claims the single-instance lock in the app data dir. If another FSai is already
+/// running, forwards this launch's arguments to it instead. The primary instance
+/// focuses its window and emits `second-instance` for each forwarded launch.
+pub fn acquire(app_handle: &AppHandle) -> Result<Instance, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    match try_lock(&dir.join(LOCK_FILE))? {
+        Some(file) => {
+            // The token goes first, so whoever finds the new port can also find it
+            let token = write_token(&dir.join(TOKEN_FILE))?;
+            listen(app_handle.clone(), &dir.join(PORT_FILE), token)?;
+            Ok(Instance::Primary(InstanceLock { _file: file }))
+        }
+        None => {
+            forward(
+                &dir.join(PORT_FILE),
+                &dir.join(TOKEN_FILE),
+                &InstanceLaunch::current(),
+            )?;
+            Ok(Instance::Secondary)
+        }
+    }
+}
+
+/// Returns the locked file, or `None` if another process holds the lock.
+#[cfg(unix)]
+fn try_lock(path: &Path) -> Result<Option<File>, String> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+    // SAFETY: the fd stays valid for the lifetime of `file`
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(Some(file));
+    }
+    let error = std::io::Error::last_os_error();
+    if error.kind() == std::io::ErrorKind::WouldBlock {
+        Ok(None)
+    } else {
+        Err(format!("Failed to lock {}: {}", path.display(), error))
+    }
+}
+
+/// Returns the locked file, or `None` if another process holds the lock.
+#[cfg(windows)]
+fn try_lock(path: &Path) -> Result<Option<File>, String> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // A share mode of 0 makes the open fail while any other handle is open
+    match OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .share_mode(0)
+        .open(path)
+    {
+        Ok(file) => Ok(Some(file)),
+        // ERROR_SHARING_VIOLATION
+        Err(e) if e.raw_os_error() == Some(32) => Ok(None),
+        Err(e) => Err(format!("Failed to lock {}: {}", path.display(), e)),
+    }
+}
+
+/// Replaces the token file with a fresh random token that only the current user
+/// can read, and returns the token.
+fn write_token(path: &Path) -> Result<String, String> {
+    let token = backend::generate_token();
+
+    let failed = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(failed(e)),
+        _ => {}
+    }
+    let mut options = OpenOptions::new();
+    // A fresh file, so neither a planted symlink nor loose permissions carry over
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(token.as_bytes()))
+        .map_err(failed)?;
+    Ok(token)
+}
+
+/// Compares in constant time, so the token cannot be guessed byte by byte.
+fn same_token(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len()
+        && expected
+            .bytes()
+            .zip(actual.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn listen(app_handle: AppHandle, port_file: &Path, token: String) -> Result<(), String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(|e| format!("Failed to open instance socket: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to open instance socket: {}", e))?
+        .port();
+    fs::write(port_file, port.to_string())
+        .map_err(|e| format!("Failed to write {}: {}", port_file.display(), e))?;
+
+    serve(listener, token, move |launch| on_second_instance(&app_handle, launch));
+    Ok(())
+}
+
+/// Accepts handovers on a background thread. Each connection is read on its
+/// own thread, so one that never sends cannot hold up the launches behind it.
+fn serve(
+    listener: TcpListener,
+    token: String,
+    on_launch: impl Fn(InstanceLaunch) + Send + Sync + 'static,
+) {
+    let token = Arc::new(token);
+    let on_launch = Arc::new(on_launch);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Ignoring second instance launch: {}", e);
+                    continue;
+                }
+            };
+            let (token, on_launch) = (token.clone(), on_launch.clone());
+            std::thread::spawn(move || match read_launch(&stream, &token) {
+                Ok(launch) => on_launch(launch),
+                Err(e) => eprintln!("Ignoring second instance launch: {}", e),
+            });
+        }
+    });
+}
+
+fn read_launch(stream: &TcpStream, token: &str) -> Result<InstanceLaunch, String> {
+    stream
+        .set_read_timeout(Some(HANDOVER_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    BufReader::new(stream.take(MAX_HANDOVER_BYTES))
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+    let handover: Handover = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+    if !same_token(token, &handover.token) {
+        return Err("wrong instance token".to_string());
+    }
+    Ok(handover.launch)
+}
+
+fn on_second_instance(app_handle: &AppHandle, launch: InstanceLaunch) {
+    println!("Second instance launched with {:?}", launch.args);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app_handle.emit("second-instance", launch);
+}
+
+fn forward(port_file: &Path, token_file: &Path, launch: &InstanceLaunch) -> Result<(), String> {
+    let mut last_error = String::new();
+    for _ in 0..CONNECT_ATTEMPTS {
+        match connect(port_file) {
+            Ok(mut stream) => {
+                let token = fs::read_to_string(token_file)
+                    .map_err(|e| format!("Failed to read {}: {}", token_file.display(), e))?;
+                let handover = Handover {
+                    token: token.trim().to_string(),
+                    launch: launch.clone(),
+                };
+                let message = serde_json::to_string(&handover).map_err(|e| e.to_string())?;
+                return writeln!(stream, "{}", message)
+                    .map_err(|e| format!("Failed to reach running instance: {}", e));
+            }
+            Err(e) => last_error = e,
+        }
+        std::thread::sleep(CONNECT_RETRY_INTERVAL);
+    }
+    Err(format!("Failed to reach running instance: {}", last_error))
+}
+
+fn connect(port_file: &Path) -> Result<TcpStream, String> {
+    let port: u16 = fs::read_to_string(port_file)
+        .map_err(|e| e.to_string())?
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} does not contain a port", port_file.display()))?;
+    TcpStream::connect((Ipv4Addr::LOCALHOST, port)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    /// A listener with its port file and token, as the primary instance sets them up.
+    fn primary(sandbox: &Sandbox) -> (TcpListener, String) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        sandbox.file(PORT_FILE, port.to_string());
+        (listener, write_token(&sandbox.path(TOKEN_FILE)).unwrap())
+    }
+
+    fn launch(args: &[&str]) -> InstanceLaunch {
+        InstanceLaunch {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            cwd: None,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn token_file_is_private_and_fresh_per_launch() {
+        use std::os::unix::fs::PermissionsExt;
+        let sandbox = Sandbox::new("instance");
+        let path = sandbox.file(TOKEN_FILE, "stale");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let first = write_token(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert_ne!(write_token(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn accepts_launches_carrying_the_token() {
+        let sandbox = Sandbox::new("instance");
+        let (listener, token) = primary(&sandbox);
+        forward(
+            &sandbox.path(PORT_FILE),
+            &sandbox.path(TOKEN_FILE),
+            &launch(&["--open", "/tmp"]),
+        )
+        .unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        assert_eq!(
+            read_launch(&stream, &token).unwrap().args,
+            ["--open", "/tmp"]
+        );
+    }
+
+    #[test]
+    fn rejects_launches_without_the_token() {
+        let sandbox = Sandbox::new("instance");
+        let (listener, token) = primary(&sandbox);
+        let port = listener.local_addr().unwrap().port();
+
+        let mut forged = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let handover = Handover {
+            token: "0".repeat(token.len()),
+            launch: launch(&["--open", "/etc"]),
+        };
+        writeln!(forged, "{}", serde_json::to_string(&handover).unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        assert!(read_launch(&stream, &token).is_err());
+
+        // The bare launch second instances used to send
+        let mut legacy = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        writeln!(
+            legacy,
+            "{}",
+            serde_json::to_string(&handover.launch).unwrap()
+        )
+        .unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        assert!(read_launch(&stream, &token).is_err());
+    }
+
+    #[test]
+    fn a_silent_connection_does_not_hold_up_other_launches() {
+        let sandbox = Sandbox::new("instance");
+        let (listener, token) = primary(&sandbox);
+        let port = listener.local_addr().unwrap().port();
+        let (sender, launches) = std::sync::mpsc::channel();
+        serve(listener, token, move |launch| {
+            let _ = sender.send(launch);
+        });
+
+        // Connects and never sends
+        let _silent = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        forward(
+            &sandbox.path(PORT_FILE),
+            &sandbox.path(TOKEN_FILE),
+            &launch(&["--open", "/tmp"]),
+        )
+        .unwrap();
+        let received = launches
+            .recv_timeout(HANDOVER_TIMEOUT / 2)
+            .expect("the launch waited on the silent connection");
+        assert_eq!(received.args, ["--open", "/tmp"]);
+    }
+}
@@ -0,0 +1,93 @@
+//! Fixtures shared by the unit tests.
+
+use crate::policy::{AccessPolicy, AccessRoot, PathPolicy};
+use crate::tools::{ToolCall, ToolContext, ToolParameters};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A scratch directory that is removed again when dropped. Its path is
+/// canonical, so expectations compare like for like with checked paths.
+pub(crate) struct Sandbox {
+    pub base: PathBuf,
+}
+
+impl Sandbox {
+    /// An empty directory; `name` keeps sandboxes of different modules apart.
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let base = std::env::temp_dir().join(format!(
+            "fsai-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(&base).unwrap();
+        Self {
+            base: fs::canonicalize(&base).unwrap(),
+        }
+    }
+
+    pub fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.base.join(relative)
+    }
+
+    /// Creates a directory, with its parents.
+    pub fn dir(&self, relative: impl AsRef<Path>) -> PathBuf {
+        let path = self.path(relative);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    /// Creates a file, with its parent directories.
+    pub fn file(&self, relative: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    pub fn link(&self, target: impl AsRef<Path>, link: impl AsRef<Path>) {
+        std::os::unix::fs::symlink(target, link).unwrap();
+    }
+
+    /// Read-write access to the sandbox, minus the `deny` patterns, which are
+    /// relative to it.
+    pub fn policy(&self, deny: &[&str]) -> PathPolicy {
+        PathPolicy::compile(&AccessPolicy {
+            roots: vec![AccessRoot {
+                path: self.base.to_string_lossy().into_owned(),
+                read_only: false,
+            }],
+            deny: deny
+                .iter()
+                .map(|pattern| self.path(pattern).to_string_lossy().into_owned())
+                .collect(),
+        })
+        .unwrap()
+    }
+
+    /// A tool context whose current directory is the sandbox.
+    pub fn context(&self) -> ToolContext {
+        ToolContext {
+            current_path: Some(self.base.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.base);
+    }
+}
+
+pub(crate) fn call(tool: &str, parameters: ToolParameters) -> ToolCall {
+    ToolCall {
+        id: "tc_test".to_string(),
+        tool_type: tool.to_string(),
+        parameters,
+        description: String::new(),
+    }
+}
@@ -0,0 +1,708 @@
+use crate::audit::{self, AuditAction, AuditEntry, AuditLog};
+use crate::files::copy::ConflictPolicy;
+use crate::files::detect;
+use crate::files::list::{self, EntryKind, ListOptions};
+use crate::files::read::{self, ReadOptions};
+use crate::files::transfer;
+use crate::files::trash;
+use crate::files::tree::{self, TreeOptions};
+use crate::files::write::{self, WriteOptions};
+use crate::files::check_access;
+use crate::jobs::{self, JobOutput, JobSpec};
+use crate::permissions::{Decision, Permission, Permissions};
+use crate::AppState;
+use crate::policy::{Access, PathPolicy};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+// The agent's context window is precious; page through anything bigger
+const AGENT_READ_MAX_BYTES: u64 = 256 * 1024;
+
+/// A tool call proposed by the model, as the frontend received it from the sidecar.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    #[serde(default)]
+    pub parameters: ToolParameters,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolParameters {
+    pub path: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub name: Option<String>,
+    pub content: Option<String>,
+    pub source_path: Option<String>,
+    pub destination_path: Option<String>,
+    pub new_name: Option<String>,
+    pub start_line: Option<u64>,
+    pub line_count: Option<u64>,
+    pub expected_hash: Option<String>,
+    pub create_only: Option<bool>,
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolContext {
+    pub current_path: Option<String>,
+}
+
+/// What a tool returns to the model, in the shape the follow-up prompt expects.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolOutcome {
+    pub tool_call_id: String,
+    pub result: Value,
+    pub message: String,
+}
+
+/// Resolves a tool path parameter, relative paths being relative to the folder
+/// the user is looking at.
+fn resolve(path: Option<&str>, context: &ToolContext, name: &str) -> Result<String, String> {
+    let path = path.ok_or_else(|| format!("{} parameter is required", name))?;
+    match &context.current_path {
+        Some(current) if !Path::new(path).is_absolute() => {
+            Ok(Path::new(current).join(path).to_string_lossy().into_owned())
+        }
+        _ => Ok(path.to_string()),
+    }
+}
+
+/// The paths a tool call touches and how, the item it acts on first. Missing
+/// parameters are left out; the tool itself reports them when it runs.
+pub(crate) fn targets(call: &ToolCall, context: &ToolContext) -> Vec<(String, Access)> {
+    let params = &call.parameters;
+    let at = |path: &Option<String>| resolve(path.as_deref(), context, "Path").ok();
+    let mut targets = Vec::new();
+    let mut push = |path: Option<String>, access: Access| {
+        if let Some(path) = path {
+            targets.push((path, access));
+        }
+    };
+    match call.tool_type.as_str() {
+        "read_file" | "read_directory" | "get_tree" | "process_file" | "navigate_user" => {
+            push(at(&params.path), Access::Read)
+        }
+        "write_file" | "delete_item" => push(at(&params.path), Access::Write),
+        "create_directory" => push(
+            at(&params.path).zip(params.name.as_ref()).map(|(parent, name)| {
+                Path::new(&parent).join(name).to_string_lossy().into_owned()
+            }),
+            Access::Write,
+        ),
+        "rename_file" => {
+            let path = at(&params.path);
+            let renamed = path.as_ref().zip(params.new_name.as_ref()).map(|(path, name)| {
+                Path::new(path).with_file_name(name).to_string_lossy().into_owned()
+            });
+            push(path, Access::Write);
+            push(renamed, Access::Write);
+        }
+        "copy_file" => {
+            push(at(&params.path), Access::Read);
+            push(at(&params.destination_path), Access::Write);
+        }
+        "move_item" => {
+            push(at(&params.source_path), Access::Write);
+            push(at(&params.destination_path), Access::Write);
+        }
+        // Whatever an unknown tool does, assume it changes what it names
+        _ => {
+            let named = [&params.path, &params.source_path, &params.destination_path, &params.from, &params.to];
+            for path in named {
+                push(at(path), Access::Write);
+            }
+        }
+    }
+    targets
+}
+
+fn read_file(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "Path")?,
+        Access::Read,
+    )?;
+    let content = read::read(
+        &path,
+        &ReadOptions {
+            start_line: call.parameters.start_line,
+            line_count: call.parameters.line_count,
+            max_bytes: Some(AGENT_READ_MAX_BYTES),
+            ..Default::default()
+        },
+    )?;
+    if content.is_binary {
+        return Err(format!(
+            "{} is a binary file; use process_file to analyze it",
+            path.display()
+        ));
+    }
+
+    // Lets a follow-up write_file refuse to clobber edits made after this read
+    let hash = write::hash_file(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let message = if content.truncated {
+        format!(
+            "File read partially ({} of {} bytes); use startLine/lineCount to read the rest",
+            content.end - content.start,
+            content.size
+        )
+    } else {
+        "File read successfully".to_string()
+    };
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "content": content.content,
+            "path": content.path,
+            "encoding": content.encoding,
+            "size": content.size,
+            "startLine": content.start_line,
+            "endLine": content.end_line,
+            "truncated": content.truncated,
+            "hash": hash,
+        }),
+        message,
+    })
+}
+
+// Sends images, PDFs and videos to the model; the type comes from the content,
+// since a renamed or oddly labelled file would otherwise be rejected or mislabelled
+fn process_file(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "Path")?,
+        Access::Read,
+    )?;
+    let file_type = detect::detect(&path)?;
+    file_type.check_uploadable()?;
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "path": file_type.path,
+            "mimeType": file_type.mime_type,
+            "data": base64::engine::general_purpose::STANDARD.encode(bytes),
+            "isFile": true,
+        }),
+        message: "File processed successfully".to_string(),
+    })
+}
+
+fn write_file(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "Path")?,
+        Access::Write,
+    )?;
+    let content = call
+        .parameters
+        .content
+        .as_deref()
+        .ok_or("Content must be a string")?;
+    let written = write::write(
+        &path,
+        content.as_bytes(),
+        &WriteOptions {
+            create_only: call.parameters.create_only.unwrap_or(false),
+            expected_hash: call.parameters.expected_hash.clone(),
+            ..Default::default()
+        },
+        &PathPolicy::current(),
+    )?;
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        message: if written.created {
+            "File created successfully".to_string()
+        } else {
+            "File written successfully".to_string()
+        },
+        result: json!({
+            "message": format!("File written successfully to {}", written.path.display()),
+            "path": written.path,
+            "bytesWritten": written.bytes_written,
+            "hash": written.hash,
+            "created": written.created,
+        }),
+    })
+}
+
+// Denied entries are left out, as they are from trees
+fn read_directory(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = resolve(call.parameters.path.as_deref(), context, "Path")?;
+    let listing = list::list(&path, &ListOptions::default(), &PathPolicy::current())?;
+    let files: Vec<Value> = listing
+        .entries
+        .iter()
+        .map(|entry| {
+            json!({
+                "name": entry.name,
+                "isDirectory": entry.kind == EntryKind::Directory,
+                "isFile": entry.kind == EntryKind::File,
+                "path": entry.path,
+            })
+        })
+        .collect();
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({ "files": files, "path": listing.path }),
+        message: "Directory listed successfully".to_string(),
+    })
+}
+
+fn create_directory(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let parent = resolve(call.parameters.path.as_deref(), context, "path")?;
+    let name = call.parameters.name.as_deref().ok_or("name parameter is required")?;
+    let path = check_access(Path::new(&parent).join(name), Access::Write)?;
+    write::create_dir(&path)?;
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "message": format!("Directory '{}' created at '{}'", name, parent),
+            "path": path,
+        }),
+        message: "Directory created successfully".to_string(),
+    })
+}
+
+fn rename_file(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "path")?,
+        Access::Write,
+    )?;
+    let new_name = call
+        .parameters
+        .new_name
+        .as_deref()
+        .ok_or("newName parameter is required")?;
+    let renamed = transfer::rename(&path, new_name, &PathPolicy::current())?;
+    let old_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "message": format!("Renamed '{}' to '{}'", old_name, new_name),
+            "path": renamed,
+        }),
+        message: "Item renamed successfully".to_string(),
+    })
+}
+
+// Only checks the folder; the frontend does the navigating
+fn navigate_user(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "Path")?,
+        Access::Read,
+    )?;
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.display()));
+    }
+    if !path.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "navigationPath": path,
+            "message": format!("Navigate to: {}", path.display()),
+        }),
+        message: "Navigation path validated successfully".to_string(),
+    })
+}
+
+fn get_tree(call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "Path")?,
+        Access::Read,
+    )?;
+    let mut options = TreeOptions::default();
+    if let Some(max_depth) = call.parameters.max_depth {
+        options.max_depth = max_depth.max(1);
+    }
+    let tree = tree::build(&path, &options, &PathPolicy::current())?;
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "tree": tree.ascii,
+            "path": tree.path,
+            "entries": tree.entries,
+            "truncated": tree.truncated,
+        }),
+        message: "Directory tree generated successfully".to_string(),
+    })
+}
+
+fn copy_file(app: &AppHandle, call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let source = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "path")?,
+        Access::Read,
+    )?;
+    let destination = transfer::check_destination(
+        &source,
+        resolve(call.parameters.destination_path.as_deref(), context, "destinationPath")?,
+    )?;
+    // Nobody is around to answer a conflict; never clobber, copy alongside instead
+    let JobOutput::Copy(copied) = jobs::run(
+        app,
+        JobSpec::Copy {
+            source,
+            destination,
+            conflict: ConflictPolicy::Rename,
+        },
+    )?
+    else {
+        unreachable!("copy jobs produce copy results");
+    };
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "message": format!("File copied from '{}' to '{}'", copied.source.display(), copied.destination.display()),
+            "filesCopied": copied.files_copied,
+            "bytesCopied": copied.bytes_copied,
+        }),
+        message: "File copied successfully".to_string(),
+    })
+}
+
+// Deletions go to the trash so they can be undone, where there is one
+fn delete_item(app: &AppHandle, call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let path = check_access(
+        &resolve(call.parameters.path.as_deref(), context, "path")?,
+        Access::Write,
+    )?;
+    if !path.exists() && path.symlink_metadata().is_err() {
+        return Err(format!("File or directory does not exist: {}", path.display()));
+    }
+    if !trash::SUPPORTED {
+        let kind = if path.is_dir() { "Directory" } else { "File" };
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        trash::delete(&path, &PathPolicy::current())?;
+        return Ok(ToolOutcome {
+            tool_call_id: call.id.clone(),
+            result: json!({
+                "message": format!("{} '{}' deleted successfully", kind, name),
+            }),
+            message: format!("{} deleted successfully", kind),
+        });
+    }
+    let JobOutput::Delete(item) = jobs::run(app, JobSpec::Delete { path })? else {
+        unreachable!("delete jobs produce trash items");
+    };
+    let kind = if item.is_directory { "Directory" } else { "File" };
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "message": format!("{} '{}' moved to the trash", kind, item.name),
+            "trashId": item.id,
+            "originalPath": item.original_path,
+        }),
+        message: format!("{} moved to the trash", kind),
+    })
+}
+
+fn move_item(app: &AppHandle, call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    let source = check_access(
+        &resolve(call.parameters.source_path.as_deref(), context, "sourcePath")?,
+        Access::Write,
+    )?;
+    let destination = transfer::check_destination(
+        &source,
+        resolve(call.parameters.destination_path.as_deref(), context, "destinationPath")?,
+    )?;
+    let JobOutput::Move(moved) = jobs::run(app, JobSpec::Move { source, destination })? else {
+        unreachable!("move jobs produce move results");
+    };
+
+    Ok(ToolOutcome {
+        tool_call_id: call.id.clone(),
+        result: json!({
+            "message": format!("Moved '{}' to '{}'", moved.source.display(), moved.destination.display()),
+            "method": moved.method,
+        }),
+        message: "Item moved successfully".to_string(),
+    })
+}
+
+fn dispatch(app: &AppHandle, call: &ToolCall, context: &ToolContext) -> Result<ToolOutcome, String> {
+    match call.tool_type.as_str() {
+        "read_file" => read_file(call, context),
+        "write_file" => write_file(call, context),
+        "read_directory" => read_directory(call, context),
+        "get_tree" => get_tree(call, context),
+        "process_file" => process_file(call, context),
+        "create_directory" => create_directory(call, context),
+        "rename_file" => rename_file(call, context),
+        "navigate_user" => navigate_user(call, context),
+        "copy_file" => copy_file(app, call, context),
+        "move_item" => move_item(app, call, context),
+        "delete_item" => delete_item(app, call, context),
+        other => Err(format!("Unknown tool type: {}", other)),
+    }
+}
+
+/// Lets a call through to execution. One the profiles ask about needs the user's
+/// approval in the audit log, which is used up here, before the call runs, so
+/// two executions racing on one approval cannot both pass.
+fn authorize(
+    permission: &Permission,
+    audit: &AuditLog,
+    call: &ToolCall,
+    context: &ToolContext,
+) -> Result<(), String> {
+    match permission.decision {
+        Decision::Allow => Ok(()),
+        Decision::Deny => Err(format!("Tool call denied: {}", permission.reason)),
+        Decision::Ask => {
+            let entry = AuditEntry::new(AuditAction::Approved, call, context);
+            if audit.take_approval(&entry) {
+                Ok(())
+            } else {
+                Err("Tool call has not been approved".to_string())
+            }
+        }
+    }
+}
+
+/// Runs a confirmed tool call. The call is first checked against the permission
+/// profiles, so a denied call never reaches execution, and one they ask about
+/// only runs once, after the user's approval is in the audit log. Every path
+/// goes through the access policy. Outcomes, refusals included, go to the audit log.
+/// Copies, moves and deletions go through the job queue, so they show up in
+/// `list_jobs` and can be paused or cancelled while they run.
+#[tauri::command]
+pub async fn execute_tool(
+    app: AppHandle,
+    tool_call: ToolCall,
+    context: Option<ToolContext>,
+) -> Result<ToolOutcome, String> {
+    let context = context.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let permission = Permissions::current().evaluate(&tool_call, &context);
+        let audit = &app.state::<AppState>().audit;
+        let outcome = authorize(&permission, audit, &tool_call, &context)
+            .and_then(|_| dispatch(&app, &tool_call, &context));
+        let recorded = match &outcome {
+            Ok(done) => Ok(done.result.clone()),
+            Err(e) => Err(e.clone()),
+        };
+        audit::record(
+            &app,
+            AuditEntry::new(AuditAction::Executed, &tool_call, &context).outcome(recorded),
+        );
+        outcome
+    })
+    .await
+    .map_err(|e| format!("Failed to execute tool: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{call, Sandbox};
+
+    fn at(sandbox: &Sandbox, relative: &str) -> String {
+        sandbox.path(relative).to_string_lossy().into_owned()
+    }
+
+    fn path(path: &str) -> ToolParameters {
+        ToolParameters {
+            path: Some(path.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn decided(decision: Decision) -> Permission {
+        Permission {
+            decision,
+            reason: "test profile".to_string(),
+        }
+    }
+
+    fn audit(sandbox: &Sandbox) -> AuditLog {
+        let audit = AuditLog::default();
+        audit.persist_to(sandbox.path("audit"));
+        audit
+    }
+
+    #[test]
+    fn resolves_targets_against_the_current_folder() {
+        let sandbox = Sandbox::new("tools");
+        let relative = targets(&call("read_file", path("notes.txt")), &sandbox.context());
+        assert_eq!(relative, vec![(at(&sandbox, "notes.txt"), Access::Read)]);
+
+        let absolute = targets(
+            &call("write_file", path("/tmp/out.txt")),
+            &sandbox.context(),
+        );
+        assert_eq!(absolute, vec![("/tmp/out.txt".to_string(), Access::Write)]);
+    }
+
+    #[test]
+    fn lists_every_path_a_call_touches() {
+        let sandbox = Sandbox::new("tools");
+        let context = sandbox.context();
+        let rename = call(
+            "rename_file",
+            ToolParameters {
+                new_name: Some("b.txt".to_string()),
+                ..path("a.txt")
+            },
+        );
+        assert_eq!(
+            targets(&rename, &context),
+            vec![
+                (at(&sandbox, "a.txt"), Access::Write),
+                (at(&sandbox, "b.txt"), Access::Write),
+            ]
+        );
+
+        let create = call(
+            "create_directory",
+            ToolParameters {
+                name: Some("new".to_string()),
+                ..path("parent")
+            },
+        );
+        assert_eq!(
+            targets(&create, &context),
+            vec![(at(&sandbox, "parent/new"), Access::Write)]
+        );
+
+        let copy = call(
+            "copy_file",
+            ToolParameters {
+                destination_path: Some("backup".to_string()),
+                ..path("a.txt")
+            },
+        );
+        assert_eq!(
+            targets(&copy, &context),
+            vec![
+                (at(&sandbox, "a.txt"), Access::Read),
+                (at(&sandbox, "backup"), Access::Write)
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_tools_are_assumed_to_write_what_they_name() {
+        let sandbox = Sandbox::new("tools");
+        let unknown = call(
+            "shred",
+            ToolParameters {
+                from: Some("a".to_string()),
+                to: Some("b".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            targets(&unknown, &sandbox.context()),
+            vec![
+                (at(&sandbox, "a"), Access::Write),
+                (at(&sandbox, "b"), Access::Write)
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_parameters_are_left_out() {
+        let sandbox = Sandbox::new("tools");
+        let rename = call("rename_file", ToolParameters::default());
+        assert!(targets(&rename, &sandbox.context()).is_empty());
+    }
+
+    #[test]
+    fn allowed_calls_pass_and_denied_calls_do_not() {
+        let sandbox = Sandbox::new("tools");
+        let audit = audit(&sandbox);
+        let read = call("read_file", path("notes.txt"));
+        assert!(authorize(&decided(Decision::Allow), &audit, &read, &sandbox.context()).is_ok());
+        let denied = authorize(&decided(Decision::Deny), &audit, &read, &sandbox.context());
+        assert!(matches!(denied, Err(e) if e.contains("test profile")));
+    }
+
+    #[test]
+    fn asked_calls_need_a_recorded_approval() {
+        let sandbox = Sandbox::new("tools");
+        let audit = audit(&sandbox);
+        let delete = call("delete_item", path("notes.txt"));
+        let ask = decided(Decision::Ask);
+        assert!(authorize(&ask, &audit, &delete, &sandbox.context()).is_err());
+
+        let denied = AuditEntry::new(AuditAction::Denied, &delete, &sandbox.context());
+        audit.append(denied);
+        assert!(authorize(&ask, &audit, &delete, &sandbox.context()).is_err());
+    }
+
+    #[test]
+    fn an_approval_runs_its_call_once() {
+        let sandbox = Sandbox::new("tools");
+        let audit = audit(&sandbox);
+        let delete = call("delete_item", path("notes.txt"));
+        let ask = decided(Decision::Ask);
+        audit.append(AuditEntry::new(
+            AuditAction::Approved,
+            &delete,
+            &sandbox.context(),
+        ));
+
+        assert!(authorize(&ask, &audit, &delete, &sandbox.context()).is_ok());
+        let again = authorize(&ask, &audit, &delete, &sandbox.context());
+        assert!(matches!(again, Err(e) if e.contains("not been approved")));
+    }
+
+    #[test]
+    fn racing_executions_share_one_approval() {
+        let sandbox = Sandbox::new("tools");
+        let audit = audit(&sandbox);
+        let delete = call("delete_item", path("notes.txt"));
+        let context = sandbox.context();
+        audit.append(AuditEntry::new(AuditAction::Approved, &delete, &context));
+
+        let ask = decided(Decision::Ask);
+        let passed = std::thread::scope(|scope| {
+            let runs: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| authorize(&ask, &audit, &delete, &context).is_ok()))
+                .collect();
+            runs.into_iter()
+                .map(|run| run.join().unwrap())
+                .filter(|&ok| ok)
+                .count()
+        });
+        assert_eq!(passed, 1);
+    }
+
+    #[test]
+    fn an_approval_does_not_cover_a_call_moved_to_another_folder() {
+        let sandbox = Sandbox::new("tools");
+        let audit = audit(&sandbox);
+        let delete = call("delete_item", path("notes.txt"));
+        audit.append(AuditEntry::new(
+            AuditAction::Approved,
+            &delete,
+            &sandbox.context(),
+        ));
+
+        let elsewhere = ToolContext {
+            current_path: Some(at(&sandbox, "other")),
+        };
+        assert!(authorize(&decided(Decision::Ask), &audit, &delete, &elsewhere).is_err());
+    }
+}
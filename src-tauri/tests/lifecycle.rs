@@ -0,0 +1,151 @@
+#![cfg(unix)]
+
+use fsai_lib::lifecycle::{kill_on_panic, terminate, ProcessGuard};
+use std::io::{BufRead, BufReader};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn spawn_sleeper() -> Child {
+    Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .expect("failed to spawn `sleep`")
+}
+
+/// Waits up to five seconds for the child to exit, returning whether it did.
+fn exits_promptly(child: &mut Child) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if child.try_wait().unwrap().is_some() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    false
+}
+
+#[test]
+fn dropping_the_guard_kills_the_process() {
+    let mut child = spawn_sleeper();
+    let guard = ProcessGuard::default();
+    guard.track(child.id());
+
+    drop(guard);
+
+    assert!(exits_promptly(&mut child), "process outlived its guard");
+}
+
+#[test]
+fn panicking_owner_does_not_leave_an_orphan() {
+    let mut child = spawn_sleeper();
+    let pid = child.id();
+
+    let result = thread::spawn(move || {
+        let guard = ProcessGuard::default();
+        guard.track(pid);
+        panic!("simulated crash while the backend is running");
+    })
+    .join();
+
+    assert!(result.is_err());
+    assert!(exits_promptly(&mut child), "process outlived a panicking owner");
+}
+
+#[test]
+fn released_process_is_left_running() {
+    let mut child = spawn_sleeper();
+    let guard = ProcessGuard::default();
+    guard.track(child.id());
+
+    guard.release();
+    drop(guard);
+
+    assert!(child.try_wait().unwrap().is_none());
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+/// Spawns a process that ignores SIGTERM, once it has started ignoring it.
+fn spawn_stubborn() -> Child {
+    let mut child = Command::new("sh")
+        .args(["-c", "trap '' TERM; echo ready; exec sleep 30"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn `sh`");
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    child
+}
+
+/// Reaps the child on another thread, as the shell plugin does for the sidecar,
+/// and hands back how it exited.
+fn reap(mut child: Child) -> thread::JoinHandle<ExitStatus> {
+    thread::spawn(move || child.wait().unwrap())
+}
+
+#[test]
+fn terminate_lets_the_process_exit_on_sigterm() {
+    let child = spawn_sleeper();
+    let pid = child.id();
+    let status = reap(child);
+
+    let mut killed = false;
+    terminate(pid, Duration::from_secs(5), || {
+        killed = true;
+        Ok::<_, ()>(())
+    })
+    .unwrap();
+
+    assert!(!killed, "a process that honours SIGTERM was force-killed");
+    assert_eq!(status.join().unwrap().signal(), Some(libc::SIGTERM));
+}
+
+#[test]
+fn terminate_kills_a_process_that_ignores_sigterm() {
+    let child = spawn_stubborn();
+    let pid = child.id();
+    let status = reap(child);
+    let guard = ProcessGuard::default();
+    guard.track(pid);
+
+    let grace = Duration::from_millis(300);
+    let started = Instant::now();
+    terminate(pid, grace, || {
+        guard.kill();
+        Ok::<_, ()>(())
+    })
+    .unwrap();
+
+    assert!(started.elapsed() >= grace, "killed before the grace period was up");
+    assert_eq!(status.join().unwrap().signal(), Some(libc::SIGKILL));
+}
+
+// One test for both cases: the hook stays installed for the rest of the run, and
+// a second hook would see the other test's panics too
+#[test]
+fn only_a_panic_on_the_main_thread_kills_the_tracked_process() {
+    let mut child = spawn_sleeper();
+    let guard = Arc::new(ProcessGuard::default());
+    guard.track(child.id());
+    kill_on_panic(&guard);
+
+    let worker = thread::spawn(|| panic!("simulated crash on a worker thread")).join();
+    assert!(worker.is_err());
+    assert!(child.try_wait().unwrap().is_none(), "a worker panic killed the process");
+
+    let main = thread::Builder::new()
+        .name("main".to_string())
+        .spawn(|| panic!("simulated crash on the main thread"))
+        .unwrap()
+        .join();
+    assert!(main.is_err());
+    assert!(exits_promptly(&mut child), "process outlived a main thread panic");
+    assert_eq!(guard.pid(), None);
+}
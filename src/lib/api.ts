@@ -13,6 +13,293 @@ interface FileItem {
   isFile: boolean;
   path: string;
   size?: number;
+  modified?: number | null;
+  hidden?: boolean;
+  isSymlink?: boolean;
+  symlinkTarget?: string | null;
+}
+
+export interface DirEntry {
+  name: string;
+  path: string;
+  kind: 'file' | 'directory' | 'other';
+  size: number;
+  modified: number | null;
+  permissions: { readonly: boolean; mode: number | null };
+  isSymlink: boolean;
+  symlinkTarget: string | null;
+  hidden: boolean;
+}
+
+export interface ListOptions {
+  sortBy?: 'name' | 'size' | 'modified' | 'kind';
+  descending?: boolean;
+  directoriesFirst?: boolean;
+  showHidden?: boolean;
+  offset?: number;
+  limit?: number;
+}
+
+interface DirListing {
+  path: string;
+  entries: DirEntry[];
+  total: number;
+  hasMore: boolean;
+}
+
+export interface ReadOptions {
+  offset?: number;
+  length?: number;
+  startLine?: number;
+  lineCount?: number;
+  maxBytes?: number;
+}
+
+export interface FileContent {
+  path: string;
+  content: string | null;
+  encoding: 'utf8' | 'utf8Bom' | 'utf16Le' | 'utf16Be' | 'latin1' | 'binary';
+  isBinary: boolean;
+  size: number;
+  start: number;
+  end: number;
+  startLine: number | null;
+  endLine: number | null;
+  truncated: boolean;
+}
+
+export interface FileType {
+  path: string;
+  mimeType: string;
+  category: 'image' | 'video' | 'audio' | 'pdf' | 'archive' | 'code' | 'document' | 'text' | 'other';
+  isText: boolean;
+  encoding: FileContent['encoding'];
+  size: number;
+  uploadable: boolean;
+}
+
+export interface WriteOptions {
+  createOnly?: boolean;
+  expectedHash?: string;
+  createDirs?: boolean;
+}
+
+export interface WriteResult {
+  path: string;
+  bytesWritten: number;
+  hash: string;
+  created: boolean;
+}
+
+export interface TreeOptions {
+  maxDepth?: number;
+  maxEntries?: number;
+  respectIgnore?: boolean;
+  showHidden?: boolean;
+  followSymlinks?: boolean;
+  format?: 'ascii' | 'json' | 'both';
+}
+
+export interface TreeNode {
+  name: string;
+  kind: 'file' | 'directory' | 'other';
+  size: number | null;
+  symlinkTarget: string | null;
+  children: TreeNode[];
+  unexpanded: 'depthLimit' | 'entryLimit' | 'cycle' | 'symlink' | 'unreadable' | null;
+  omitted: number;
+}
+
+export interface Tree {
+  path: string;
+  root: TreeNode | null;
+  ascii: string | null;
+  entries: number;
+  truncated: boolean;
+}
+
+export interface AccessRoot {
+  // May start with ~ for the home directory
+  path: string;
+  readOnly?: boolean;
+}
+
+export interface AccessPolicy {
+  roots: AccessRoot[];
+  // Globs that stay off limits inside the roots, including everything below them
+  deny: string[];
+}
+
+export type Decision = 'allow' | 'ask' | 'deny';
+
+export interface PermissionRule {
+  // Globs like the access policy's deny patterns; empty matches any path
+  paths?: string[];
+  minSize?: number;
+  maxSize?: number;
+  decision: Decision;
+}
+
+export interface ToolPermission {
+  decision: Decision;
+  // Checked in order; the first match decides
+  rules?: PermissionRule[];
+}
+
+export interface PermissionProfiles {
+  // Keyed by tool type, e.g. move_item
+  tools: Record<string, ToolPermission>;
+  default: Decision;
+}
+
+export interface RiskAssessment {
+  // 0 to 100
+  score: number;
+  level: 'low' | 'medium' | 'high';
+  reasons: string[];
+}
+
+export interface Permission {
+  decision: Decision;
+  reason: string;
+}
+
+export interface AuditRecord {
+  seq: number;
+  // Milliseconds since the Unix epoch
+  timestamp: number;
+  action: 'proposed' | 'approved' | 'denied' | 'executed' | 'recovered';
+  toolCallId: string;
+  toolType: string;
+  description: string;
+  parameters: Record<string, unknown>;
+  paths: string[];
+  approver: 'user' | 'profile' | null;
+  reason: string | null;
+  result: unknown;
+  error: string | null;
+  prevHash: string;
+  hash: string;
+}
+
+export interface AuditQuery {
+  since?: number;
+  until?: number;
+  // Records touching this path or anything below it
+  path?: string;
+  tool?: string;
+  limit?: number;
+}
+
+export interface AuditVerification {
+  records: number;
+  valid: boolean;
+  brokenAt: number | null;
+  error: string | null;
+}
+
+export interface FsChange {
+  kind: 'created' | 'modified' | 'removed' | 'renamed';
+  path: string;
+  oldPath: string | null;
+}
+
+export interface FsChanged {
+  path: string;
+  changes: FsChange[];
+  rescan: boolean;
+}
+
+export type ConflictPolicy = 'skip' | 'overwrite' | 'rename' | 'ask';
+
+export interface CopyOptions {
+  id?: string;
+  conflict?: ConflictPolicy;
+  verify?: boolean;
+}
+
+export interface CopyResult {
+  id: string;
+  source: string;
+  destination: string;
+  filesCopied: number;
+  filesSkipped: number;
+  bytesCopied: number;
+}
+
+export interface CopyProgress {
+  id: string;
+  source: string;
+  destination: string;
+  current: string;
+  fileBytesDone: number;
+  fileBytesTotal: number;
+  bytesDone: number;
+  bytesTotal: number;
+  filesDone: number;
+  filesTotal: number;
+}
+
+export interface CopyConflict {
+  id: string;
+  source: string;
+  destination: string;
+  sourceIsDir: boolean;
+  destinationIsDir: boolean;
+}
+
+export interface ConflictAnswer {
+  resolution: 'skip' | 'overwrite' | 'rename' | 'cancel';
+  applyToAll?: boolean;
+}
+
+export interface MoveResult {
+  source: string;
+  destination: string;
+  method: 'rename' | 'copy';
+}
+
+export interface MoveProgress {
+  source: string;
+  destination: string;
+  bytesDone: number;
+  bytesTotal: number;
+  filesDone: number;
+  filesTotal: number;
+}
+
+export type JobSpec =
+  | { kind: 'copy'; source: string; destination: string; conflict?: Exclude<ConflictPolicy, 'ask'> }
+  | { kind: 'move'; source: string; destination: string }
+  | { kind: 'delete'; path: string }
+  | { kind: 'index'; path: string };
+
+export type JobState = 'queued' | 'running' | 'paused' | 'completed' | 'failed' | 'cancelled';
+
+export type Job = JobSpec & {
+  id: number;
+  state: JobState;
+  progress: {
+    bytesDone: number;
+    bytesTotal: number;
+    itemsDone: number;
+    itemsTotal: number;
+    current: string | null;
+  };
+  result: any | null;
+  error: string | null;
+  createdAt: number | null;
+  startedAt: number | null;
+  finishedAt: number | null;
+};
+
+export interface TrashItem {
+  id: string;
+  name: string;
+  originalPath: string;
+  deletedAt: number | null;
+  isDirectory: boolean;
+  size: number | null;
 }
 
 interface AIContext {
@@ -45,9 +332,15 @@ interface ToolCall {
     sourcePath?: string;
     destinationPath?: string;
     newName?: string;
+    startLine?: number;
+    lineCount?: number;
+    expectedHash?: string;
+    createOnly?: boolean;
   };
   description: string;
-  risk: 'low' | 'high';
+  // Attached by evaluateToolCalls
+  risk?: RiskAssessment;
+  permission?: Permission;
 }
 
 interface AIRequest {
@@ -66,6 +359,28 @@ export interface Settings {
   multimediaSupport: boolean;
 }
 
+export interface BackendError {
+  kind: 'missingBinary' | 'spawnFailed' | 'handshakeTimeout' | 'healthCheckFailed' | 'fatal' | 'prematureExit';
+  message: string;
+}
+
+export type BackendStatus = 'starting' | 'ready' | 'unhealthy' | 'stopped';
+
+export interface InstanceLaunch {
+  args: string[];
+  cwd: string | null;
+}
+
+export type LogLevel = 'info' | 'warn' | 'error';
+
+export interface LogEntry {
+  seq: number;
+  timestamp: number;
+  level: LogLevel;
+  source: 'stdout' | 'stderr' | 'supervisor';
+  message: string;
+}
+
 interface ApiRequestOptions extends Omit<RequestInit, 'body'> {
   body?: any;
 }
@@ -74,25 +389,66 @@ class FSaiAPI {
   private static backendPort: number | null = null;
   private static backendReady = false;
   private static readyPromise: Promise<void> | null = null;
+  private static authToken: Promise<string> | null = null;
+  // Settles the current readyPromise; replaced on every (re)start
+  private static resolveReady: (() => void) | null = null;
+  private static lifecycleListening = false;
 
   static async initialize(): Promise<void> {
     if (this.readyPromise) {
       return this.readyPromise;
     }
 
+    this.listenForLifecycle();
     this.readyPromise = new Promise((resolve) => {
-      listen('backend-ready', (event) => {
-        this.backendPort = event.payload as number;
-        this.backendReady = true;
-        resolve();
-      });
-
+      this.resolveReady = resolve;
       this.pollForBackend().then(resolve);
     });
 
     return this.readyPromise;
   }
 
+  // Registered once for the app's lifetime, so restarts do not stack up listeners
+  private static listenForLifecycle() {
+    if (this.lifecycleListening) {
+      return;
+    }
+    this.lifecycleListening = true;
+
+    listen('backend-exited', () => {
+      // The supervisor will relaunch on a new port; wait for the next backend-ready
+      this.backendPort = null;
+      this.backendReady = false;
+      this.readyPromise = null;
+    });
+
+    listen<number>('backend-ready', (event) => {
+      this.backendPort = event.payload;
+      this.backendReady = true;
+      this.resolveReady?.();
+    });
+  }
+
+  static onBackendFailed(callback: (error: BackendError) => void) {
+    return listen<BackendError>('backend-failed', (event) => callback(event.payload));
+  }
+
+  static async getBackendStatus(): Promise<BackendStatus> {
+    return invoke<BackendStatus>('get_backend_status');
+  }
+
+  static onSecondInstance(callback: (launch: InstanceLaunch) => void) {
+    return listen<InstanceLaunch>('second-instance', (event) => callback(event.payload));
+  }
+
+  static async getBackendLogs(since?: number, level?: LogLevel): Promise<LogEntry[]> {
+    return invoke<LogEntry[]>('get_backend_logs', { since, level });
+  }
+
+  static onBackendLog(callback: (entry: LogEntry) => void) {
+    return listen<LogEntry>('backend-log', (event) => callback(event.payload));
+  }
+
   private static async pollForBackend(): Promise<void> {
     while (!this.backendPort) {
       try {
@@ -114,13 +470,22 @@ class FSaiAPI {
     }
   }
 
+  private static getAuthToken(): Promise<string> {
+    if (!this.authToken) {
+      this.authToken = invoke<string>('get_backend_token');
+    }
+    return this.authToken;
+  }
+
   private static async request<T>(url: string, options: RequestInit = {}): Promise<T> {
     await this.ensureBackendReady();
+    const token = await this.getAuthToken();
     
     const response = await fetch(url, {
         ...options,
         headers: {
             'Content-Type': 'application/json',
+            'X-FSai-Token': token,
             ...options.headers,
         },
     });
@@ -140,32 +505,201 @@ class FSaiAPI {
     return this.request(url, fetchOptions);
   }
 
-  static async listDirectory(path: string): Promise<ApiResponse<FileItem[]>> {
-    return this.makeApiRequest('/fs/list', { method: 'POST', body: { path } });
+  private static toFileItem(entry: DirEntry): FileItem {
+    return {
+      name: entry.name,
+      isDirectory: entry.kind === 'directory',
+      isFile: entry.kind === 'file',
+      path: entry.path,
+      size: entry.kind === 'file' ? entry.size : undefined,
+      modified: entry.modified,
+      hidden: entry.hidden,
+      isSymlink: entry.isSymlink,
+      symlinkTarget: entry.symlinkTarget,
+    };
+  }
+
+  // Listed natively so browsing keeps working while the backend is down
+  static async listDirectory(path: string, options?: ListOptions): Promise<ApiResponse<FileItem[]>> {
+    try {
+      const listing = await invoke<DirListing>('list_directory', { path, options });
+      return { success: true, data: listing.entries.map((entry) => this.toFileItem(entry)) };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
+  }
+
+  static async readFile(path: string, options?: ReadOptions): Promise<ApiResponse<{ content: string; path: string }>> {
+    try {
+      const file = await invoke<FileContent>('read_file', { path, options });
+      if (file.isBinary || file.content === null) {
+        return { success: false, error: 'File appears to be binary and cannot be displayed as text' };
+      }
+      return { success: true, data: { content: file.content, path: file.path } };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
   }
 
-  static async readFile(path: string): Promise<ApiResponse<{ content: string; path: string }>> {
-    return this.makeApiRequest('/fs/read', { method: 'POST', body: { path } });
+  static async writeFile(path: string, content: string, options?: WriteOptions): Promise<ApiResponse<WriteResult>> {
+    try {
+      const result = await invoke<WriteResult>('write_file', { path, content, options });
+      return { success: true, data: result };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
   }
 
-  static async writeFile(path: string, content: string): Promise<ApiResponse<{ message: string; path: string }>> {
-    return this.makeApiRequest('/fs/write', { method: 'POST', body: { path, content } });
+  static async getTree(path: string, options?: TreeOptions): Promise<ApiResponse<Tree>> {
+    try {
+      const tree = await invoke<Tree>('get_tree', { path, options });
+      return { success: true, data: tree };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
   }
 
+  // Moves to the trash where the platform has one, deletes permanently otherwise
   static async deleteFile(path: string): Promise<ApiResponse<{ message: string; path: string }>> {
-    return this.makeApiRequest('/fs/delete', { method: 'POST', body: { path } });
+    try {
+      const item = await invoke<TrashItem | null>('trash_item', { path });
+      if (item) {
+        return { success: true, data: { message: 'Moved to the trash', path: item.originalPath } };
+      }
+      await invoke('delete_permanently', { path });
+      return { success: true, data: { message: 'Deleted permanently', path } };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
+  }
+
+  static async moveItem(source: string, destination: string): Promise<ApiResponse<MoveResult>> {
+    try {
+      return { success: true, data: await invoke<MoveResult>('move_item', { source, destination }) };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
+  }
+
+  static onMoveProgress(callback: (progress: MoveProgress) => void) {
+    return listen<MoveProgress>('move-progress', (event) => callback(event.payload));
+  }
+
+  static async submitJob(spec: JobSpec): Promise<Job> {
+    return invoke<Job>('submit_job', { spec });
+  }
+
+  static async listJobs(): Promise<Job[]> {
+    return invoke<Job[]>('list_jobs');
+  }
+
+  static async cancelJob(id: number): Promise<void> {
+    return invoke('cancel_job', { id });
+  }
+
+  static async pauseJob(id: number): Promise<void> {
+    return invoke('pause_job', { id });
+  }
+
+  static async resumeJob(id: number): Promise<void> {
+    return invoke('resume_job', { id });
+  }
+
+  static onJobUpdated(callback: (job: Job) => void) {
+    return listen<Job>('job-updated', (event) => callback(event.payload));
+  }
+
+  static async getAccessPolicy(): Promise<AccessPolicy> {
+    return invoke<AccessPolicy>('get_access_policy');
+  }
+
+  static async setAccessPolicy(policy: AccessPolicy): Promise<AccessPolicy> {
+    return invoke<AccessPolicy>('set_access_policy', { policy });
+  }
+
+  static async getPermissionProfiles(): Promise<PermissionProfiles> {
+    return invoke<PermissionProfiles>('get_permission_profiles');
+  }
+
+  static async setPermissionProfiles(profiles: PermissionProfiles): Promise<PermissionProfiles> {
+    return invoke<PermissionProfiles>('set_permission_profiles', { profiles });
+  }
+
+  // Attaches the permission profiles' decision and a risk assessment to each proposed call
+  static async evaluateToolCalls(toolCalls: ToolCall[], context?: AIContext): Promise<ToolCall[]> {
+    const [permissions, risks] = await Promise.all([
+      invoke<Permission[]>('evaluate_tool_calls', { toolCalls, context }),
+      invoke<RiskAssessment[]>('assess_tool_calls', { toolCalls, context })
+    ]);
+    return toolCalls.map((toolCall, i) => ({ ...toolCall, permission: permissions[i], risk: risks[i] }));
+  }
+
+  // Returns the resolved path that fs-changed events for this directory carry
+  static async watchPath(path: string): Promise<string> {
+    return invoke<string>('watch_path', { path });
+  }
+
+  static async unwatchPath(path: string): Promise<boolean> {
+    return invoke<boolean>('unwatch_path', { path });
+  }
+
+  static onFsChanged(callback: (change: FsChanged) => void) {
+    return listen<FsChanged>('fs-changed', (event) => callback(event.payload));
+  }
+
+  static async listTrash(): Promise<TrashItem[]> {
+    return invoke<TrashItem[]>('list_trash');
+  }
+
+  static async restoreFromTrash(id: string): Promise<string> {
+    return invoke<string>('restore_from_trash', { id });
+  }
+
+  static async emptyTrash(ids?: string[]): Promise<number> {
+    return invoke<number>('empty_trash', { ids });
   }
 
   static async renameFile(path: string, newName: string): Promise<ApiResponse<{ message: string; path: string }>> {
-    return this.makeApiRequest('/fs/rename', { method: 'POST', body: { path, newName } });
+    try {
+      const renamed = await invoke<string>('rename_item', { path, newName });
+      return { success: true, data: { message: `Renamed to '${newName}'`, path: renamed } };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
   }
 
-  static async copyFile(path: string, destinationPath: string): Promise<ApiResponse<{ message: string; path: string }>> {
-    return this.makeApiRequest('/fs/copy', { method: 'POST', body: { path, destinationPath } });
+  static async copyFile(path: string, destinationPath: string, options?: CopyOptions): Promise<ApiResponse<CopyResult>> {
+    try {
+      const result = await invoke<CopyResult>('copy_item', { source: path, destination: destinationPath, options });
+      return { success: true, data: result };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
+  }
+
+  static async cancelCopy(id: string): Promise<void> {
+    return invoke('cancel_copy', { id });
+  }
+
+  static async resolveCopyConflict(id: string, answer: ConflictAnswer): Promise<void> {
+    return invoke('resolve_copy_conflict', { id, answer });
+  }
+
+  static onCopyProgress(callback: (progress: CopyProgress) => void) {
+    return listen<CopyProgress>('copy-progress', (event) => callback(event.payload));
+  }
+
+  static onCopyConflict(callback: (conflict: CopyConflict) => void) {
+    return listen<CopyConflict>('copy-conflict', (event) => callback(event.payload));
   }
 
   static async createDirectory(path: string): Promise<ApiResponse<{ message: string; path: string }>> {
-    return this.makeApiRequest('/fs/mkdir', { method: 'POST', body: { path } });
+    try {
+      const created = await invoke<string>('create_directory', { path });
+      return { success: true, data: { message: 'Directory created successfully', path: created } };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
   }
 
   static async healthCheck(): Promise<ApiResponse> {
@@ -182,7 +716,25 @@ class FSaiAPI {
   }
 
   static async executeToolCall(toolCall: ToolCall, context?: AIContext): Promise<ApiResponse> {
-    return this.makeApiRequest('/ai/execute-tool', { method: 'POST', body: { toolCall, context } });
+    try {
+      return { success: true, data: await invoke('execute_tool', { toolCall, context }) };
+    } catch (error) {
+      return { success: false, error: `${error}` };
+    }
+  }
+
+  // Records the user's answer in the confirmation bar; decisions made by the
+  // permission profiles are recorded when the calls are evaluated
+  static async recordToolDecision(toolCall: ToolCall, approved: boolean, context?: AIContext): Promise<void> {
+    return invoke('record_tool_decision', { toolCall, context, approved });
+  }
+
+  static async queryAuditLog(query?: AuditQuery): Promise<AuditRecord[]> {
+    return invoke<AuditRecord[]>('query_audit_log', { query });
+  }
+
+  static async verifyAuditLog(): Promise<AuditVerification> {
+    return invoke<AuditVerification>('verify_audit_log');
   }
 
   static async processFollowUp(originalPrompt: string, context: AIContext, toolResults: any[]): Promise<ApiResponse<AIResponse>> {
@@ -192,15 +744,22 @@ class FSaiAPI {
     });
   }
 
-  static async checkFileType(path: string): Promise<ApiResponse<{ isText: boolean; reason?: string }>> {
+  static async detectFileType(path: string): Promise<ApiResponse<FileType>> {
     try {
-      return await this.makeApiRequest('/fs/check-type', { method: 'POST', body: { path } });
+      const fileType = await invoke<FileType>('detect_file_type', { path });
+      return { success: true, data: fileType };
     } catch (error) {
-      return {
-        success: false,
-        error: `API call failed: ${error}`
-      };
+      return { success: false, error: `${error}` };
+    }
+  }
+
+  static async checkFileType(path: string): Promise<ApiResponse<{ isText: boolean; reason?: string }>> {
+    const result = await this.detectFileType(path);
+    if (!result.success || !result.data) {
+      return { success: false, error: result.error };
     }
+    const { isText, mimeType } = result.data;
+    return { success: true, data: { isText, reason: isText ? undefined : `Binary file (${mimeType})` } };
   }
 
   static async getSettings(): Promise<ApiResponse<Settings>> {
@@ -216,4 +775,4 @@ class FSaiAPI {
 }
 
 export default FSaiAPI;
-export type { ApiResponse, FileItem, AIContext, ToolCall, AIRequest, AIResponse }; 
\ No newline at end of file
+export type { ApiResponse, FileItem, AIContext, ToolCall, AIRequest, AIResponse }; 
\ No newline at end of file